@@ -0,0 +1,339 @@
+-- 將舊版資料庫升級至 schema.sql 的結構，可重複執行
+
+ALTER TABLE
+  public.occupy_table
+  ADD COLUMN IF NOT EXISTS world_id integer NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS team_id integer NULL,
+  ADD COLUMN IF NOT EXISTS battle_team_id integer NULL,
+  ADD COLUMN IF NOT EXISTS battle_time timestamp with time zone NULL,
+  ADD COLUMN IF NOT EXISTS battle_channel_id bigint NULL,
+  ADD COLUMN IF NOT EXISTS battle_reminded boolean NOT NULL DEFAULT FALSE,
+  ADD COLUMN IF NOT EXISTS battle_thread_id bigint NULL,
+  ADD COLUMN IF NOT EXISTS due_notified_at timestamp with time zone NULL,
+  ADD COLUMN IF NOT EXISTS shield_until timestamp with time zone NULL;
+
+-- 礦點改以伺服器、世界與礦點區分
+ALTER TABLE
+  public.occupy_table
+DROP
+  CONSTRAINT IF EXISTS "Occupy_Table_pkey",
+ADD
+  CONSTRAINT "Occupy_Table_pkey" PRIMARY KEY (guild_id, world_id, ore_point_id);
+
+CREATE TABLE IF NOT EXISTS
+  public.battle_notify_role (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    role_id bigint NOT NULL,
+    CONSTRAINT "Battle_Notify_Role_pkey" PRIMARY KEY (guild_id, world_id)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.world (
+    id serial NOT NULL,
+    guild_id bigint NOT NULL,
+    name character varying(255) NOT NULL,
+    CONSTRAINT "World_pkey" PRIMARY KEY (id),
+    CONSTRAINT "World_name_key" UNIQUE (guild_id, name)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.channel_world (
+    channel_id bigint NOT NULL,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL,
+    CONSTRAINT "Channel_World_pkey" PRIMARY KEY (channel_id)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.occupy_history (
+    id integer NOT NULL GENERATED BY DEFAULT AS IDENTITY,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    team_id integer NULL,
+    season_id integer NULL,
+    ore_point_id integer NOT NULL,
+    user_id bigint NOT NULL,
+    target_user_id bigint NULL,
+    action character varying(32) NOT NULL,
+    reverted boolean NOT NULL DEFAULT FALSE,
+    time timestamp with time zone NOT NULL DEFAULT now(),
+    CONSTRAINT "Occupy_History_pkey" PRIMARY KEY (id)
+  );
+
+ALTER TABLE
+  public.occupy_history
+  ADD COLUMN IF NOT EXISTS world_id integer NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS team_id integer NULL,
+  ADD COLUMN IF NOT EXISTS reverted boolean NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS
+  public.season (
+    id serial NOT NULL,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    number integer NOT NULL,
+    start_time timestamp with time zone NOT NULL,
+    end_time timestamp with time zone NULL,
+    CONSTRAINT "Season_pkey" PRIMARY KEY (id)
+  );
+
+ALTER TABLE
+  public.season
+  ADD COLUMN IF NOT EXISTS world_id integer NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS
+  public.season_schedule (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    channel_id bigint NOT NULL,
+    start_time timestamp with time zone NOT NULL,
+    CONSTRAINT "Season_Schedule_pkey" PRIMARY KEY (guild_id, world_id)
+  );
+
+ALTER TABLE
+  public.season_schedule
+  ADD COLUMN IF NOT EXISTS world_id integer NOT NULL DEFAULT 0;
+
+ALTER TABLE
+  public.season_schedule
+DROP
+  CONSTRAINT IF EXISTS "Season_Schedule_pkey",
+ADD
+  CONSTRAINT "Season_Schedule_pkey" PRIMARY KEY (guild_id, world_id);
+
+CREATE TABLE IF NOT EXISTS
+  public.occupy_archive (
+    season_id integer NOT NULL,
+    ore_point_id integer NOT NULL,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    user_id bigint NOT NULL,
+    due_time timestamp with time zone NOT NULL,
+    battle_user_id bigint NULL,
+    team_id integer NULL,
+    CONSTRAINT "Occupy_Archive_pkey" PRIMARY KEY (season_id, ore_point_id)
+  );
+
+ALTER TABLE
+  public.occupy_archive
+  ADD COLUMN IF NOT EXISTS world_id integer NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS team_id integer NULL;
+
+CREATE TABLE IF NOT EXISTS
+  public.team (
+    id serial NOT NULL,
+    guild_id bigint NOT NULL,
+    name character varying(255) NOT NULL,
+    role_id bigint NOT NULL,
+    CONSTRAINT "Team_pkey" PRIMARY KEY (id),
+    CONSTRAINT "Team_role_key" UNIQUE (guild_id, role_id)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.guild_config (
+    guild_id bigint NOT NULL,
+    proposal_timeout_hours integer NOT NULL DEFAULT 24,
+    queue_counts_limit boolean NOT NULL DEFAULT FALSE,
+    challenge_channel_id bigint NULL,
+    notify_channel_id bigint NULL,
+    notify_events integer NOT NULL DEFAULT 2,
+    locale character varying(8) NULL,
+    log_retention_days integer NULL,
+    frozen_at timestamp with time zone NULL,
+    shield_hours integer NULL,
+    point_cooldown_hours integer NULL,
+    player_cooldown_hours integer NULL,
+    vacation_max_days integer NULL,
+    CONSTRAINT "Guild_Config_pkey" PRIMARY KEY (guild_id)
+  );
+
+ALTER TABLE
+  public.guild_config
+  ADD COLUMN IF NOT EXISTS queue_counts_limit boolean NOT NULL DEFAULT FALSE,
+  ADD COLUMN IF NOT EXISTS challenge_channel_id bigint NULL,
+  ADD COLUMN IF NOT EXISTS notify_channel_id bigint NULL,
+  ADD COLUMN IF NOT EXISTS notify_events integer NOT NULL DEFAULT 2,
+  ADD COLUMN IF NOT EXISTS locale character varying(8) NULL,
+  ADD COLUMN IF NOT EXISTS log_retention_days integer NULL,
+  ADD COLUMN IF NOT EXISTS frozen_at timestamp with time zone NULL,
+  ADD COLUMN IF NOT EXISTS shield_hours integer NULL,
+  ADD COLUMN IF NOT EXISTS point_cooldown_hours integer NULL,
+  ADD COLUMN IF NOT EXISTS player_cooldown_hours integer NULL,
+  ADD COLUMN IF NOT EXISTS vacation_max_days integer NULL;
+
+CREATE TABLE IF NOT EXISTS
+  public.battle_proposal (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL,
+    ore_point_id integer NOT NULL,
+    channel_id bigint NOT NULL,
+    slots timestamp with time zone[] NOT NULL,
+    created_at timestamp with time zone NOT NULL DEFAULT now(),
+    CONSTRAINT "Battle_Proposal_pkey" PRIMARY KEY (guild_id, world_id, ore_point_id)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.challenge_queue (
+    id integer NOT NULL GENERATED BY DEFAULT AS IDENTITY,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL,
+    ore_point_id integer NOT NULL,
+    user_id bigint NOT NULL,
+    team_id integer NULL,
+    queued_at timestamp with time zone NOT NULL DEFAULT now(),
+    CONSTRAINT "Challenge_Queue_pkey" PRIMARY KEY (id),
+    CONSTRAINT "Challenge_Queue_user_key" UNIQUE (guild_id, world_id, ore_point_id, user_id)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.ore_type_notify_role (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    ore_type integer NOT NULL,
+    role_id bigint NOT NULL,
+    CONSTRAINT "Ore_Type_Notify_Role_pkey" PRIMARY KEY (guild_id, world_id, ore_type)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.webhook (
+    id serial NOT NULL,
+    guild_id bigint NOT NULL,
+    url character varying(2048) NOT NULL,
+    secret character varying(255) NOT NULL,
+    CONSTRAINT "Webhook_pkey" PRIMARY KEY (id)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.api_token (
+    guild_id bigint NOT NULL,
+    name character varying(255) NOT NULL,
+    token_hash character(64) NOT NULL,
+    created_at timestamp with time zone NOT NULL DEFAULT now(),
+    CONSTRAINT "Api_Token_pkey" PRIMARY KEY (guild_id, name),
+    CONSTRAINT "Api_Token_hash_key" UNIQUE (token_hash)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.game_server (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    rest_url character varying(2048) NULL,
+    rest_password character varying(255) NULL,
+    rcon_address character varying(255) NULL,
+    rcon_password character varying(255) NULL,
+    require_online boolean NOT NULL DEFAULT FALSE,
+    CONSTRAINT "Game_Server_pkey" PRIMARY KEY (guild_id, world_id)
+  );
+
+ALTER TABLE
+  public.game_server
+  ADD COLUMN IF NOT EXISTS rcon_address character varying(255) NULL,
+  ADD COLUMN IF NOT EXISTS rcon_password character varying(255) NULL;
+
+CREATE TABLE IF NOT EXISTS
+  public.player_link (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    user_id bigint NOT NULL,
+    character_name character varying(255) NOT NULL,
+    player_uid character varying(64) NULL,
+    steam_id character varying(64) NULL,
+    verified boolean NOT NULL DEFAULT FALSE,
+    CONSTRAINT "Player_Link_pkey" PRIMARY KEY (guild_id, world_id, user_id),
+    CONSTRAINT "Player_Link_name_key" UNIQUE (guild_id, world_id, character_name)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.bot_role (
+    guild_id bigint NOT NULL,
+    role_id bigint NOT NULL,
+    level integer NOT NULL,
+    CONSTRAINT "Bot_Role_pkey" PRIMARY KEY (guild_id, role_id)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.member_channel (
+    guild_id bigint NOT NULL,
+    channel_id bigint NOT NULL,
+    CONSTRAINT "Member_Channel_pkey" PRIMARY KEY (guild_id, channel_id)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.translation (
+    category character varying(16) NOT NULL,
+    id integer NOT NULL,
+    locale character varying(8) NOT NULL,
+    name character varying(255) NOT NULL,
+    CONSTRAINT "Translation_pkey" PRIMARY KEY (category, id, locale)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.command_log (
+    id bigint NOT NULL GENERATED BY DEFAULT AS IDENTITY,
+    guild_id bigint NULL,
+    channel_id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    content text NOT NULL,
+    command character varying(100) NULL,
+    ore_point_id integer NULL,
+    success boolean NULL,
+    error text NULL,
+    time timestamp with time zone NOT NULL DEFAULT now(),
+    CONSTRAINT "Command_Log_pkey" PRIMARY KEY (id)
+  );
+
+CREATE INDEX IF NOT EXISTS command_log_guild_time ON public.command_log (guild_id, time);
+
+CREATE TABLE IF NOT EXISTS
+  public.admin_action (
+    id integer NOT NULL GENERATED BY DEFAULT AS IDENTITY,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    season_id integer NULL,
+    ore_point_id integer NOT NULL,
+    user_id bigint NOT NULL,
+    action character varying(32) NOT NULL,
+    previous_user_id bigint NULL,
+    previous_due_time timestamp with time zone NULL,
+    previous_team_id integer NULL,
+    previous_battle_user_id bigint NULL,
+    previous_battle_team_id integer NULL,
+    previous_battle_time timestamp with time zone NULL,
+    previous_battle_thread_id bigint NULL,
+    previous_shield_until timestamp with time zone NULL,
+    current_user_id bigint NULL,
+    current_due_time timestamp with time zone NULL,
+    current_battle_user_id bigint NULL,
+    reverted boolean NOT NULL DEFAULT FALSE,
+    time timestamp with time zone NOT NULL DEFAULT now(),
+    CONSTRAINT "Admin_Action_pkey" PRIMARY KEY (id)
+  );
+
+ALTER TABLE
+  public.admin_action
+  ADD COLUMN IF NOT EXISTS season_id integer NULL,
+  ADD COLUMN IF NOT EXISTS previous_shield_until timestamp with time zone NULL;
+
+CREATE INDEX IF NOT EXISTS admin_action_guild_time ON public.admin_action (guild_id, time);
+
+CREATE TABLE IF NOT EXISTS
+  public.vacation (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    user_id bigint NOT NULL,
+    end_time timestamp with time zone NOT NULL,
+    delegate_user_id bigint NULL,
+    CONSTRAINT "Vacation_pkey" PRIMARY KEY (guild_id, world_id, user_id)
+  );
+
+CREATE TABLE IF NOT EXISTS
+  public.freeze_history (
+    guild_id bigint NOT NULL,
+    start_time timestamp with time zone NOT NULL,
+    end_time timestamp with time zone NOT NULL
+  );
+
+CREATE INDEX IF NOT EXISTS freeze_history_guild_time ON public.freeze_history (guild_id, end_time);
+
+insert into "public"."translation" ("category", "id", "locale", "name") values ('ore_type', 1, 'zh-CN', '金属矿石'), ('ore_type', 2, 'zh-CN', '石炭'), ('ore_type', 4, 'zh-CN', '硫磺'), ('ore_type', 8, 'zh-CN', '纯水晶'), ('ore_type', 1, 'en', 'Ore'), ('ore_type', 2, 'en', 'Coal'), ('ore_type', 4, 'en', 'Sulfur'), ('ore_type', 8, 'en', 'Pure Quartz'), ('ore_type', 1, 'ja', '鉱石'), ('ore_type', 2, 'ja', '石炭'), ('ore_type', 4, 'ja', '硫黄'), ('ore_type', 8, 'ja', '純水晶'), ('ore_point', 1, 'zh-CN', '破败教会'), ('ore_point', 2, 'zh-CN', '要塞遗迹'), ('ore_point', 3, 'zh-CN', '丘陵海角'), ('ore_point', 4, 'zh-CN', '修行者瀑布'), ('ore_point', 5, 'zh-CN', '竹林深处'), ('ore_point', 6, 'zh-CN', '探究者歧路'), ('ore_point', 7, 'zh-CN', '彩蝶之森'), ('ore_point', 8, 'zh-CN', '伪善者之丘'), ('ore_point', 9, 'zh-CN', '湖畔山丘'), ('ore_point', 10, 'zh-CN', '花兔山山顶'), ('ore_point', 11, 'zh-CN', '湿地之岛旁'), ('ore_point', 12, 'zh-CN', '草熊猫之森'), ('ore_point', 13, 'zh-CN', '守护者密域'), ('ore_point', 14, 'zh-CN', '神速密域'), ('ore_point', 15, 'zh-CN', '古代文明遗迹'), ('ore_point', 16, 'zh-CN', '黑曜火山山腰'), ('ore_point', 17, 'zh-CN', '黑曜火山山顶'), ('ore_point', 18, 'zh-CN', '火山黑市商人'), ('ore_point', 19, 'zh-CN', '魔渊龙北侧山顶'), ('ore_point', 20, 'zh-CN', '黑曜火山北侧'), ('ore_point', 21, 'zh-CN', '鲨小子的地盘'), ('ore_point', 22, 'zh-CN', '草熊猫之森西侧'), ('ore_point', 23, 'zh-CN', '守护者密域山坡'), ('ore_point', 24, 'zh-CN', '神速密域南侧'), ('ore_point', 25, 'zh-CN', '通往雪山的岔路'), ('ore_point', 26, 'zh-CN', '霜冻雪山山脚'), ('ore_point', 27, 'zh-CN', '日暮沙地东侧'), ('ore_point', 28, 'zh-CN', '日暮沙地西侧'), ('ore_point', 29, 'zh-CN', '日暮沙地北侧'), ('ore_point', 30, 'zh-CN', '黑曜火山东南'), ('ore_point', 31, 'zh-CN', '边远渔村'), ('ore_point', 32, 'zh-CN', '火山阿努比斯'), ('ore_point', 33, 'zh-CN', '黑曜火山瀑布'), ('ore_point', 34, 'zh-CN', '黑曜火山湖岛'), ('ore_point', 35, 'zh-CN', '黑曜火山南侧'), ('ore_point', 36, 'zh-CN', '黑曜火山西南'), ('ore_point', 37, 'zh-CN', '冥铠蝎入口'), ('ore_point', 38, 'zh-CN', '冥铠蝎高地'), ('ore_point', 39, 'zh-CN', '冥铠蝎高地东侧'), ('ore_point', 40, 'zh-CN', '自卫队高塔东侧'), ('ore_point', 41, 'zh-CN', '沙丘入口高地南侧'), ('ore_point', 42, 'zh-CN', '沙丘入口高地'), ('ore_point', 43, 'zh-CN', '沙漠之镇东北'), ('ore_point', 44, 'zh-CN', '雷冠龙东侧'), ('ore_point', 45, 'zh-CN', '雷冠龙北侧'), ('ore_point', 46, 'zh-CN', '雷冠龙南侧'), ('ore_point', 47, 'zh-CN', '永炎同心会高塔'), ('ore_point', 48, 'zh-CN', '魔渊龙岩浆湖'), ('ore_point', 49, 'zh-CN', '黑曜火山山脚'), ('ore_point', 50, 'zh-CN', '空涡龙'), ('ore_point', 51, 'zh-CN', '霜冻雪山'), ('ore_point', 52, 'zh-CN', '不溶湖东侧'), ('ore_point', 53, 'zh-CN', '白银灵峰山腰'), ('ore_point', 54, 'zh-CN', '唤冬兽西侧'), ('ore_point', 55, 'zh-CN', '唤冬兽东侧'), ('ore_point', 56, 'zh-CN', '白银灵峰北侧'), ('ore_point', 1, 'en', 'Ruined Church'), ('ore_point', 2, 'en', 'Fortress Ruins'), ('ore_point', 3, 'en', 'Hill Cape'), ('ore_point', 4, 'en', 'Ascetic''s Waterfall'), ('ore_point', 5, 'en', 'Deep Bamboo Grove'), ('ore_point', 6, 'en', 'Seeker''s Crossroads'), ('ore_point', 7, 'en', 'Butterfly Forest'), ('ore_point', 8, 'en', 'Hypocrite''s Hill'), ('ore_point', 9, 'en', 'Lakeside Hill'), ('ore_point', 10, 'en', 'Flower Rabbit Hill Summit'), ('ore_point', 11, 'en', 'Beside Wetland Island'), ('ore_point', 12, 'en', 'Mossanda Forest'), ('ore_point', 13, 'en', 'Guardian''s Sanctuary'), ('ore_point', 14, 'en', 'Swift Sanctuary'), ('ore_point', 15, 'en', 'Ancient Civilization Ruins'), ('ore_point', 16, 'en', 'Mount Obsidian Hillside'), ('ore_point', 17, 'en', 'Mount Obsidian Summit'), ('ore_point', 18, 'en', 'Volcano Black Marketeer'), ('ore_point', 19, 'en', 'Jormuntide Ignis North Summit'), ('ore_point', 20, 'en', 'Mount Obsidian North'), ('ore_point', 21, 'en', 'Gobfin''s Turf'), ('ore_point', 22, 'en', 'Mossanda Forest West'), ('ore_point', 23, 'en', 'Guardian''s Sanctuary Slope'), ('ore_point', 24, 'en', 'Swift Sanctuary South'), ('ore_point', 25, 'en', 'Fork to the Snow Mountain'), ('ore_point', 26, 'en', 'Frostbite Mountain Foothills'), ('ore_point', 27, 'en', 'Sunset Sands East'), ('ore_point', 28, 'en', 'Sunset Sands West'), ('ore_point', 29, 'en', 'Sunset Sands North'), ('ore_point', 30, 'en', 'Mount Obsidian Southeast'), ('ore_point', 31, 'en', 'Remote Fishing Village'), ('ore_point', 32, 'en', 'Volcano Anubis'), ('ore_point', 33, 'en', 'Mount Obsidian Waterfall'), ('ore_point', 34, 'en', 'Mount Obsidian Lake Island'), ('ore_point', 35, 'en', 'Mount Obsidian South'), ('ore_point', 36, 'en', 'Mount Obsidian Southwest'), ('ore_point', 37, 'en', 'Menasting Entrance'), ('ore_point', 38, 'en', 'Menasting Highlands'), ('ore_point', 39, 'en', 'Menasting Highlands East'), ('ore_point', 40, 'en', 'PIDF Tower East'), ('ore_point', 41, 'en', 'Dune Entrance Highlands South'), ('ore_point', 42, 'en', 'Dune Entrance Highlands'), ('ore_point', 43, 'en', 'Desert Town Northeast'), ('ore_point', 44, 'en', 'Orserk East'), ('ore_point', 45, 'en', 'Orserk North'), ('ore_point', 46, 'en', 'Orserk South'), ('ore_point', 47, 'en', 'Eternal Pyre Tower'), ('ore_point', 48, 'en', 'Jormuntide Ignis Lava Lake'), ('ore_point', 49, 'en', 'Mount Obsidian Foothills'), ('ore_point', 50, 'en', 'Jetragon'), ('ore_point', 51, 'en', 'Frostbite Mountain'), ('ore_point', 52, 'en', 'Unmelting Lake East'), ('ore_point', 53, 'en', 'Silver Spirit Peak Hillside'), ('ore_point', 54, 'en', 'Frostallion West'), ('ore_point', 55, 'en', 'Frostallion East'), ('ore_point', 56, 'en', 'Silver Spirit Peak North'), ('ore_point', 1, 'ja', '荒れ果てた教会'), ('ore_point', 2, 'ja', '要塞跡'), ('ore_point', 3, 'ja', '丘陵の岬'), ('ore_point', 4, 'ja', '修行者の滝'), ('ore_point', 5, 'ja', '竹林の奥'), ('ore_point', 6, 'ja', '探求者の分かれ道'), ('ore_point', 7, 'ja', '蝶の森'), ('ore_point', 8, 'ja', '偽善者の丘'), ('ore_point', 9, 'ja', '湖畔の丘'), ('ore_point', 10, 'ja', '花兎山の山頂'), ('ore_point', 11, 'ja', '湿地の島のそば'), ('ore_point', 12, 'ja', '草熊猫の森'), ('ore_point', 13, 'ja', '守護者の秘境'), ('ore_point', 14, 'ja', '神速の秘境'), ('ore_point', 15, 'ja', '古代文明の遺跡'), ('ore_point', 16, 'ja', '黒曜火山の中腹'), ('ore_point', 17, 'ja', '黒曜火山の山頂'), ('ore_point', 18, 'ja', '火山の闇商人'), ('ore_point', 19, 'ja', '魔淵竜の北の山頂'), ('ore_point', 20, 'ja', '黒曜火山の北側'), ('ore_point', 21, 'ja', 'サメ小僧の縄張り'), ('ore_point', 22, 'ja', '草熊猫の森の西側'), ('ore_point', 23, 'ja', '守護者の秘境の斜面'), ('ore_point', 24, 'ja', '神速の秘境の南側'), ('ore_point', 25, 'ja', '雪山への分かれ道'), ('ore_point', 26, 'ja', '霜凍雪山の麓'), ('ore_point', 27, 'ja', '日暮れの砂地の東側'), ('ore_point', 28, 'ja', '日暮れの砂地の西側'), ('ore_point', 29, 'ja', '日暮れの砂地の北側'), ('ore_point', 30, 'ja', '黒曜火山の南東'), ('ore_point', 31, 'ja', '辺境の漁村'), ('ore_point', 32, 'ja', '火山のアヌビス'), ('ore_point', 33, 'ja', '黒曜火山の滝'), ('ore_point', 34, 'ja', '黒曜火山の湖の島'), ('ore_point', 35, 'ja', '黒曜火山の南側'), ('ore_point', 36, 'ja', '黒曜火山の南西'), ('ore_point', 37, 'ja', '冥鎧蠍の入口'), ('ore_point', 38, 'ja', '冥鎧蠍の高台'), ('ore_point', 39, 'ja', '冥鎧蠍の高台の東側'), ('ore_point', 40, 'ja', '自衛隊の塔の東側'), ('ore_point', 41, 'ja', '砂丘入口の高台の南側'), ('ore_point', 42, 'ja', '砂丘入口の高台'), ('ore_point', 43, 'ja', '砂漠の町の北東'), ('ore_point', 44, 'ja', '雷冠竜の東側'), ('ore_point', 45, 'ja', '雷冠竜の北側'), ('ore_point', 46, 'ja', '雷冠竜の南側'), ('ore_point', 47, 'ja', '永炎同心会の塔'), ('ore_point', 48, 'ja', '魔淵竜の溶岩湖'), ('ore_point', 49, 'ja', '黒曜火山の麓'), ('ore_point', 50, 'ja', '空渦竜'), ('ore_point', 51, 'ja', '霜凍雪山'), ('ore_point', 52, 'ja', '不溶湖の東側'), ('ore_point', 53, 'ja', '白銀霊峰の中腹'), ('ore_point', 54, 'ja', '喚冬獣の西側'), ('ore_point', 55, 'ja', '喚冬獣の東側'), ('ore_point', 56, 'ja', '白銀霊峰の北側') on conflict do nothing;
@@ -28,15 +28,399 @@ CREATE TABLE
   public.occupy_table (
     ore_point_id integer NOT NULL,
     guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
     user_id bigint NOT NULL,
     due_time timestamp with time zone NOT NULL,
-    battle_user_id bigint NULL
+    battle_user_id bigint NULL,
+    team_id integer NULL,
+    battle_team_id integer NULL,
+    battle_time timestamp with time zone NULL,
+    battle_channel_id bigint NULL,
+    battle_reminded boolean NOT NULL DEFAULT FALSE,
+    battle_thread_id bigint NULL,
+    due_notified_at timestamp with time zone NULL,
+    shield_until timestamp with time zone NULL
   );
 
 ALTER TABLE
   public.occupy_table
 ADD
-  CONSTRAINT "Occupy_Table_pkey" PRIMARY KEY (guild_id);
+  CONSTRAINT "Occupy_Table_pkey" PRIMARY KEY (guild_id, world_id, ore_point_id);
+
+CREATE TABLE
+  public.battle_notify_role (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    role_id bigint NOT NULL
+  );
+
+ALTER TABLE
+  public.battle_notify_role
+ADD
+  CONSTRAINT "Battle_Notify_Role_pkey" PRIMARY KEY (guild_id, world_id);
+
+CREATE TABLE
+  public.world (
+    id serial NOT NULL,
+    guild_id bigint NOT NULL,
+    name character varying(255) NOT NULL
+  );
+
+ALTER TABLE
+  public.world
+ADD
+  CONSTRAINT "World_pkey" PRIMARY KEY (id);
+
+ALTER TABLE
+  public.world
+ADD
+  CONSTRAINT "World_name_key" UNIQUE (guild_id, name);
+
+CREATE TABLE
+  public.channel_world (
+    channel_id bigint NOT NULL,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL
+  );
+
+ALTER TABLE
+  public.channel_world
+ADD
+  CONSTRAINT "Channel_World_pkey" PRIMARY KEY (channel_id);
+
+CREATE TABLE
+  public.occupy_history (
+    id integer NOT NULL GENERATED BY DEFAULT AS IDENTITY,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    team_id integer NULL,
+    season_id integer NULL,
+    ore_point_id integer NOT NULL,
+    user_id bigint NOT NULL,
+    target_user_id bigint NULL,
+    action character varying(32) NOT NULL,
+    reverted boolean NOT NULL DEFAULT FALSE,
+    time timestamp with time zone NOT NULL DEFAULT now()
+  );
+
+ALTER TABLE
+  public.occupy_history
+ADD
+  CONSTRAINT "Occupy_History_pkey" PRIMARY KEY (id);
+
+CREATE TABLE
+  public.season (
+    id serial NOT NULL,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    number integer NOT NULL,
+    start_time timestamp with time zone NOT NULL,
+    end_time timestamp with time zone NULL
+  );
+
+ALTER TABLE
+  public.season
+ADD
+  CONSTRAINT "Season_pkey" PRIMARY KEY (id);
+
+CREATE TABLE
+  public.season_schedule (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    channel_id bigint NOT NULL,
+    start_time timestamp with time zone NOT NULL
+  );
+
+ALTER TABLE
+  public.season_schedule
+ADD
+  CONSTRAINT "Season_Schedule_pkey" PRIMARY KEY (guild_id, world_id);
+
+CREATE TABLE
+  public.occupy_archive (
+    season_id integer NOT NULL,
+    ore_point_id integer NOT NULL,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    user_id bigint NOT NULL,
+    due_time timestamp with time zone NOT NULL,
+    battle_user_id bigint NULL,
+    team_id integer NULL
+  );
+
+ALTER TABLE
+  public.occupy_archive
+ADD
+  CONSTRAINT "Occupy_Archive_pkey" PRIMARY KEY (season_id, ore_point_id);
+
+CREATE TABLE
+  public.team (
+    id serial NOT NULL,
+    guild_id bigint NOT NULL,
+    name character varying(255) NOT NULL,
+    role_id bigint NOT NULL
+  );
+
+ALTER TABLE
+  public.team
+ADD
+  CONSTRAINT "Team_pkey" PRIMARY KEY (id);
+
+ALTER TABLE
+  public.team
+ADD
+  CONSTRAINT "Team_role_key" UNIQUE (guild_id, role_id);
+
+CREATE TABLE
+  public.guild_config (
+    guild_id bigint NOT NULL,
+    proposal_timeout_hours integer NOT NULL DEFAULT 24,
+    queue_counts_limit boolean NOT NULL DEFAULT FALSE,
+    challenge_channel_id bigint NULL,
+    notify_channel_id bigint NULL,
+    notify_events integer NOT NULL DEFAULT 2,
+    locale character varying(8) NULL,
+    log_retention_days integer NULL,
+    frozen_at timestamp with time zone NULL,
+    shield_hours integer NULL,
+    point_cooldown_hours integer NULL,
+    player_cooldown_hours integer NULL,
+    vacation_max_days integer NULL
+  );
+
+ALTER TABLE
+  public.guild_config
+ADD
+  CONSTRAINT "Guild_Config_pkey" PRIMARY KEY (guild_id);
+
+CREATE TABLE
+  public.battle_proposal (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL,
+    ore_point_id integer NOT NULL,
+    channel_id bigint NOT NULL,
+    slots timestamp with time zone[] NOT NULL,
+    created_at timestamp with time zone NOT NULL DEFAULT now()
+  );
+
+ALTER TABLE
+  public.battle_proposal
+ADD
+  CONSTRAINT "Battle_Proposal_pkey" PRIMARY KEY (guild_id, world_id, ore_point_id);
+
+CREATE TABLE
+  public.challenge_queue (
+    id integer NOT NULL GENERATED BY DEFAULT AS IDENTITY,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL,
+    ore_point_id integer NOT NULL,
+    user_id bigint NOT NULL,
+    team_id integer NULL,
+    queued_at timestamp with time zone NOT NULL DEFAULT now()
+  );
+
+ALTER TABLE
+  public.challenge_queue
+ADD
+  CONSTRAINT "Challenge_Queue_pkey" PRIMARY KEY (id);
+
+ALTER TABLE
+  public.challenge_queue
+ADD
+  CONSTRAINT "Challenge_Queue_user_key" UNIQUE (guild_id, world_id, ore_point_id, user_id);
+
+CREATE TABLE
+  public.ore_type_notify_role (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    ore_type integer NOT NULL,
+    role_id bigint NOT NULL
+  );
+
+ALTER TABLE
+  public.ore_type_notify_role
+ADD
+  CONSTRAINT "Ore_Type_Notify_Role_pkey" PRIMARY KEY (guild_id, world_id, ore_type);
+
+CREATE TABLE
+  public.webhook (
+    id serial NOT NULL,
+    guild_id bigint NOT NULL,
+    url character varying(2048) NOT NULL,
+    secret character varying(255) NOT NULL
+  );
+
+ALTER TABLE
+  public.webhook
+ADD
+  CONSTRAINT "Webhook_pkey" PRIMARY KEY (id);
+
+CREATE TABLE
+  public.api_token (
+    guild_id bigint NOT NULL,
+    name character varying(255) NOT NULL,
+    token_hash character(64) NOT NULL,
+    created_at timestamp with time zone NOT NULL DEFAULT now()
+  );
+
+ALTER TABLE
+  public.api_token
+ADD
+  CONSTRAINT "Api_Token_pkey" PRIMARY KEY (guild_id, name);
+
+ALTER TABLE
+  public.api_token
+ADD
+  CONSTRAINT "Api_Token_hash_key" UNIQUE (token_hash);
+
+CREATE TABLE
+  public.game_server (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    rest_url character varying(2048) NULL,
+    rest_password character varying(255) NULL,
+    rcon_address character varying(255) NULL,
+    rcon_password character varying(255) NULL,
+    require_online boolean NOT NULL DEFAULT FALSE
+  );
+
+ALTER TABLE
+  public.game_server
+ADD
+  CONSTRAINT "Game_Server_pkey" PRIMARY KEY (guild_id, world_id);
+
+CREATE TABLE
+  public.player_link (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    user_id bigint NOT NULL,
+    character_name character varying(255) NOT NULL,
+    player_uid character varying(64) NULL,
+    steam_id character varying(64) NULL,
+    verified boolean NOT NULL DEFAULT FALSE
+  );
+
+ALTER TABLE
+  public.player_link
+ADD
+  CONSTRAINT "Player_Link_pkey" PRIMARY KEY (guild_id, world_id, user_id);
+
+ALTER TABLE
+  public.player_link
+ADD
+  CONSTRAINT "Player_Link_name_key" UNIQUE (guild_id, world_id, character_name);
+
+CREATE TABLE
+  public.bot_role (
+    guild_id bigint NOT NULL,
+    role_id bigint NOT NULL,
+    level integer NOT NULL
+  );
+
+ALTER TABLE
+  public.bot_role
+ADD
+  CONSTRAINT "Bot_Role_pkey" PRIMARY KEY (guild_id, role_id);
+
+CREATE TABLE
+  public.member_channel (
+    guild_id bigint NOT NULL,
+    channel_id bigint NOT NULL
+  );
+
+ALTER TABLE
+  public.member_channel
+ADD
+  CONSTRAINT "Member_Channel_pkey" PRIMARY KEY (guild_id, channel_id);
+
+CREATE TABLE
+  public.translation (
+    category character varying(16) NOT NULL,
+    id integer NOT NULL,
+    locale character varying(8) NOT NULL,
+    name character varying(255) NOT NULL
+  );
+
+ALTER TABLE
+  public.translation
+ADD
+  CONSTRAINT "Translation_pkey" PRIMARY KEY (category, id, locale);
+
+CREATE TABLE
+  public.command_log (
+    id bigint NOT NULL GENERATED BY DEFAULT AS IDENTITY,
+    guild_id bigint NULL,
+    channel_id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    content text NOT NULL,
+    command character varying(100) NULL,
+    ore_point_id integer NULL,
+    success boolean NULL,
+    error text NULL,
+    time timestamp with time zone NOT NULL DEFAULT now()
+  );
+
+ALTER TABLE
+  public.command_log
+ADD
+  CONSTRAINT "Command_Log_pkey" PRIMARY KEY (id);
+
+CREATE INDEX command_log_guild_time ON public.command_log (guild_id, time);
+
+CREATE TABLE
+  public.admin_action (
+    id integer NOT NULL GENERATED BY DEFAULT AS IDENTITY,
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    season_id integer NULL,
+    ore_point_id integer NOT NULL,
+    user_id bigint NOT NULL,
+    action character varying(32) NOT NULL,
+    previous_user_id bigint NULL,
+    previous_due_time timestamp with time zone NULL,
+    previous_team_id integer NULL,
+    previous_battle_user_id bigint NULL,
+    previous_battle_team_id integer NULL,
+    previous_battle_time timestamp with time zone NULL,
+    previous_battle_thread_id bigint NULL,
+    previous_shield_until timestamp with time zone NULL,
+    current_user_id bigint NULL,
+    current_due_time timestamp with time zone NULL,
+    current_battle_user_id bigint NULL,
+    reverted boolean NOT NULL DEFAULT FALSE,
+    time timestamp with time zone NOT NULL DEFAULT now()
+  );
+
+ALTER TABLE
+  public.admin_action
+ADD
+  CONSTRAINT "Admin_Action_pkey" PRIMARY KEY (id);
+
+CREATE INDEX admin_action_guild_time ON public.admin_action (guild_id, time);
+
+CREATE TABLE
+  public.vacation (
+    guild_id bigint NOT NULL,
+    world_id integer NOT NULL DEFAULT 0,
+    user_id bigint NOT NULL,
+    end_time timestamp with time zone NOT NULL,
+    delegate_user_id bigint NULL
+  );
+
+ALTER TABLE
+  public.vacation
+ADD
+  CONSTRAINT "Vacation_pkey" PRIMARY KEY (guild_id, world_id, user_id);
+
+CREATE TABLE
+  public.freeze_history (
+    guild_id bigint NOT NULL,
+    start_time timestamp with time zone NOT NULL,
+    end_time timestamp with time zone NOT NULL
+  );
+
+CREATE INDEX freeze_history_guild_time ON public.freeze_history (guild_id, end_time);
 
 insert into "public"."ore_type" ("emoji", "id", "name") values (':copper_ore:1222550112388251668', 1, '金屬礦石'), (':coal:1222552834902327407', 2, '石炭'), (':sulfur:1222553853061234688', 4, '硫磺'), (':quartz:1222560703550853231', 8, '純水晶');
-insert into "public"."ore_point" ("id", "name", "ore_type", "x", "y") values (1, '破敗教會', 1, 71, -404), (2, '要塞遺跡', 1, 155, -393), (3, '丘陵海角', 1, 7, -529), (4, '修行者瀑布', 1, -249, -456), (5, '竹林深處', 1, -343, -253), (6, '探究者歧路', 1, -255, -212), (7, '彩蝶之森', 1, -77, -317), (8, '偽善者之丘', 1, 91, -263), (9, '湖畔山丘', 1, -32, -170), (10, '花兔山山頂', 1, 0, -82), (11, '濕地之島旁', 3, 268, -227), (12, '草熊貓之森', 1, 252, -93), (13, '守護者密域', 3, 187, -40), (14, '神速密域', 1, 310, -38), (15, '古代文明遺跡', 1, -418, -606), (16, '黑曜火山山腰', 1, -536, -479), (17, '黑曜火山山頂', 1, -636, -496), (18, '火山黑市商人', 1, -766, -672), (19, '魔淵龍北側山頂', 1, -580, -352), (20, '黑曜火山北側', 1, -674, -291), (21, '鯊小子的地盤', 2, 149, -208), (22, '草熊貓之森西側', 2, 200, -114), (23, '守護者密域山坡', 2, 155, -67), (24, '神速密域南側', 2, 290, -21), (25, '通往雪山的岔路', 2, 101, 26), (26, '霜凍雪山山腳', 2, 101, 59), (27, '日暮沙地東側', 2, -96, -119), (28, '日暮沙地西側', 2, -157, -89), (29, '日暮沙地北側', 2, -125, -83), (30, '黒曜火山東南', 2, -465, -676), (31, '邊遠漁村', 2, -511, -722), (32, '火山阿努比斯', 2, -572, -648), (33, '黒曜火山瀑布', 2, -604, -724), (34, '黒曜火山湖島', 2, -705, -640), (35, '黒曜火山南側', 2, -695, -733), (36, '黒曜火山西南', 2, -735, -695), (37, '冥鎧蠍入口', 2, 515, 66), (38, '冥鎧蠍高地', 2, 591, 151), (39, '冥鎧蠍高地東側', 2, 645, 154), (40, '自衛隊高塔東側', 2, 622, 311), (41, '沙丘入口高地南側', 2, 266, 212), (42, '沙丘入口高地', 2, 290, 242), (43, '沙漠之鎮東北', 2, 436, 432), (44, '雷冠龍東側', 2, 435, 521), (45, '雷冠龍北側', 2, 334, 560), (46, '雷冠龍南側', 2, 320, 500), (47, '永炎同心會高塔', 4, -596, -518), (48, '魔淵龍岩漿湖', 4, -593, -403), (49, '黒曜火山山腳', 4, -743, -444), (50, '空渦龍', 4, -739, -339), (51, '霜凍雪山', 8, 206, 96), (52, '不溶湖東側', 8, -209, 249), (53, '白銀靈峰山腰', 8, -253, 394), (54, '喚冬獸西側', 8, -417, 473), (55, '喚冬獸東側', 8, -308, 542), (56, '白銀靈峰北側', 8, -139, 581);
\ No newline at end of file
+insert into "public"."ore_point" ("id", "name", "ore_type", "x", "y") values (1, '破敗教會', 1, 71, -404), (2, '要塞遺跡', 1, 155, -393), (3, '丘陵海角', 1, 7, -529), (4, '修行者瀑布', 1, -249, -456), (5, '竹林深處', 1, -343, -253), (6, '探究者歧路', 1, -255, -212), (7, '彩蝶之森', 1, -77, -317), (8, '偽善者之丘', 1, 91, -263), (9, '湖畔山丘', 1, -32, -170), (10, '花兔山山頂', 1, 0, -82), (11, '濕地之島旁', 3, 268, -227), (12, '草熊貓之森', 1, 252, -93), (13, '守護者密域', 3, 187, -40), (14, '神速密域', 1, 310, -38), (15, '古代文明遺跡', 1, -418, -606), (16, '黑曜火山山腰', 1, -536, -479), (17, '黑曜火山山頂', 1, -636, -496), (18, '火山黑市商人', 1, -766, -672), (19, '魔淵龍北側山頂', 1, -580, -352), (20, '黑曜火山北側', 1, -674, -291), (21, '鯊小子的地盤', 2, 149, -208), (22, '草熊貓之森西側', 2, 200, -114), (23, '守護者密域山坡', 2, 155, -67), (24, '神速密域南側', 2, 290, -21), (25, '通往雪山的岔路', 2, 101, 26), (26, '霜凍雪山山腳', 2, 101, 59), (27, '日暮沙地東側', 2, -96, -119), (28, '日暮沙地西側', 2, -157, -89), (29, '日暮沙地北側', 2, -125, -83), (30, '黒曜火山東南', 2, -465, -676), (31, '邊遠漁村', 2, -511, -722), (32, '火山阿努比斯', 2, -572, -648), (33, '黒曜火山瀑布', 2, -604, -724), (34, '黒曜火山湖島', 2, -705, -640), (35, '黒曜火山南側', 2, -695, -733), (36, '黒曜火山西南', 2, -735, -695), (37, '冥鎧蠍入口', 2, 515, 66), (38, '冥鎧蠍高地', 2, 591, 151), (39, '冥鎧蠍高地東側', 2, 645, 154), (40, '自衛隊高塔東側', 2, 622, 311), (41, '沙丘入口高地南側', 2, 266, 212), (42, '沙丘入口高地', 2, 290, 242), (43, '沙漠之鎮東北', 2, 436, 432), (44, '雷冠龍東側', 2, 435, 521), (45, '雷冠龍北側', 2, 334, 560), (46, '雷冠龍南側', 2, 320, 500), (47, '永炎同心會高塔', 4, -596, -518), (48, '魔淵龍岩漿湖', 4, -593, -403), (49, '黒曜火山山腳', 4, -743, -444), (50, '空渦龍', 4, -739, -339), (51, '霜凍雪山', 8, 206, 96), (52, '不溶湖東側', 8, -209, 249), (53, '白銀靈峰山腰', 8, -253, 394), (54, '喚冬獸西側', 8, -417, 473), (55, '喚冬獸東側', 8, -308, 542), (56, '白銀靈峰北側', 8, -139, 581);
+insert into "public"."translation" ("category", "id", "locale", "name") values ('ore_type', 1, 'zh-CN', '金属矿石'), ('ore_type', 2, 'zh-CN', '石炭'), ('ore_type', 4, 'zh-CN', '硫磺'), ('ore_type', 8, 'zh-CN', '纯水晶'), ('ore_type', 1, 'en', 'Ore'), ('ore_type', 2, 'en', 'Coal'), ('ore_type', 4, 'en', 'Sulfur'), ('ore_type', 8, 'en', 'Pure Quartz'), ('ore_type', 1, 'ja', '鉱石'), ('ore_type', 2, 'ja', '石炭'), ('ore_type', 4, 'ja', '硫黄'), ('ore_type', 8, 'ja', '純水晶'), ('ore_point', 1, 'zh-CN', '破败教会'), ('ore_point', 2, 'zh-CN', '要塞遗迹'), ('ore_point', 3, 'zh-CN', '丘陵海角'), ('ore_point', 4, 'zh-CN', '修行者瀑布'), ('ore_point', 5, 'zh-CN', '竹林深处'), ('ore_point', 6, 'zh-CN', '探究者歧路'), ('ore_point', 7, 'zh-CN', '彩蝶之森'), ('ore_point', 8, 'zh-CN', '伪善者之丘'), ('ore_point', 9, 'zh-CN', '湖畔山丘'), ('ore_point', 10, 'zh-CN', '花兔山山顶'), ('ore_point', 11, 'zh-CN', '湿地之岛旁'), ('ore_point', 12, 'zh-CN', '草熊猫之森'), ('ore_point', 13, 'zh-CN', '守护者密域'), ('ore_point', 14, 'zh-CN', '神速密域'), ('ore_point', 15, 'zh-CN', '古代文明遗迹'), ('ore_point', 16, 'zh-CN', '黑曜火山山腰'), ('ore_point', 17, 'zh-CN', '黑曜火山山顶'), ('ore_point', 18, 'zh-CN', '火山黑市商人'), ('ore_point', 19, 'zh-CN', '魔渊龙北侧山顶'), ('ore_point', 20, 'zh-CN', '黑曜火山北侧'), ('ore_point', 21, 'zh-CN', '鲨小子的地盘'), ('ore_point', 22, 'zh-CN', '草熊猫之森西侧'), ('ore_point', 23, 'zh-CN', '守护者密域山坡'), ('ore_point', 24, 'zh-CN', '神速密域南侧'), ('ore_point', 25, 'zh-CN', '通往雪山的岔路'), ('ore_point', 26, 'zh-CN', '霜冻雪山山脚'), ('ore_point', 27, 'zh-CN', '日暮沙地东侧'), ('ore_point', 28, 'zh-CN', '日暮沙地西侧'), ('ore_point', 29, 'zh-CN', '日暮沙地北侧'), ('ore_point', 30, 'zh-CN', '黑曜火山东南'), ('ore_point', 31, 'zh-CN', '边远渔村'), ('ore_point', 32, 'zh-CN', '火山阿努比斯'), ('ore_point', 33, 'zh-CN', '黑曜火山瀑布'), ('ore_point', 34, 'zh-CN', '黑曜火山湖岛'), ('ore_point', 35, 'zh-CN', '黑曜火山南侧'), ('ore_point', 36, 'zh-CN', '黑曜火山西南'), ('ore_point', 37, 'zh-CN', '冥铠蝎入口'), ('ore_point', 38, 'zh-CN', '冥铠蝎高地'), ('ore_point', 39, 'zh-CN', '冥铠蝎高地东侧'), ('ore_point', 40, 'zh-CN', '自卫队高塔东侧'), ('ore_point', 41, 'zh-CN', '沙丘入口高地南侧'), ('ore_point', 42, 'zh-CN', '沙丘入口高地'), ('ore_point', 43, 'zh-CN', '沙漠之镇东北'), ('ore_point', 44, 'zh-CN', '雷冠龙东侧'), ('ore_point', 45, 'zh-CN', '雷冠龙北侧'), ('ore_point', 46, 'zh-CN', '雷冠龙南侧'), ('ore_point', 47, 'zh-CN', '永炎同心会高塔'), ('ore_point', 48, 'zh-CN', '魔渊龙岩浆湖'), ('ore_point', 49, 'zh-CN', '黑曜火山山脚'), ('ore_point', 50, 'zh-CN', '空涡龙'), ('ore_point', 51, 'zh-CN', '霜冻雪山'), ('ore_point', 52, 'zh-CN', '不溶湖东侧'), ('ore_point', 53, 'zh-CN', '白银灵峰山腰'), ('ore_point', 54, 'zh-CN', '唤冬兽西侧'), ('ore_point', 55, 'zh-CN', '唤冬兽东侧'), ('ore_point', 56, 'zh-CN', '白银灵峰北侧'), ('ore_point', 1, 'en', 'Ruined Church'), ('ore_point', 2, 'en', 'Fortress Ruins'), ('ore_point', 3, 'en', 'Hill Cape'), ('ore_point', 4, 'en', 'Ascetic''s Waterfall'), ('ore_point', 5, 'en', 'Deep Bamboo Grove'), ('ore_point', 6, 'en', 'Seeker''s Crossroads'), ('ore_point', 7, 'en', 'Butterfly Forest'), ('ore_point', 8, 'en', 'Hypocrite''s Hill'), ('ore_point', 9, 'en', 'Lakeside Hill'), ('ore_point', 10, 'en', 'Flower Rabbit Hill Summit'), ('ore_point', 11, 'en', 'Beside Wetland Island'), ('ore_point', 12, 'en', 'Mossanda Forest'), ('ore_point', 13, 'en', 'Guardian''s Sanctuary'), ('ore_point', 14, 'en', 'Swift Sanctuary'), ('ore_point', 15, 'en', 'Ancient Civilization Ruins'), ('ore_point', 16, 'en', 'Mount Obsidian Hillside'), ('ore_point', 17, 'en', 'Mount Obsidian Summit'), ('ore_point', 18, 'en', 'Volcano Black Marketeer'), ('ore_point', 19, 'en', 'Jormuntide Ignis North Summit'), ('ore_point', 20, 'en', 'Mount Obsidian North'), ('ore_point', 21, 'en', 'Gobfin''s Turf'), ('ore_point', 22, 'en', 'Mossanda Forest West'), ('ore_point', 23, 'en', 'Guardian''s Sanctuary Slope'), ('ore_point', 24, 'en', 'Swift Sanctuary South'), ('ore_point', 25, 'en', 'Fork to the Snow Mountain'), ('ore_point', 26, 'en', 'Frostbite Mountain Foothills'), ('ore_point', 27, 'en', 'Sunset Sands East'), ('ore_point', 28, 'en', 'Sunset Sands West'), ('ore_point', 29, 'en', 'Sunset Sands North'), ('ore_point', 30, 'en', 'Mount Obsidian Southeast'), ('ore_point', 31, 'en', 'Remote Fishing Village'), ('ore_point', 32, 'en', 'Volcano Anubis'), ('ore_point', 33, 'en', 'Mount Obsidian Waterfall'), ('ore_point', 34, 'en', 'Mount Obsidian Lake Island'), ('ore_point', 35, 'en', 'Mount Obsidian South'), ('ore_point', 36, 'en', 'Mount Obsidian Southwest'), ('ore_point', 37, 'en', 'Menasting Entrance'), ('ore_point', 38, 'en', 'Menasting Highlands'), ('ore_point', 39, 'en', 'Menasting Highlands East'), ('ore_point', 40, 'en', 'PIDF Tower East'), ('ore_point', 41, 'en', 'Dune Entrance Highlands South'), ('ore_point', 42, 'en', 'Dune Entrance Highlands'), ('ore_point', 43, 'en', 'Desert Town Northeast'), ('ore_point', 44, 'en', 'Orserk East'), ('ore_point', 45, 'en', 'Orserk North'), ('ore_point', 46, 'en', 'Orserk South'), ('ore_point', 47, 'en', 'Eternal Pyre Tower'), ('ore_point', 48, 'en', 'Jormuntide Ignis Lava Lake'), ('ore_point', 49, 'en', 'Mount Obsidian Foothills'), ('ore_point', 50, 'en', 'Jetragon'), ('ore_point', 51, 'en', 'Frostbite Mountain'), ('ore_point', 52, 'en', 'Unmelting Lake East'), ('ore_point', 53, 'en', 'Silver Spirit Peak Hillside'), ('ore_point', 54, 'en', 'Frostallion West'), ('ore_point', 55, 'en', 'Frostallion East'), ('ore_point', 56, 'en', 'Silver Spirit Peak North'), ('ore_point', 1, 'ja', '荒れ果てた教会'), ('ore_point', 2, 'ja', '要塞跡'), ('ore_point', 3, 'ja', '丘陵の岬'), ('ore_point', 4, 'ja', '修行者の滝'), ('ore_point', 5, 'ja', '竹林の奥'), ('ore_point', 6, 'ja', '探求者の分かれ道'), ('ore_point', 7, 'ja', '蝶の森'), ('ore_point', 8, 'ja', '偽善者の丘'), ('ore_point', 9, 'ja', '湖畔の丘'), ('ore_point', 10, 'ja', '花兎山の山頂'), ('ore_point', 11, 'ja', '湿地の島のそば'), ('ore_point', 12, 'ja', '草熊猫の森'), ('ore_point', 13, 'ja', '守護者の秘境'), ('ore_point', 14, 'ja', '神速の秘境'), ('ore_point', 15, 'ja', '古代文明の遺跡'), ('ore_point', 16, 'ja', '黒曜火山の中腹'), ('ore_point', 17, 'ja', '黒曜火山の山頂'), ('ore_point', 18, 'ja', '火山の闇商人'), ('ore_point', 19, 'ja', '魔淵竜の北の山頂'), ('ore_point', 20, 'ja', '黒曜火山の北側'), ('ore_point', 21, 'ja', 'サメ小僧の縄張り'), ('ore_point', 22, 'ja', '草熊猫の森の西側'), ('ore_point', 23, 'ja', '守護者の秘境の斜面'), ('ore_point', 24, 'ja', '神速の秘境の南側'), ('ore_point', 25, 'ja', '雪山への分かれ道'), ('ore_point', 26, 'ja', '霜凍雪山の麓'), ('ore_point', 27, 'ja', '日暮れの砂地の東側'), ('ore_point', 28, 'ja', '日暮れの砂地の西側'), ('ore_point', 29, 'ja', '日暮れの砂地の北側'), ('ore_point', 30, 'ja', '黒曜火山の南東'), ('ore_point', 31, 'ja', '辺境の漁村'), ('ore_point', 32, 'ja', '火山のアヌビス'), ('ore_point', 33, 'ja', '黒曜火山の滝'), ('ore_point', 34, 'ja', '黒曜火山の湖の島'), ('ore_point', 35, 'ja', '黒曜火山の南側'), ('ore_point', 36, 'ja', '黒曜火山の南西'), ('ore_point', 37, 'ja', '冥鎧蠍の入口'), ('ore_point', 38, 'ja', '冥鎧蠍の高台'), ('ore_point', 39, 'ja', '冥鎧蠍の高台の東側'), ('ore_point', 40, 'ja', '自衛隊の塔の東側'), ('ore_point', 41, 'ja', '砂丘入口の高台の南側'), ('ore_point', 42, 'ja', '砂丘入口の高台'), ('ore_point', 43, 'ja', '砂漠の町の北東'), ('ore_point', 44, 'ja', '雷冠竜の東側'), ('ore_point', 45, 'ja', '雷冠竜の北側'), ('ore_point', 46, 'ja', '雷冠竜の南側'), ('ore_point', 47, 'ja', '永炎同心会の塔'), ('ore_point', 48, 'ja', '魔淵竜の溶岩湖'), ('ore_point', 49, 'ja', '黒曜火山の麓'), ('ore_point', 50, 'ja', '空渦竜'), ('ore_point', 51, 'ja', '霜凍雪山'), ('ore_point', 52, 'ja', '不溶湖の東側'), ('ore_point', 53, 'ja', '白銀霊峰の中腹'), ('ore_point', 54, 'ja', '喚冬獣の西側'), ('ore_point', 55, 'ja', '喚冬獣の東側'), ('ore_point', 56, 'ja', '白銀霊峰の北側');
\ No newline at end of file
@@ -0,0 +1,475 @@
+use crate::{
+    commands::Context,
+    confirm,
+    db::{BotDB, HistoryRecord},
+    i18n::{self, tr},
+    metrics,
+    permission::require_admin,
+    structs::{ListResult, OrePoint},
+    world::{DEFAULT_WORLD_ID, DEFAULT_WORLD_NAME},
+};
+use anyhow::{Context as _, Result};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use poise::{
+    serenity_prelude::{Color, CreateEmbed},
+    CreateReply,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::SocketAddr;
+
+/// 每次查詢紀錄的最大筆數
+const MAX_HISTORY_LIMIT: u32 = 500;
+
+enum ApiError {
+    Unauthorized,
+    NotFound,
+    Internal(anyhow::Error),
+}
+
+impl<E: Into<anyhow::Error>> From<E> for ApiError {
+    fn from(err: E) -> Self {
+        ApiError::Internal(err.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "unauthorized"),
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "not found"),
+            ApiError::Internal(err) => {
+                tracing::error!("{err}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal error")
+            }
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Deserialize)]
+struct WorldQuery {
+    world: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct HistoryQuery {
+    world: Option<i32>,
+    user: Option<u64>,
+    before: Option<DateTime<Utc>>,
+    limit: Option<u32>,
+}
+
+// Discord 的 ID 超過 JavaScript 整數精度，以字串輸出
+#[derive(Serialize)]
+struct WorldJson {
+    id: i32,
+    name: String,
+}
+
+#[derive(Serialize)]
+struct PointJson {
+    id: i32,
+    name: String,
+    ore_type: i32,
+    x: i32,
+    y: i32,
+    user_id: Option<String>,
+    team_name: Option<String>,
+    due_time: Option<DateTime<Utc>>,
+    shield_until: Option<DateTime<Utc>>,
+    battle_user_id: Option<String>,
+    battle_team_name: Option<String>,
+    battle_time: Option<DateTime<Utc>>,
+    queue_count: u32,
+}
+
+impl From<ListResult> for PointJson {
+    fn from(value: ListResult) -> Self {
+        PointJson {
+            id: value.id,
+            name: value.name,
+            ore_type: value.ore_type,
+            x: value.x,
+            y: value.y,
+            user_id: value.user_id.map(|x| x.to_string()),
+            team_name: value.team_name,
+            due_time: value.due_time,
+            shield_until: value.shield_until,
+            battle_user_id: value.battle_user_id.map(|x| x.to_string()),
+            battle_team_name: value.battle_team_name,
+            battle_time: value.battle_time,
+            queue_count: value.queue_count,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HistoryJson {
+    ore_point_id: i32,
+    user_id: String,
+    target_user_id: Option<String>,
+    team_id: Option<i32>,
+    action: String,
+    time: DateTime<Utc>,
+}
+
+impl From<HistoryRecord> for HistoryJson {
+    fn from(value: HistoryRecord) -> Self {
+        HistoryJson {
+            ore_point_id: value.ore_point_id,
+            user_id: value.user_id.to_string(),
+            target_user_id: value.target_user_id.map(|x| x.to_string()),
+            team_id: value.team_id,
+            action: value.action,
+            time: value.time,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PlayerJson {
+    user_id: String,
+    occupied: Vec<PointJson>,
+    challenging: Vec<PointJson>,
+    actions: serde_json::Map<String, serde_json::Value>,
+}
+
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// 取得 Authorization 標頭中的 Bearer 金鑰
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// 確認請求的金鑰屬於該伺服器
+async fn authorize(db: &BotDB, headers: &HeaderMap, guild_id: u64) -> Result<(), ApiError> {
+    let token = bearer_token(headers).ok_or(ApiError::Unauthorized)?;
+
+    match db.get_api_token_guild(&hash_token(token)).await? {
+        Some(token_guild) if token_guild == guild_id => Ok(()),
+        _ => Err(ApiError::Unauthorized),
+    }
+}
+
+async fn points_of(db: &BotDB, guild_id: u64, world_id: i32) -> Result<Vec<ListResult>, ApiError> {
+    if world_id != DEFAULT_WORLD_ID && db.get_world_name(guild_id, world_id).await?.is_none() {
+        return Err(ApiError::NotFound);
+    }
+    let count = OrePoint::iter().count() as u32;
+    Ok(db.get_point_data(guild_id, world_id, 0, count).await?)
+}
+
+async fn worlds(
+    State(db): State<BotDB>,
+    Path(guild_id): Path<u64>,
+    headers: HeaderMap,
+) -> ApiResult<Vec<WorldJson>> {
+    authorize(&db, &headers, guild_id).await?;
+
+    let worlds = db.get_worlds(guild_id).await?;
+    Ok(Json(
+        std::iter::once((DEFAULT_WORLD_ID, DEFAULT_WORLD_NAME.to_string()))
+            .chain(worlds)
+            .map(|(id, name)| WorldJson { id, name })
+            .collect(),
+    ))
+}
+
+async fn points(
+    State(db): State<BotDB>,
+    Path(guild_id): Path<u64>,
+    Query(query): Query<WorldQuery>,
+    headers: HeaderMap,
+) -> ApiResult<Vec<PointJson>> {
+    authorize(&db, &headers, guild_id).await?;
+
+    let world_id = query.world.unwrap_or(DEFAULT_WORLD_ID);
+    let points = points_of(&db, guild_id, world_id).await?;
+    Ok(Json(points.into_iter().map(PointJson::from).collect()))
+}
+
+async fn occupations(
+    State(db): State<BotDB>,
+    Path(guild_id): Path<u64>,
+    Query(query): Query<WorldQuery>,
+    headers: HeaderMap,
+) -> ApiResult<Vec<PointJson>> {
+    authorize(&db, &headers, guild_id).await?;
+
+    let world_id = query.world.unwrap_or(DEFAULT_WORLD_ID);
+    let points = points_of(&db, guild_id, world_id).await?;
+    Ok(Json(
+        points
+            .into_iter()
+            .filter(|point| point.user_id.is_some())
+            .map(PointJson::from)
+            .collect(),
+    ))
+}
+
+async fn history(
+    State(db): State<BotDB>,
+    Path(guild_id): Path<u64>,
+    Query(query): Query<HistoryQuery>,
+    headers: HeaderMap,
+) -> ApiResult<Vec<HistoryJson>> {
+    authorize(&db, &headers, guild_id).await?;
+
+    let world_id = query.world.unwrap_or(DEFAULT_WORLD_ID);
+    let limit = query.limit.unwrap_or(100).min(MAX_HISTORY_LIMIT);
+    let records = db
+        .get_history(guild_id, world_id, query.user, query.before, limit)
+        .await?;
+    Ok(Json(records.into_iter().map(HistoryJson::from).collect()))
+}
+
+async fn player(
+    State(db): State<BotDB>,
+    Path((guild_id, user_id)): Path<(u64, u64)>,
+    Query(query): Query<WorldQuery>,
+    headers: HeaderMap,
+) -> ApiResult<PlayerJson> {
+    authorize(&db, &headers, guild_id).await?;
+
+    let world_id = query.world.unwrap_or(DEFAULT_WORLD_ID);
+    let (occupied, challenging): (Vec<_>, Vec<_>) = points_of(&db, guild_id, world_id)
+        .await?
+        .into_iter()
+        .filter(|point| point.user_id == Some(user_id) || point.battle_user_id == Some(user_id))
+        .partition(|point| point.user_id == Some(user_id));
+    let actions = db
+        .get_action_counts(guild_id, world_id, user_id)
+        .await?
+        .into_iter()
+        .map(|(action, count)| (action, count.into()))
+        .collect();
+
+    Ok(Json(PlayerJson {
+        user_id: user_id.to_string(),
+        occupied: occupied.into_iter().map(PointJson::from).collect(),
+        challenging: challenging.into_iter().map(PointJson::from).collect(),
+        actions,
+    }))
+}
+
+fn router(db: BotDB, metrics_token: Option<String>) -> Router {
+    Router::new()
+        .route("/api/guilds/:guild_id/worlds", get(worlds))
+        .route("/api/guilds/:guild_id/points", get(points))
+        .route("/api/guilds/:guild_id/occupations", get(occupations))
+        .route("/api/guilds/:guild_id/history", get(history))
+        .route("/api/guilds/:guild_id/players/:user_id", get(player))
+        .merge(metrics::routes(metrics_token))
+        .with_state(db)
+}
+
+/// 提供唯讀的 HTTP API 與監控端點
+pub async fn serve(addr: SocketAddr, db: BotDB, metrics_token: Option<String>) {
+    if let Err(err) = axum::Server::bind(&addr)
+        .serve(router(db, metrics_token).into_make_service())
+        .await
+    {
+        tracing::error!("{err}");
+    }
+}
+
+/// 管理 HTTP API 的存取金鑰
+#[poise::command(
+    slash_command,
+    rename = "api",
+    check = "require_admin",
+    subcommands("create_token", "delete_token", "list_tokens"),
+    subcommand_required
+)]
+pub async fn api(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 新增 API 金鑰
+#[poise::command(slash_command, rename = "新增", ephemeral)]
+async fn create_token(
+    ctx: Context<'_>,
+    #[max_length = 255]
+    #[rename = "名稱"]
+    #[description = "金鑰名稱"]
+    name: String,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+
+    let tokens = db.get_api_tokens(guild_id).await?;
+    if tokens.iter().any(|(token_name, _)| *token_name == name) {
+        ctx.reply(tr!(locale, "api.exists")).await?;
+        return Ok(());
+    }
+
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    db.create_api_token(guild_id, &name, &hash_token(&token))
+        .await?;
+
+    ctx.reply(tr!(
+        locale,
+        "api.created",
+        name = name,
+        token = token,
+        guild_id = guild_id
+    ))
+    .await?;
+    Ok(())
+}
+
+/// 刪除 API 金鑰
+#[poise::command(slash_command, rename = "刪除", ephemeral)]
+async fn delete_token(
+    ctx: Context<'_>,
+    #[rename = "名稱"]
+    #[description = "金鑰名稱"]
+    name: String,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let locale = i18n::locale(ctx).await;
+
+    let embed = confirm::embed(
+        tr!(locale, "confirm.delete_api"),
+        tr!(locale, "confirm.delete_api.description", name = name),
+    );
+    if !confirm::confirm(ctx, embed).await? {
+        return Ok(());
+    }
+
+    if ctx.data().delete_api_token(guild_id, &name).await? {
+        ctx.reply(tr!(locale, "api.deleted", name = name)).await?;
+    } else {
+        ctx.reply(tr!(locale, "api.not_found")).await?;
+    }
+    Ok(())
+}
+
+/// 列出所有 API 金鑰
+#[poise::command(slash_command, rename = "列表", ephemeral)]
+async fn list_tokens(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let tokens = ctx.data().get_api_tokens(guild_id).await?;
+    let locale = i18n::locale(ctx).await;
+
+    let description = if tokens.is_empty() {
+        tr!(locale, "api.empty")
+    } else {
+        tokens
+            .iter()
+            .map(|(name, created_at)| format!("{name} (<t:{}:F>)", created_at.timestamp()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .color(Color::BLUE)
+                    .title(tr!(locale, "api.list"))
+                    .description(description),
+            )
+            .reply(true)
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Client;
+    use std::net::TcpListener;
+
+    fn serve(db: BotDB) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(router(db, None).into_make_service()),
+        );
+        addr
+    }
+
+    async fn get_worlds(addr: SocketAddr, guild_id: u64, token: Option<&str>) -> reqwest::Response {
+        let request = Client::new().get(format!("http://{addr}/api/guilds/{guild_id}/worlds"));
+        match token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+        .send()
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn token_is_stored_as_sha256() {
+        assert_eq!(
+            hash_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_token_is_rejected() {
+        let addr = serve(BotDB::offline());
+        assert_eq!(get_worlds(addr, 1, None).await.status(), 401);
+
+        let response = Client::new()
+            .get(format!("http://{addr}/api/guilds/1/worlds"))
+            .header(AUTHORIZATION, "Basic dXNlcjpwYXNz")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 401);
+    }
+
+    #[tokio::test]
+    async fn token_only_reads_its_own_guild() {
+        // 需要 TEST_DATABASE_URL 指向可建立資料庫的 PostgreSQL，未設定時略過
+        let Some(db) = BotDB::test("api").await else {
+            return;
+        };
+        db.create_api_token(1, "bot", &hash_token("guild-1"))
+            .await
+            .unwrap();
+        db.create_api_token(2, "bot", &hash_token("guild-2"))
+            .await
+            .unwrap();
+        let addr = serve(db);
+
+        let response = get_worlds(addr, 1, Some("guild-1")).await;
+        assert_eq!(response.status(), 200);
+        let worlds: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(worlds[0]["id"], DEFAULT_WORLD_ID);
+        assert_eq!(worlds[0]["name"], DEFAULT_WORLD_NAME);
+
+        assert_eq!(get_worlds(addr, 1, Some("guild-2")).await.status(), 401);
+        assert_eq!(get_worlds(addr, 1, Some("unknown")).await.status(), 401);
+        assert_eq!(get_worlds(addr, 1, None).await.status(), 401);
+    }
+}
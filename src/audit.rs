@@ -0,0 +1,310 @@
+use crate::{
+    commands::Context,
+    db::{BotDB, LogFilter},
+    i18n::{self, tr, Locale},
+    list::ListContent,
+    permission::require_admin,
+    time::parse_time,
+};
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
+use poise::{
+    serenity_prelude::{
+        ButtonStyle, Color, ComponentInteraction, CreateActionRow, CreateButton, CreateEmbed,
+        CreateEmbedFooter, GuildChannel, ResolvedValue, User,
+    },
+    CreateReply,
+};
+use std::fmt::Write;
+
+const PAGE_SIZE: u32 = 10;
+/// 紀錄內容顯示的最大字數
+const MAX_CONTENT_LENGTH: usize = 100;
+/// 按鈕 custom_id 的最大長度
+const MAX_CUSTOM_ID_LENGTH: usize = 100;
+
+/// 取得指令參數中的礦點編號
+pub fn command_point_id(ctx: Context<'_>) -> Option<i32> {
+    let poise::Context::Application(ctx) = ctx else {
+        return None;
+    };
+    ctx.args.iter().find_map(|option| match option.value {
+        ResolvedValue::Integer(value) if option.name == "礦點" => i32::try_from(value).ok(),
+        _ => None,
+    })
+}
+
+/// 取得元件 custom_id 中的礦點編號，格式為 `prefix:世界:礦點:...`
+pub fn component_point_id(interaction: &ComponentInteraction) -> Option<i32> {
+    let (prefix, args) = interaction.data.custom_id.split_once(':')?;
+    if !matches!(prefix, "battle" | "result" | "transfer") {
+        return None;
+    }
+    args.split(':').nth(1)?.parse().ok()
+}
+
+fn timestamp(time: Option<DateTime<Utc>>) -> i64 {
+    time.map_or(0, |time| time.timestamp())
+}
+
+fn custom_id(filter: &LogFilter, page_index: u32) -> String {
+    format!(
+        "log:{}:{}:{}:{}:{}:{}",
+        filter.user_id.unwrap_or_default(),
+        filter.channel_id.unwrap_or_default(),
+        timestamp(filter.since),
+        timestamp(filter.until),
+        page_index,
+        filter.command.as_deref().unwrap_or_default()
+    )
+}
+
+/// 查詢條件是否能完整放進翻頁按鈕的 custom_id
+fn fits_custom_id(filter: &LogFilter) -> bool {
+    custom_id(filter, u32::MAX).chars().count() <= MAX_CUSTOM_ID_LENGTH
+}
+
+/// 解析按鈕的 custom_id，回傳查詢條件與頁碼
+pub fn parse(args: &str) -> Option<(LogFilter, u32)> {
+    let mut parts = args.splitn(6, ':');
+    let id = |value: u64| (value != 0).then_some(value);
+    let time = |value: i64| {
+        (value != 0)
+            .then(|| DateTime::from_timestamp(value, 0))
+            .flatten()
+    };
+
+    let user_id = id(parts.next()?.parse().ok()?);
+    let channel_id = id(parts.next()?.parse().ok()?);
+    let since = time(parts.next()?.parse().ok()?);
+    let until = time(parts.next()?.parse().ok()?);
+    let page_index = parts.next()?.parse().ok()?;
+    let command = parts
+        .next()
+        .filter(|command| !command.is_empty())
+        .map(String::from);
+
+    Some((
+        LogFilter {
+            user_id,
+            channel_id,
+            command,
+            since,
+            until,
+        },
+        page_index,
+    ))
+}
+
+pub async fn logs(
+    db: &BotDB,
+    guild_id: u64,
+    filter: &LogFilter,
+    page_index: u32,
+    locale: Locale,
+) -> Result<ListContent> {
+    let count = db.count_logs(guild_id, filter).await?;
+    let max_page = count.div_ceil(PAGE_SIZE).max(1);
+    let page_index = page_index.min(max_page - 1);
+    let records = db
+        .get_logs(guild_id, filter, page_index * PAGE_SIZE, PAGE_SIZE)
+        .await?;
+    let retention = match db.get_guild_config(guild_id).await?.log_retention_days {
+        Some(days) => tr!(locale, "log.retention", days = days),
+        None => tr!(locale, "log.forever"),
+    };
+
+    let mut description = String::new();
+    for record in records {
+        let status = match record.success {
+            Some(true) => "✅",
+            Some(false) => "❌",
+            None => "⏳",
+        };
+        let mut content: String = record.content.chars().take(MAX_CONTENT_LENGTH).collect();
+        if content.len() < record.content.len() {
+            content.push('…');
+        }
+        let _ = write!(
+            description,
+            "{} <t:{}:f> <@{}> <#{}> `{}`",
+            status,
+            record.time.timestamp(),
+            record.user_id,
+            record.channel_id,
+            content
+        );
+        if let Some(ore_point_id) = record.ore_point_id {
+            let _ = write!(
+                description,
+                " {}",
+                tr!(locale, "log.point", id = ore_point_id)
+            );
+        }
+        if let Some(error) = record.error {
+            let error: String = error.chars().take(MAX_CONTENT_LENGTH).collect();
+            let _ = write!(description, "\n> {error}");
+        }
+        description.push('\n');
+    }
+    if description.is_empty() {
+        description.push_str(&tr!(locale, "log.empty"));
+    }
+
+    let embed = CreateEmbed::new()
+        .color(Color::DARK_GREY)
+        .title(tr!(locale, "log.title"))
+        .description(description)
+        .footer(CreateEmbedFooter::new(format!(
+            "{}/{} · {} · {}",
+            page_index + 1,
+            max_page,
+            tr!(locale, "log.count", count = count),
+            retention
+        )));
+
+    let buttons = CreateActionRow::Buttons(vec![
+        if page_index > 0 {
+            CreateButton::new(custom_id(filter, page_index - 1))
+        } else {
+            CreateButton::new("0").disabled(true)
+        }
+        .emoji('◀'),
+        CreateButton::new(custom_id(filter, page_index))
+            .emoji('🔄')
+            .style(ButtonStyle::Success),
+        if page_index + 1 < max_page {
+            CreateButton::new(custom_id(filter, page_index + 1))
+        } else {
+            CreateButton::new("0").disabled(true)
+        }
+        .emoji('▶'),
+    ]);
+
+    Ok(ListContent {
+        embed,
+        component: vec![buttons],
+    })
+}
+
+/// 刪除超過保留期限的指令紀錄
+pub async fn run_purge(db: &BotDB) -> Result<()> {
+    let count = db.purge_logs(Utc::now()).await?;
+    if count > 0 {
+        tracing::info!("purged {count} command logs");
+    }
+    Ok(())
+}
+
+/// 查詢指令紀錄
+#[poise::command(slash_command, rename = "紀錄", check = "require_admin", ephemeral)]
+pub async fn audit_log(
+    ctx: Context<'_>,
+    #[rename = "玩家"]
+    #[description = "只顯示此玩家的紀錄"]
+    user: Option<User>,
+    #[rename = "頻道"]
+    #[description = "只顯示此頻道的紀錄"]
+    channel: Option<GuildChannel>,
+    #[max_length = 64]
+    #[rename = "指令"]
+    #[description = "只顯示此指令的紀錄，例如 強制佔領"]
+    command: Option<String>,
+    #[rename = "開始時間"]
+    #[description = "只顯示此時間之後的紀錄 (YYYY-MM-DD HH:MM)"]
+    since: Option<String>,
+    #[rename = "結束時間"]
+    #[description = "只顯示此時間之前的紀錄 (YYYY-MM-DD HH:MM)"]
+    until: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let locale = i18n::locale(ctx).await;
+
+    let parse = |input: Option<String>| input.map(|input| parse_time(&input).ok_or(())).transpose();
+    let (Ok(since), Ok(until)) = (parse(since), parse(until)) else {
+        ctx.reply(tr!(locale, "time.invalid")).await?;
+        return Ok(());
+    };
+
+    let filter = LogFilter {
+        user_id: user.map(|user| user.id.get()),
+        channel_id: channel.map(|channel| channel.id.get()),
+        command: command.map(|command| command.trim().to_owned()),
+        since,
+        until,
+    };
+    if !fits_custom_id(&filter) {
+        ctx.reply(tr!(locale, "log.filter_too_long")).await?;
+        return Ok(());
+    }
+
+    let content = logs(ctx.data(), guild_id, &filter, 0, locale).await?;
+    ctx.send(
+        CreateReply::default()
+            .embed(content.embed)
+            .components(content.component)
+            .reply(true)
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn custom_id_round_trip() {
+        let filter = LogFilter {
+            user_id: Some(123),
+            channel_id: Some(456),
+            command: Some("礦點 設定:1".to_owned()),
+            since: Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()),
+            until: Some(Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap()),
+        };
+        let id = custom_id(&filter, 3);
+        let (parsed, page_index) = parse(id.strip_prefix("log:").unwrap()).unwrap();
+        assert_eq!(page_index, 3);
+        assert_eq!(parsed.user_id, filter.user_id);
+        assert_eq!(parsed.channel_id, filter.channel_id);
+        assert_eq!(parsed.command, filter.command);
+        assert_eq!(parsed.since, filter.since);
+        assert_eq!(parsed.until, filter.until);
+    }
+
+    #[test]
+    fn parse_empty_filter() {
+        let (filter, page_index) = parse("0:0:0:0:0:").unwrap();
+        assert_eq!(page_index, 0);
+        assert!(filter.user_id.is_none() && filter.channel_id.is_none());
+        assert!(filter.command.is_none());
+        assert!(filter.since.is_none() && filter.until.is_none());
+        assert!(parse("0:0:0:0").is_none());
+        assert!(parse("x:0:0:0:0:").is_none());
+    }
+
+    #[test]
+    fn long_filter_is_rejected() {
+        let mut filter = LogFilter {
+            user_id: Some(u64::MAX),
+            channel_id: Some(u64::MAX),
+            command: Some("a".repeat(20)),
+            ..Default::default()
+        };
+        assert!(fits_custom_id(&filter));
+        filter.command = Some("a".repeat(64));
+        assert!(!fits_custom_id(&filter));
+    }
+
+    #[test]
+    fn command_pattern_escapes_wildcards() {
+        let filter = LogFilter {
+            command: Some(r"50%_a\b".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(filter.command_pattern().as_deref(), Some(r"50\%\_a\\b%"));
+        assert_eq!(LogFilter::default().command_pattern(), None);
+    }
+}
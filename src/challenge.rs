@@ -0,0 +1,1432 @@
+use crate::{
+    commands::Context,
+    db::{BattleProposal, BotDB, HistoryData, OccupyData, QueueData},
+    events::{self, EventKind},
+    freeze::not_frozen,
+    i18n::{self, tr, Locale},
+    link::{linked_names, mention, mention_player},
+    notify::{self, NotifyEvent},
+    permission::{interaction_level, require_member, require_officer, BotRole},
+    structs::{HistoryAction, OrePoint},
+    team::member_team,
+    time::parse_time,
+    undo, vacation,
+    world::{autocomplete_world, resolve_world},
+};
+use anyhow::{Context as _, Error, Result};
+use chrono::{DateTime, Days, Duration as ChronoDuration, Utc};
+use poise::{
+    serenity_prelude::{
+        ButtonStyle, ChannelId, ChannelType, ComponentInteraction, Context as SerenityContext,
+        CreateActionRow, CreateAllowedMentions, CreateButton, CreateForumPost,
+        CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, CreateThread,
+        EditThread, Http, UserId,
+    },
+    CreateReply, Modal,
+};
+use std::time::Duration;
+
+type ApplicationContext<'a> = poise::ApplicationContext<'a, BotDB, Error>;
+
+/// 挑戰開始前提醒的時間 (分鐘)
+const REMINDER_MINUTES: i64 = 15;
+
+pub struct Resolution {
+    pub winner: u64,
+    pub loser: u64,
+    pub next_challenger: Option<u64>,
+    pub thread_id: Option<u64>,
+}
+
+#[derive(poise::ChoiceParameter)]
+enum Winner {
+    #[name = "佔領者"]
+    Owner,
+    #[name = "挑戰者"]
+    Challenger,
+}
+
+#[derive(Modal)]
+#[name = "約戰時間"]
+struct BattleTimeModal {
+    #[name = "時段 1"]
+    #[placeholder = "YYYY-MM-DD HH:MM"]
+    first: String,
+    #[name = "時段 2"]
+    #[placeholder = "YYYY-MM-DD HH:MM"]
+    second: Option<String>,
+    #[name = "時段 3"]
+    #[placeholder = "YYYY-MM-DD HH:MM"]
+    third: Option<String>,
+}
+
+/// 取得玩家挑戰落敗或撤回後可再次挑戰礦點的時間，不在冷卻中時回傳 None
+pub async fn cooldown_until(
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    user_id: u64,
+    ore_point_id: i32,
+) -> Result<Option<DateTime<Utc>>> {
+    let config = db.get_guild_config(guild_id).await?;
+    let mut ends = Vec::new();
+    for (hours, ore_point_id) in [
+        (config.point_cooldown_hours, Some(ore_point_id)),
+        (config.player_cooldown_hours, None),
+    ] {
+        let Some(hours) = hours else { continue };
+        let Some(last) = db
+            .last_challenge_loss(guild_id, world_id, user_id, ore_point_id)
+            .await?
+        else {
+            continue;
+        };
+        let freezes = db.get_freezes(guild_id, last).await?;
+        ends.push(cooldown_end(last, hours, &freezes));
+    }
+    Ok(active_cooldown(ends, Utc::now()))
+}
+
+/// 落敗後經過冷卻時數的時間，不計入冷卻期間內凍結的時間
+fn cooldown_end(
+    last: DateTime<Utc>,
+    hours: i32,
+    freezes: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> DateTime<Utc> {
+    extend_for_freezes(last, last + ChronoDuration::hours(hours.into()), freezes)
+}
+
+/// 礦點與玩家冷卻中最晚結束的時間，皆已結束時回傳 None
+fn active_cooldown(ends: Vec<DateTime<Utc>>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    ends.into_iter().max().filter(|until| *until > now)
+}
+
+/// 將冷卻結束時間延後冷卻期間內凍結的時間，凍結期間需依開始時間排列
+fn extend_for_freezes(
+    start: DateTime<Utc>,
+    mut until: DateTime<Utc>,
+    freezes: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> DateTime<Utc> {
+    for &(freeze_start, freeze_end) in freezes {
+        if freeze_end <= start {
+            continue;
+        }
+        if freeze_start >= until {
+            break;
+        }
+        until += freeze_end - freeze_start.max(start);
+    }
+    until
+}
+
+/// 確認玩家或其隊伍是否已佔領或挑戰同類礦點
+pub async fn exceeds_type_limit(
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    user_id: u64,
+    team_id: Option<i32>,
+    ore_type: i32,
+) -> Result<bool> {
+    if db
+        .has_occupy_type(guild_id, world_id, user_id, team_id, ore_type)
+        .await?
+    {
+        return Ok(true);
+    }
+
+    // 依設定計入排隊中的挑戰
+    let config = db.get_guild_config(guild_id).await?;
+    Ok(config.queue_counts_limit
+        && db
+            .has_queued_type(guild_id, world_id, user_id, team_id, ore_type)
+            .await?)
+}
+
+/// 由隊列遞補下一位挑戰者
+pub async fn promote_next(
+    http: &Http,
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    ore_point_id: i32,
+) -> Result<Option<u64>> {
+    let point = OrePoint::iter()
+        .find(|p| p.id == ore_point_id)
+        .context("找不到礦點")?;
+    let Some(mut data) = db.get_occupy_data(guild_id, world_id, ore_point_id).await? else {
+        return Ok(None);
+    };
+    // 未到期、防守保護期間、凍結期間或佔領者休假保護期間不遞補，之後由排程遞補
+    let now = Utc::now();
+    if !can_promote(&data, now) {
+        return Ok(None);
+    }
+    if db.get_guild_config(guild_id).await?.frozen_at.is_some() {
+        return Ok(None);
+    }
+    if db
+        .get_vacation(guild_id, world_id, data.user_id, now)
+        .await?
+        .is_some_and(|vacation| vacation::protects(&vacation, now))
+    {
+        return Ok(None);
+    }
+
+    for (user_id, team_id) in db.get_queue(guild_id, world_id, ore_point_id).await? {
+        // 已成為佔領者或同隊伍的玩家移出隊列
+        if is_owner_side(&data, user_id, team_id) {
+            db.leave_queue(guild_id, world_id, ore_point_id, user_id)
+                .await?;
+            continue;
+        }
+        // 已達上限或仍在冷卻中的玩家保留在隊列中
+        if exceeds_type_limit(db, guild_id, world_id, user_id, team_id, point.ore_type).await?
+            || cooldown_until(db, guild_id, world_id, user_id, ore_point_id)
+                .await?
+                .is_some()
+        {
+            continue;
+        }
+        db.leave_queue(guild_id, world_id, ore_point_id, user_id)
+            .await?;
+
+        let owner_id = data.user_id;
+        data.battle_user_id = Some(user_id);
+        data.battle_team_id = team_id;
+        db.update_occupy_data(data).await?;
+        db.add_history(HistoryData {
+            guild_id,
+            world_id,
+            team_id,
+            ore_point_id,
+            user_id,
+            target_user_id: Some(owner_id),
+            action: HistoryAction::Challenge,
+        })
+        .await?;
+        events::emit(
+            EventKind::Challenged,
+            guild_id,
+            world_id,
+            ore_point_id,
+            user_id,
+            Some(owner_id),
+        );
+        let locale = i18n::guild_locale(db, guild_id).await;
+        notify::announce(
+            http,
+            db,
+            guild_id,
+            world_id,
+            &point,
+            NotifyEvent::Challenge,
+            &tr!(
+                locale,
+                "challenge.promoted_announce",
+                challenger = mention_player(db, guild_id, world_id, user_id).await,
+                owner = mention_player(db, guild_id, world_id, owner_id).await,
+                point = point.label(locale)
+            ),
+        )
+        .await;
+        open_thread(http, db, guild_id, world_id, ore_point_id).await;
+        return Ok(Some(user_id));
+    }
+
+    Ok(None)
+}
+
+/// 依挑戰結果更新佔領資料並清除挑戰狀態，回傳 (勝者, 敗者, 勝者隊伍)
+fn settle(
+    data: &mut OccupyData,
+    battle_user_id: u64,
+    challenger_won: bool,
+    now: DateTime<Utc>,
+    shield_hours: Option<i32>,
+) -> Result<(u64, u64, Option<i32>)> {
+    let owner_id = data.user_id;
+    let result = if challenger_won {
+        (battle_user_id, owner_id, data.battle_team_id)
+    } else {
+        (owner_id, battle_user_id, data.team_id)
+    };
+
+    if challenger_won {
+        data.user_id = battle_user_id;
+        data.team_id = data.battle_team_id;
+        data.due_time = now
+            .checked_add_days(Days::new(14))
+            .context("Failed to add days")?;
+        data.shield_until = None;
+    } else if let Some(hours) = shield_hours {
+        // 防守成功的佔領者在保護期間內不會被登記挑戰
+        data.shield_until = Some(now + ChronoDuration::hours(hours.into()));
+    }
+    data.battle_user_id = None;
+    data.battle_team_id = None;
+    data.battle_time = None;
+    data.battle_thread_id = None;
+    Ok(result)
+}
+
+/// 礦點是否已到期、未在挑戰中且不在防守保護期
+fn can_promote(data: &OccupyData, now: DateTime<Utc>) -> bool {
+    data.battle_user_id.is_none()
+        && data.due_time <= now
+        && data.shield_until.is_none_or(|time| time <= now)
+}
+
+/// 排隊的玩家是否為佔領者本人或其隊友
+fn is_owner_side(data: &OccupyData, user_id: u64, team_id: Option<i32>) -> bool {
+    user_id == data.user_id || (team_id.is_some() && team_id == data.team_id)
+}
+
+fn next_challenger_message(locale: Locale, next_challenger: Option<u64>) -> String {
+    next_challenger.map_or(String::new(), |user_id| {
+        format!(
+            "\n{}",
+            tr!(locale, "challenge.promoted", user = format!("<@{user_id}>"))
+        )
+    })
+}
+
+fn resolution_message(locale: Locale, point: &OrePoint, result: &Resolution) -> String {
+    format!(
+        "{}{}",
+        tr!(
+            locale,
+            "challenge.resolved",
+            winner = format!("<@{}>", result.winner),
+            loser = format!("<@{}>", result.loser),
+            point = point.label(locale)
+        ),
+        next_challenger_message(locale, result.next_challenger)
+    )
+}
+
+fn result_buttons(locale: Locale, world_id: i32, ore_point_id: i32) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("result:{}:{}:owner", world_id, ore_point_id))
+            .label(tr!(locale, "challenge.owner_won"))
+            .style(ButtonStyle::Primary),
+        CreateButton::new(format!("result:{}:{}:challenger", world_id, ore_point_id))
+            .label(tr!(locale, "challenge.challenger_won"))
+            .style(ButtonStyle::Danger),
+    ])
+}
+
+/// 在設定的頻道為挑戰建立討論串，並加入雙方玩家
+pub async fn open_thread(http: &Http, db: &BotDB, guild_id: u64, world_id: i32, ore_point_id: i32) {
+    if let Err(err) = create_thread(http, db, guild_id, world_id, ore_point_id).await {
+        tracing::error!("{err}");
+    }
+}
+
+async fn create_thread(
+    http: &Http,
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    ore_point_id: i32,
+) -> Result<()> {
+    let config = db.get_guild_config(guild_id).await?;
+    let Some(channel_id) = config.challenge_channel_id else {
+        return Ok(());
+    };
+    let point = OrePoint::iter()
+        .find(|p| p.id == ore_point_id)
+        .context("找不到礦點")?;
+    let data = db
+        .get_occupy_data(guild_id, world_id, ore_point_id)
+        .await?
+        .context("礦點未被佔領")?;
+    let battle_user_id = data.battle_user_id.context("礦點沒有挑戰者")?;
+    let names = linked_names(db, guild_id, world_id).await?;
+    let locale = i18n::guild_locale(db, guild_id).await;
+
+    let name = tr!(
+        locale,
+        "challenge.thread_name",
+        id = point.id,
+        name = point.localized_name(locale)
+    );
+    let message = CreateMessage::new()
+        .allowed_mentions(CreateAllowedMentions::new().all_users(true))
+        .content(tr!(
+            locale,
+            "challenge.thread",
+            owner = mention(&names, data.user_id),
+            challenger = mention(&names, battle_user_id),
+            point = point.label(locale),
+            time = data.due_time.timestamp(),
+            hours = config.proposal_timeout_hours
+        ))
+        .components(vec![result_buttons(locale, world_id, ore_point_id)]);
+
+    // 論壇頻道建立貼文，文字頻道建立公開討論串
+    let channel_id = ChannelId::new(channel_id as u64);
+    let is_forum = channel_id
+        .to_channel(http)
+        .await?
+        .guild()
+        .is_some_and(|channel| channel.kind == ChannelType::Forum);
+    let (thread, message) = if is_forum {
+        let thread = channel_id
+            .create_forum_post(http, CreateForumPost::new(name, message))
+            .await?;
+        (thread, None)
+    } else {
+        let thread = channel_id
+            .create_thread(
+                http,
+                CreateThread::new(name).kind(ChannelType::PublicThread),
+            )
+            .await?;
+        (thread, Some(message))
+    };
+    // 先記錄討論串，之後的步驟失敗時仍可封存
+    db.set_battle_thread(guild_id, world_id, ore_point_id, thread.id.get())
+        .await?;
+    if let Some(message) = message {
+        thread.id.send_message(http, message).await?;
+    }
+
+    for user_id in [data.user_id, battle_user_id] {
+        // 玩家已離開伺服器或缺少權限時略過
+        if let Err(err) = thread
+            .id
+            .add_thread_member(http, UserId::new(user_id))
+            .await
+        {
+            tracing::warn!("add thread member {user_id}: {err}");
+        }
+    }
+    Ok(())
+}
+
+/// 在挑戰討論串發送結果並封存
+pub async fn close_thread(http: &Http, thread_id: u64, content: Option<String>) {
+    let thread_id = ChannelId::new(thread_id);
+    if let Some(content) = content {
+        let message = thread_id
+            .send_message(
+                http,
+                CreateMessage::new()
+                    .allowed_mentions(CreateAllowedMentions::new().all_users(true))
+                    .content(content),
+            )
+            .await;
+        if let Err(err) = message {
+            tracing::error!("{err}");
+        }
+    }
+    let result = thread_id
+        .edit_thread(http, EditThread::new().archived(true).locked(true))
+        .await;
+    if let Err(err) = result {
+        tracing::error!("{err}");
+    }
+}
+
+/// 結算礦點的挑戰，挑戰者獲勝時取得礦點
+pub async fn resolve(
+    http: &Http,
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    ore_point_id: i32,
+    challenger_won: bool,
+) -> Result<Option<Resolution>> {
+    let Some(mut data) = db.get_occupy_data(guild_id, world_id, ore_point_id).await? else {
+        return Ok(None);
+    };
+    let Some(battle_user_id) = data.battle_user_id else {
+        return Ok(None);
+    };
+
+    let thread_id = data.battle_thread_id;
+    let shield_hours = db.get_guild_config(guild_id).await?.shield_hours;
+    let (winner, loser, team_id) = settle(
+        &mut data,
+        battle_user_id,
+        challenger_won,
+        Utc::now(),
+        shield_hours,
+    )?;
+
+    // 同時登記的結果只有一個會結算
+    let settled = db
+        .settle_challenge(
+            data,
+            battle_user_id,
+            HistoryData {
+                guild_id,
+                world_id,
+                team_id,
+                ore_point_id,
+                user_id: winner,
+                target_user_id: Some(loser),
+                action: HistoryAction::ChallengeWon,
+            },
+        )
+        .await?;
+    if !settled {
+        return Ok(None);
+    }
+
+    events::emit(
+        EventKind::Resolved,
+        guild_id,
+        world_id,
+        ore_point_id,
+        winner,
+        Some(loser),
+    );
+
+    // 新的佔領者不需要繼續排隊
+    db.leave_queue(guild_id, world_id, ore_point_id, winner)
+        .await?;
+    let next_challenger = promote_next(http, db, guild_id, world_id, ore_point_id).await?;
+
+    let result = Resolution {
+        winner,
+        loser,
+        next_challenger,
+        thread_id,
+    };
+    if let Some(point) = OrePoint::iter().find(|p| p.id == ore_point_id) {
+        let locale = i18n::guild_locale(db, guild_id).await;
+        notify::announce(
+            http,
+            db,
+            guild_id,
+            world_id,
+            &point,
+            NotifyEvent::Resolve,
+            &resolution_message(locale, &point, &result),
+        )
+        .await;
+    }
+    Ok(Some(result))
+}
+
+/// 登記挑戰結果
+#[poise::command(slash_command, rename = "挑戰結果", check = "require_officer")]
+pub async fn resolve_challenge(
+    ctx: Context<'_>,
+    #[rename = "礦點"]
+    #[description = "挑戰的礦點編號"]
+    point_id: i32,
+    #[rename = "勝者"]
+    #[description = "挑戰的勝者"]
+    winner: Winner,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let locale = i18n::locale(ctx).await;
+
+    let point = OrePoint::iter()
+        .find(|p| p.id == point_id)
+        .context("找不到礦點")?;
+
+    let challenger_won = matches!(winner, Winner::Challenger);
+    let previous = ctx
+        .data()
+        .get_occupy_data(guild_id, world_id, point.id)
+        .await?;
+    let Some(result) = resolve(
+        ctx.http(),
+        ctx.data(),
+        guild_id,
+        world_id,
+        point.id,
+        challenger_won,
+    )
+    .await?
+    else {
+        ctx.send(
+            CreateReply::default()
+                .reply(true)
+                .ephemeral(true)
+                .content(tr!(locale, "challenge.no_challenge")),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    undo::record(
+        ctx.data(),
+        guild_id,
+        world_id,
+        point.id,
+        ctx.author().id.get(),
+        HistoryAction::ChallengeWon,
+        previous,
+    )
+    .await?;
+
+    ctx.reply(resolution_message(locale, &point, &result))
+        .await?;
+
+    if let Some(thread_id) = result.thread_id {
+        // 在討論串中執行時不重複發送結果
+        let guild_locale = i18n::guild_locale(ctx.data(), guild_id).await;
+        let content = (thread_id != ctx.channel_id().get())
+            .then(|| resolution_message(guild_locale, &point, &result));
+        close_thread(ctx.http(), thread_id, content).await;
+    }
+    Ok(())
+}
+
+/// 撤回對礦點的挑戰
+#[poise::command(slash_command, rename = "撤回挑戰", check = "require_member")]
+pub async fn withdraw_challenge(
+    ctx: Context<'_>,
+    #[rename = "礦點"]
+    #[description = "挑戰的礦點編號"]
+    point_id: i32,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let user_id = ctx.author().id.get();
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+
+    let point = OrePoint::iter()
+        .find(|p| p.id == point_id)
+        .context("找不到礦點")?;
+
+    let data = db.get_occupy_data(guild_id, world_id, point.id).await?;
+    let Some(mut data) = data.filter(|data| data.battle_user_id == Some(user_id)) else {
+        ctx.send(
+            CreateReply::default()
+                .reply(true)
+                .ephemeral(true)
+                .content(tr!(locale, "challenge.not_challenger")),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let owner_id = data.user_id;
+    let team_id = data.battle_team_id;
+    let thread_id = data.battle_thread_id;
+    data.battle_user_id = None;
+    data.battle_team_id = None;
+    data.battle_time = None;
+    data.battle_thread_id = None;
+    db.update_occupy_data(data).await?;
+    db.delete_battle_proposal(guild_id, world_id, point.id)
+        .await?;
+    db.add_history(HistoryData {
+        guild_id,
+        world_id,
+        team_id,
+        ore_point_id: point.id,
+        user_id,
+        target_user_id: Some(owner_id),
+        action: HistoryAction::Withdraw,
+    })
+    .await?;
+
+    let next_challenger = promote_next(ctx.http(), db, guild_id, world_id, point.id).await?;
+
+    ctx.reply(format!(
+        "{}{}",
+        tr!(
+            locale,
+            "challenge.withdrawn_reply",
+            point = point.label(locale)
+        ),
+        next_challenger_message(locale, next_challenger)
+    ))
+    .await?;
+
+    if let Some(thread_id) = thread_id {
+        let guild_locale = i18n::guild_locale(db, guild_id).await;
+        let content = (thread_id != ctx.channel_id().get()).then(|| {
+            tr!(
+                guild_locale,
+                "challenge.withdrawn",
+                user = format!("<@{user_id}>")
+            )
+        });
+        close_thread(ctx.http(), thread_id, content).await;
+    }
+    Ok(())
+}
+
+/// 加入礦點的挑戰隊列
+#[poise::command(
+    slash_command,
+    rename = "排隊",
+    check = "require_member",
+    check = "not_frozen",
+    ephemeral
+)]
+pub async fn join_queue(
+    ctx: Context<'_>,
+    #[rename = "礦點"]
+    #[description = "排隊挑戰的礦點編號"]
+    point_id: i32,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let user_id = ctx.author().id.get();
+    let team_id = member_team(ctx, user_id).await?;
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+
+    let point = OrePoint::iter()
+        .find(|p| p.id == point_id)
+        .context("找不到礦點")?;
+
+    let Some(data) = db.get_occupy_data(guild_id, world_id, point.id).await? else {
+        ctx.reply(tr!(locale, "queue.not_occupied")).await?;
+        return Ok(());
+    };
+    if data.battle_user_id.is_none() {
+        ctx.reply(tr!(locale, "queue.no_challenger")).await?;
+        return Ok(());
+    }
+    if data.user_id == user_id || data.battle_user_id == Some(user_id) {
+        ctx.reply(tr!(locale, "queue.involved")).await?;
+        return Ok(());
+    }
+    if let Some(until) = cooldown_until(db, guild_id, world_id, user_id, point.id).await? {
+        ctx.reply(tr!(locale, "occupy.cooldown", time = until.timestamp()))
+            .await?;
+        return Ok(());
+    }
+    if exceeds_type_limit(db, guild_id, world_id, user_id, team_id, point.ore_type).await? {
+        ctx.reply(tr!(locale, "occupy.type_limit")).await?;
+        return Ok(());
+    }
+
+    let joined = db
+        .join_queue(QueueData {
+            guild_id,
+            world_id,
+            ore_point_id: point.id,
+            user_id,
+            team_id,
+        })
+        .await?;
+    if !joined {
+        ctx.reply(tr!(locale, "queue.already_queued")).await?;
+        return Ok(());
+    }
+
+    let queue = db.get_queue(guild_id, world_id, point.id).await?;
+    let position = queue.iter().position(|&(x, _)| x == user_id).unwrap_or(0) + 1;
+    ctx.reply(tr!(
+        locale,
+        "queue.joined",
+        point = point.label(locale),
+        position = position
+    ))
+    .await?;
+    Ok(())
+}
+
+/// 離開礦點的挑戰隊列
+#[poise::command(
+    slash_command,
+    rename = "取消排隊",
+    check = "require_member",
+    ephemeral
+)]
+pub async fn leave_queue(
+    ctx: Context<'_>,
+    #[rename = "礦點"]
+    #[description = "排隊挑戰的礦點編號"]
+    point_id: i32,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let user_id = ctx.author().id.get();
+    let locale = i18n::locale(ctx).await;
+
+    let point = OrePoint::iter()
+        .find(|p| p.id == point_id)
+        .context("找不到礦點")?;
+
+    if ctx
+        .data()
+        .leave_queue(guild_id, world_id, point.id, user_id)
+        .await?
+    {
+        ctx.reply(tr!(locale, "queue.left", point = point.label(locale)))
+            .await?;
+    } else {
+        ctx.reply(tr!(locale, "queue.not_queued")).await?;
+    }
+    Ok(())
+}
+
+/// 向佔領者提出挑戰時間
+#[poise::command(
+    slash_command,
+    rename = "約戰",
+    check = "require_member",
+    check = "not_frozen"
+)]
+pub async fn propose_battle(
+    ctx: ApplicationContext<'_>,
+    #[rename = "礦點"]
+    #[description = "挑戰的礦點編號"]
+    point_id: i32,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx.into(), world).await? else {
+        return Ok(());
+    };
+    let user_id = ctx.author().id.get();
+    let db = ctx.data();
+    let locale = i18n::locale(ctx.into()).await;
+
+    let point = OrePoint::iter()
+        .find(|p| p.id == point_id)
+        .context("找不到礦點")?;
+
+    let data = db.get_occupy_data(guild_id, world_id, point.id).await?;
+    let Some(data) = data.filter(|data| data.battle_user_id == Some(user_id)) else {
+        ctx.send(
+            CreateReply::default()
+                .reply(true)
+                .ephemeral(true)
+                .content(tr!(locale, "challenge.not_challenger")),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    if let Some(battle_time) = data.battle_time {
+        ctx.send(
+            CreateReply::default()
+                .reply(true)
+                .ephemeral(true)
+                .content(tr!(
+                    locale,
+                    "battle.scheduled",
+                    time = battle_time.timestamp()
+                )),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(modal) =
+        poise::execute_modal::<_, _, BattleTimeModal>(ctx, None, Some(Duration::from_secs(600)))
+            .await?
+    else {
+        return Ok(());
+    };
+
+    // 解析提出的時段
+    let now = Utc::now();
+    let mut slots = Vec::new();
+    for input in [Some(modal.first), modal.second, modal.third]
+        .into_iter()
+        .flatten()
+        .filter(|input| !input.trim().is_empty())
+    {
+        match parse_time(&input) {
+            Some(time) if time > now => slots.push(time),
+            _ => {
+                ctx.send(CreateReply::default().ephemeral(true).content(tr!(
+                    locale,
+                    "battle.invalid_slot",
+                    input = input
+                )))
+                .await?;
+                return Ok(());
+            }
+        }
+    }
+    if slots.is_empty() {
+        ctx.send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content(tr!(locale, "battle.no_slot")),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    // 提出的時段給佔領者選擇，使用伺服器預設的語言
+    let timeout_hours = db.get_guild_config(guild_id).await?.proposal_timeout_hours;
+    let guild_locale = i18n::guild_locale(db, guild_id).await;
+    let content = slots.iter().enumerate().fold(
+        format!(
+            "{}\n",
+            tr!(
+                guild_locale,
+                "battle.proposed",
+                challenger = format!("<@{user_id}>"),
+                owner = format!("<@{}>", data.user_id),
+                point = point.label(guild_locale),
+                hours = timeout_hours
+            )
+        ),
+        |content, (index, slot)| {
+            content + &format!("`{}.` <t:{}:F>\n", index + 1, slot.timestamp())
+        },
+    );
+    let buttons = (0..slots.len())
+        .map(|index| {
+            CreateButton::new(format!("battle:{}:{}:{}", world_id, point.id, index))
+                .label(tr!(guild_locale, "battle.slot", index = index + 1))
+                .style(ButtonStyle::Primary)
+        })
+        .collect();
+
+    // 有挑戰討論串時在討論串中提出，訊息送出後才記錄約戰
+    let channel_id = data
+        .battle_thread_id
+        .unwrap_or_else(|| ctx.channel_id().get());
+    let proposal = BattleProposal {
+        guild_id,
+        world_id,
+        ore_point_id: point.id,
+        channel_id,
+        slots,
+    };
+
+    if channel_id == ctx.channel_id().get() {
+        ctx.send(
+            CreateReply::default()
+                .allowed_mentions(CreateAllowedMentions::new().all_users(true))
+                .content(content)
+                .components(vec![CreateActionRow::Buttons(buttons)]),
+        )
+        .await?;
+        db.create_battle_proposal(proposal).await?;
+    } else {
+        ChannelId::new(channel_id)
+            .send_message(
+                ctx,
+                CreateMessage::new()
+                    .allowed_mentions(CreateAllowedMentions::new().all_users(true))
+                    .content(content)
+                    .components(vec![CreateActionRow::Buttons(buttons)]),
+            )
+            .await?;
+        db.create_battle_proposal(proposal).await?;
+        ctx.send(CreateReply::default().ephemeral(true).content(tr!(
+            locale,
+            "battle.proposed_in",
+            channel = format!("<#{channel_id}>")
+        )))
+        .await?;
+    }
+    Ok(())
+}
+
+/// 佔領者選擇挑戰時段
+pub async fn accept_battle(
+    ctx: &SerenityContext,
+    db: &BotDB,
+    interaction: &ComponentInteraction,
+    args: &str,
+) -> Result<()> {
+    let guild_id = interaction.guild_id.context("Unknown guild_id")?.get();
+    let (world_id, ore_point_id, index): (i32, i32, usize) = (|| {
+        let mut parts = args.split(':');
+        Some((
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+        ))
+    })()
+    .context("parse custom_id error")?;
+
+    let point = OrePoint::iter()
+        .find(|p| p.id == ore_point_id)
+        .context("找不到礦點")?;
+
+    let data = db
+        .get_occupy_data(guild_id, world_id, ore_point_id)
+        .await?
+        .context("礦點未被佔領")?;
+    let locale = i18n::interaction_locale(db, Some(guild_id), &interaction.locale).await;
+
+    let reply = |content: String| {
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .ephemeral(true)
+                .content(content),
+        )
+    };
+
+    // 凍結期間不能約定對戰時段
+    if let Some(frozen_at) = db.get_guild_config(guild_id).await?.frozen_at {
+        interaction
+            .create_response(
+                ctx,
+                reply(tr!(locale, "freeze.frozen", time = frozen_at.timestamp())),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    if data.user_id != interaction.user.id.get() {
+        interaction
+            .create_response(ctx, reply(tr!(locale, "battle.owner_only")))
+            .await?;
+        return Ok(());
+    }
+
+    let proposal = db
+        .get_battle_proposal(guild_id, world_id, ore_point_id)
+        .await?;
+    let Some(battle_time) = proposal.and_then(|proposal| proposal.slots.get(index).copied()) else {
+        interaction
+            .create_response(ctx, reply(tr!(locale, "battle.expired")))
+            .await?;
+        return Ok(());
+    };
+
+    if battle_time <= Utc::now() {
+        interaction
+            .create_response(ctx, reply(tr!(locale, "battle.slot_passed")))
+            .await?;
+        return Ok(());
+    }
+
+    db.set_battle_time(
+        guild_id,
+        world_id,
+        ore_point_id,
+        battle_time,
+        interaction.channel_id.get(),
+    )
+    .await?;
+    db.delete_battle_proposal(guild_id, world_id, ore_point_id)
+        .await?;
+
+    let guild_locale = i18n::guild_locale(db, guild_id).await;
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(tr!(
+                        guild_locale,
+                        "battle.accepted",
+                        owner = format!("<@{}>", data.user_id),
+                        challenger = format!("<@{}>", data.battle_user_id.unwrap_or_default()),
+                        time = battle_time.timestamp(),
+                        point = point.label(guild_locale)
+                    ))
+                    .components(Vec::new()),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// 防守保護或休假保護結束後由隊列遞補挑戰者
+pub async fn run_promotions(http: &Http, db: &BotDB) -> Result<()> {
+    for (guild_id, world_id, ore_point_id) in db.get_promotable(Utc::now()).await? {
+        // 單一礦點失敗時不影響其他礦點
+        if let Err(err) = promote_next(http, db, guild_id, world_id, ore_point_id).await {
+            tracing::error!("{err}");
+        }
+    }
+    Ok(())
+}
+
+/// 佔領者未於時限內回應約戰時判定棄權
+pub async fn run_forfeits(http: &Http, db: &BotDB) -> Result<()> {
+    for proposal in db.get_expired_battle_proposals(Utc::now()).await? {
+        // 單一約戰失敗時不影響其他約戰
+        let result = match resolve(
+            http,
+            db,
+            proposal.guild_id,
+            proposal.world_id,
+            proposal.ore_point_id,
+            true,
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                tracing::error!("{err}");
+                continue;
+            }
+        };
+
+        // 挑戰已被結算時只需移除約戰
+        let Some(result) = result else {
+            if let Err(err) = db
+                .delete_battle_proposal(proposal.guild_id, proposal.world_id, proposal.ore_point_id)
+                .await
+            {
+                tracing::error!("{err}");
+            }
+            continue;
+        };
+
+        let Some(point) = OrePoint::iter().find(|p| p.id == proposal.ore_point_id) else {
+            continue;
+        };
+        let locale = i18n::guild_locale(db, proposal.guild_id).await;
+        let content = format!(
+            "{}{}",
+            tr!(
+                locale,
+                "battle.forfeit",
+                loser = format!("<@{}>", result.loser),
+                winner = format!("<@{}>", result.winner),
+                point = point.label(locale)
+            ),
+            next_challenger_message(locale, result.next_challenger)
+        );
+
+        if let Some(thread_id) = result.thread_id {
+            close_thread(http, thread_id, Some(content)).await;
+            continue;
+        }
+        let message = ChannelId::new(proposal.channel_id)
+            .send_message(
+                http,
+                CreateMessage::new()
+                    .allowed_mentions(CreateAllowedMentions::new().all_users(true))
+                    .content(content),
+            )
+            .await;
+        if let Err(err) = message {
+            tracing::error!("{err}");
+        }
+    }
+    Ok(())
+}
+
+/// 由討論串中的按鈕登記挑戰結果，限落敗方或管理員
+pub async fn report_result(
+    ctx: &SerenityContext,
+    db: &BotDB,
+    interaction: &ComponentInteraction,
+    args: &str,
+) -> Result<()> {
+    let guild_id = interaction.guild_id.context("Unknown guild_id")?.get();
+    let (world_id, ore_point_id, challenger_won): (i32, i32, bool) = (|| {
+        let mut parts = args.split(':');
+        Some((
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+            parts.next()? == "challenger",
+        ))
+    })()
+    .context("parse custom_id error")?;
+
+    let point = OrePoint::iter()
+        .find(|p| p.id == ore_point_id)
+        .context("找不到礦點")?;
+    let locale = i18n::interaction_locale(db, Some(guild_id), &interaction.locale).await;
+
+    let reply = |content: String| {
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .ephemeral(true)
+                .content(content),
+        )
+    };
+
+    // 凍結期間不能登記挑戰結果
+    if let Some(frozen_at) = db.get_guild_config(guild_id).await?.frozen_at {
+        interaction
+            .create_response(
+                ctx,
+                reply(tr!(locale, "freeze.frozen", time = frozen_at.timestamp())),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let Some(data) = db
+        .get_occupy_data(guild_id, world_id, ore_point_id)
+        .await?
+        .filter(|data| data.battle_user_id.is_some())
+    else {
+        interaction
+            .create_response(ctx, reply(tr!(locale, "result.settled")))
+            .await?;
+        return Ok(());
+    };
+
+    let user_id = interaction.user.id.get();
+    let loser_id = if challenger_won {
+        Some(data.user_id)
+    } else {
+        data.battle_user_id
+    };
+    let is_officer = interaction_level(db, guild_id, interaction.member.as_ref())
+        .await?
+        .is_some_and(|level| level >= BotRole::Officer);
+    if loser_id != Some(user_id) && !is_officer {
+        interaction
+            .create_response(ctx, reply(tr!(locale, "result.loser_only")))
+            .await?;
+        return Ok(());
+    }
+
+    let Some(result) = resolve(
+        &ctx.http,
+        db,
+        guild_id,
+        world_id,
+        ore_point_id,
+        challenger_won,
+    )
+    .await?
+    else {
+        interaction
+            .create_response(ctx, reply(tr!(locale, "result.settled")))
+            .await?;
+        return Ok(());
+    };
+    // 非落敗方登記的結果視為管理操作
+    if loser_id != Some(user_id) {
+        undo::record(
+            db,
+            guild_id,
+            world_id,
+            ore_point_id,
+            user_id,
+            HistoryAction::ChallengeWon,
+            Some(data),
+        )
+        .await?;
+    }
+
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new().components(Vec::new()),
+            ),
+        )
+        .await?;
+    if let Some(thread_id) = result.thread_id {
+        let locale = i18n::guild_locale(db, guild_id).await;
+        let content = resolution_message(locale, &point, &result);
+        close_thread(&ctx.http, thread_id, Some(content)).await;
+    }
+    Ok(())
+}
+
+/// 在挑戰開始前通知雙方與通知身分組
+pub async fn run_reminders(http: &Http, db: &BotDB) -> Result<()> {
+    let before = Utc::now() + ChronoDuration::minutes(REMINDER_MINUTES);
+    for battle in db.get_upcoming_battles(before).await? {
+        // 單一挑戰失敗時不影響其他挑戰
+        if let Err(err) = db
+            .set_battle_reminded(battle.guild_id, battle.world_id, battle.ore_point_id)
+            .await
+        {
+            tracing::error!("{err}");
+            continue;
+        }
+
+        let Some(point) = OrePoint::iter().find(|p| p.id == battle.ore_point_id) else {
+            continue;
+        };
+        let mentions =
+            match notify::role_mentions(db, battle.guild_id, battle.world_id, &point).await {
+                Ok(mentions) => mentions,
+                Err(err) => {
+                    tracing::error!("{err}");
+                    continue;
+                }
+            };
+        let names = match linked_names(db, battle.guild_id, battle.world_id).await {
+            Ok(names) => names,
+            Err(err) => {
+                tracing::error!("{err}");
+                continue;
+            }
+        };
+        let locale = i18n::guild_locale(db, battle.guild_id).await;
+        let message = ChannelId::new(battle.channel_id)
+            .send_message(
+                http,
+                CreateMessage::new()
+                    .allowed_mentions(CreateAllowedMentions::new().all_roles(true).all_users(true))
+                    .content(tr!(
+                        locale,
+                        "battle.reminder",
+                        owner = mention(&names, battle.user_id),
+                        challenger = mention(&names, battle.battle_user_id),
+                        point = point.label(locale),
+                        time = battle.battle_time.timestamp(),
+                        mentions = mentions
+                    )),
+            )
+            .await;
+        if let Err(err) = message {
+            tracing::error!("{err}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    /// 隊伍 1 的玩家 10 佔領礦點 1
+    fn occupation(due_time: DateTime<Utc>) -> OccupyData {
+        OccupyData {
+            team_id: Some(1),
+            ..OccupyData::fixture(due_time)
+        }
+    }
+
+    fn challenged() -> OccupyData {
+        let mut data = occupation(at(1));
+        data.battle_user_id = Some(20);
+        data.battle_team_id = Some(2);
+        data.battle_time = Some(at(3));
+        data.battle_thread_id = Some(500);
+        data
+    }
+
+    #[test]
+    fn settling_clears_challenge_and_thread() {
+        for challenger_won in [true, false] {
+            let mut data = challenged();
+            settle(&mut data, 20, challenger_won, at(4), None).unwrap();
+            assert_eq!(data.battle_user_id, None);
+            assert_eq!(data.battle_team_id, None);
+            assert_eq!(data.battle_time, None);
+            assert_eq!(data.battle_thread_id, None);
+        }
+    }
+
+    #[test]
+    fn defense_win_grants_shield() {
+        let mut data = challenged();
+        let result = settle(&mut data, 20, false, at(4), Some(6)).unwrap();
+        assert_eq!(result, (10, 20, Some(1)));
+        assert_eq!(data.user_id, 10);
+        assert_eq!(data.team_id, Some(1));
+        assert_eq!(data.due_time, at(1));
+        assert_eq!(data.shield_until, Some(at(10)));
+    }
+
+    #[test]
+    fn defense_win_without_shield_hours() {
+        let mut data = challenged();
+        settle(&mut data, 20, false, at(4), None).unwrap();
+        assert_eq!(data.shield_until, None);
+    }
+
+    #[test]
+    fn challenger_win_takes_over_point() {
+        let mut data = challenged();
+        data.shield_until = Some(at(2));
+        let result = settle(&mut data, 20, true, at(4), Some(6)).unwrap();
+        assert_eq!(result, (20, 10, Some(2)));
+        assert_eq!(data.user_id, 20);
+        assert_eq!(data.team_id, Some(2));
+        assert_eq!(data.due_time, at(4) + ChronoDuration::days(14));
+        assert_eq!(data.shield_until, None);
+    }
+
+    #[test]
+    fn promotion_waits_for_due_time() {
+        assert!(!can_promote(&occupation(at(5)), at(4)));
+        assert!(can_promote(&occupation(at(5)), at(5)));
+    }
+
+    #[test]
+    fn promotion_waits_for_challenge_and_shield() {
+        let mut data = occupation(at(1));
+        data.battle_user_id = Some(20);
+        assert!(!can_promote(&data, at(4)));
+
+        let mut data = occupation(at(1));
+        data.shield_until = Some(at(6));
+        assert!(!can_promote(&data, at(4)));
+        assert!(can_promote(&data, at(6)));
+    }
+
+    #[test]
+    fn queue_drops_owner_and_teammates() {
+        let data = occupation(at(1));
+        assert!(is_owner_side(&data, 10, None));
+        assert!(is_owner_side(&data, 20, Some(1)));
+        assert!(!is_owner_side(&data, 20, Some(2)));
+        assert!(!is_owner_side(&data, 20, None));
+    }
+
+    #[test]
+    fn cooldown_ends_after_configured_hours() {
+        assert_eq!(cooldown_end(at(1), 4, &[]), at(5));
+        assert_eq!(cooldown_end(at(1), 4, &[(at(2), at(3))]), at(6));
+    }
+
+    #[test]
+    fn longest_cooldown_applies() {
+        // 礦點冷卻至 5 點，玩家冷卻至 8 點
+        assert_eq!(active_cooldown(vec![at(5), at(8)], at(6)), Some(at(8)));
+        assert_eq!(active_cooldown(vec![at(5), at(8)], at(8)), None);
+        assert_eq!(active_cooldown(Vec::new(), at(6)), None);
+    }
+
+    #[test]
+    fn cooldown_extends_for_freezes_inside_it() {
+        // 落敗於 1 點，冷卻 4 小時，2 點到 4 點凍結
+        assert_eq!(extend_for_freezes(at(1), at(5), &[(at(2), at(4))]), at(7));
+        // 延長後落入冷卻期間的凍結也會計入
+        assert_eq!(
+            extend_for_freezes(at(1), at(5), &[(at(2), at(4)), (at(6), at(8))]),
+            at(9)
+        );
+    }
+
+    #[test]
+    fn cooldown_ignores_freezes_outside_it() {
+        assert_eq!(extend_for_freezes(at(1), at(5), &[(at(6), at(8))]), at(5));
+        // 落敗前已開始的凍結只計算落敗後的部分
+        assert_eq!(extend_for_freezes(at(3), at(5), &[(at(1), at(4))]), at(6));
+        assert_eq!(extend_for_freezes(at(3), at(5), &[]), at(5));
+    }
+}
@@ -1,7 +1,21 @@
 use crate::{
-    db::{BotDB, OccupyData},
+    api, audit, challenge, config, confirm,
+    db::{BotDB, HistoryData, OccupyData},
+    due,
+    events::{self, EventKind},
+    freeze::{self, not_frozen},
+    i18n::{self, tr},
+    leaderboard,
+    link::{self, autocomplete_character, mention_player, resolve_player},
     list,
-    structs::OrePoint,
+    notify::{self, autocomplete_ore_type, NotifyEvent},
+    permission::{self, require_admin, require_member, require_officer, BotRole},
+    season,
+    server::{self, OnlineCheck},
+    structs::{HistoryAction, OrePoint, OreType},
+    team::{self, member_team},
+    transfer, undo, vacation, webhook,
+    world::{self, autocomplete_world, resolve_world},
 };
 use anyhow::{Context as _, Error, Result};
 use chrono::{Days, Utc};
@@ -14,19 +28,33 @@ use poise::{
 use shuttle_runtime::async_trait;
 use std::fmt::Display;
 
-type Context<'a> = poise::Context<'a, BotDB, Error>;
+pub(crate) type Context<'a> = poise::Context<'a, BotDB, Error>;
 
 /// 佔領一座礦點
-#[poise::command(slash_command, rename = "佔領")]
+#[poise::command(
+    slash_command,
+    rename = "佔領",
+    check = "require_member",
+    check = "not_frozen"
+)]
 async fn occupy(
     ctx: Context<'_>,
     #[rename = "礦點"]
     #[description = "佔領的礦點編號"]
     point_id: i32,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
 ) -> Result<()> {
     let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
     let user_id = ctx.author().id.get();
+    let team_id = member_team(ctx, user_id).await?;
     let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
 
     let point = OrePoint::iter()
         .find(|p| p.id == point_id)
@@ -35,23 +63,22 @@ async fn occupy(
     // begin transaction
     let trans = db.begin_transaction().await?;
 
-    // 確認是否擁有同類礦點
-    if db
-        .has_occupy_type(guild_id, user_id, point.ore_type)
+    // 確認是否擁有同類礦點 (隊伍成員共用限制)
+    if challenge::exceeds_type_limit(db, guild_id, world_id, user_id, team_id, point.ore_type)
         .await?
     {
         ctx.send(
             CreateReply::default()
                 .reply(true)
                 .ephemeral(true)
-                .content("你已佔領同類礦點或已發起挑戰"),
+                .content(tr!(locale, "occupy.type_limit")),
         )
         .await?;
         return Ok(());
     }
 
     // 確認是否佔領
-    match db.get_occupy_data(guild_id, point.id).await? {
+    match db.get_occupy_data(guild_id, world_id, point.id).await? {
         Some(mut data) => {
             // 已被佔領
 
@@ -61,9 +88,65 @@ async fn occupy(
                     CreateReply::default()
                         .reply(true)
                         .ephemeral(true)
-                        .content(format!(
-                            "礦點已被佔領，可於 <t:{0}:R> (<t:{0}:F>) 發起挑戰",
-                            data.due_time.timestamp()
+                        .content(tr!(
+                            locale,
+                            "occupy.not_due",
+                            time = data.due_time.timestamp()
+                        )),
+                )
+                .await?;
+                return Ok(());
+            }
+
+            if let Some(shield_until) = data.shield_until.filter(|time| *time > Utc::now()) {
+                // 防守成功後的保護期間
+                ctx.send(
+                    CreateReply::default()
+                        .reply(true)
+                        .ephemeral(true)
+                        .content(tr!(
+                            locale,
+                            "occupy.shielded",
+                            time = shield_until.timestamp()
+                        )),
+                )
+                .await?;
+                return Ok(());
+            }
+
+            let now = Utc::now();
+            if let Some(vacation) = db
+                .get_vacation(guild_id, world_id, data.user_id, now)
+                .await?
+                .filter(|vacation| vacation::protects(vacation, now))
+            {
+                // 佔領者休假中
+                ctx.send(
+                    CreateReply::default()
+                        .reply(true)
+                        .ephemeral(true)
+                        .content(tr!(
+                            locale,
+                            "occupy.on_leave",
+                            time = vacation.end_time.timestamp()
+                        )),
+                )
+                .await?;
+                return Ok(());
+            }
+
+            if let Some(cooldown_until) =
+                challenge::cooldown_until(db, guild_id, world_id, user_id, point.id).await?
+            {
+                // 挑戰落敗或撤回後的冷卻時間
+                ctx.send(
+                    CreateReply::default()
+                        .reply(true)
+                        .ephemeral(true)
+                        .content(tr!(
+                            locale,
+                            "occupy.cooldown",
+                            time = cooldown_until.timestamp()
                         )),
                 )
                 .await?;
@@ -76,7 +159,28 @@ async fn occupy(
                     CreateReply::default()
                         .reply(true)
                         .ephemeral(true)
-                        .content("礦點已有玩家登記挑戰"),
+                        .content(tr!(locale, "occupy.has_challenger")),
+                )
+                .await?;
+                return Ok(());
+            }
+
+            // 依伺服器設定確認角色在線上
+            let online_error = match server::check_online(db, guild_id, world_id, user_id).await {
+                Ok(OnlineCheck::Skipped | OnlineCheck::Online) => None,
+                Ok(OnlineCheck::Offline) => Some(tr!(locale, "occupy.offline")),
+                Ok(OnlineCheck::NotLinked) => Some(tr!(locale, "occupy.not_linked")),
+                Err(err) => {
+                    tracing::error!("{err}");
+                    Some(tr!(locale, "occupy.server_error"))
+                }
+            };
+            if let Some(content) = online_error {
+                ctx.send(
+                    CreateReply::default()
+                        .reply(true)
+                        .ephemeral(true)
+                        .content(content),
                 )
                 .await?;
                 return Ok(());
@@ -84,105 +188,375 @@ async fn occupy(
 
             // 登記挑戰
             data.battle_user_id = Some(user_id);
+            data.battle_team_id = team_id;
             let original_user_id = data.user_id;
             db.update_occupy_data(data).await?;
+            db.add_history(HistoryData {
+                guild_id,
+                world_id,
+                team_id,
+                ore_point_id: point.id,
+                user_id,
+                target_user_id: Some(original_user_id),
+                action: HistoryAction::Challenge,
+            })
+            .await?;
             trans.commit().await?;
+            events::emit(
+                EventKind::Challenged,
+                guild_id,
+                world_id,
+                point.id,
+                user_id,
+                Some(original_user_id),
+            );
 
-            // 找到登記通知的身分組
-            let role_id = db.get_guild_notify_role(guild_id).await?;
+            // 未設定通知頻道時於回覆中通知身分組
+            let mentions =
+                notify::reply_mentions(db, guild_id, world_id, &point, NotifyEvent::Challenge)
+                    .await?;
+            let owner = mention_player(db, guild_id, world_id, original_user_id).await;
+            let challenger = mention_player(db, guild_id, world_id, user_id).await;
+            let guild_locale = i18n::guild_locale(db, guild_id).await;
 
             ctx.send(
                 CreateReply::default()
                     .reply(true)
                     .allowed_mentions(CreateAllowedMentions::new().all_roles(true).all_users(true))
-                    .content(format!(
-                        "已登記挑戰由 <@{}> 佔領的 {} {} ({}, {}) {}\n",
-                        original_user_id,
-                        point.emoji(),
-                        point.name,
-                        point.x,
-                        point.y,
-                        role_id.map_or(String::new(), |role_id| format!("<@&{role_id}>"))
+                    .content(tr!(
+                        locale,
+                        "occupy.challenged_reply",
+                        owner = owner,
+                        point = point.label(locale),
+                        mentions = mentions
                     )),
             )
             .await?;
 
+            notify::announce(
+                ctx.http(),
+                db,
+                guild_id,
+                world_id,
+                &point,
+                NotifyEvent::Challenge,
+                &tr!(
+                    guild_locale,
+                    "occupy.challenged",
+                    challenger = challenger,
+                    owner = owner,
+                    point = point.label(guild_locale)
+                ),
+            )
+            .await;
+
+            challenge::open_thread(ctx.http(), db, guild_id, world_id, point.id).await;
+
             Ok(())
         }
         None => {
             let data = OccupyData {
                 ore_point_id: point.id,
                 guild_id,
+                world_id,
                 user_id,
                 due_time: Utc::now()
                     .checked_add_days(Days::new(14))
                     .context("Failed to add days")?,
                 battle_user_id: None,
+                team_id,
+                battle_team_id: None,
+                battle_time: None,
+                battle_thread_id: None,
+                shield_until: None,
             };
 
             db.occupy(data).await?;
+            db.add_history(HistoryData {
+                guild_id,
+                world_id,
+                team_id,
+                ore_point_id: point.id,
+                user_id,
+                target_user_id: None,
+                action: HistoryAction::Occupy,
+            })
+            .await?;
             trans.commit().await?;
-            ctx.reply(format!(
-                "已佔領 {} {} ({}, {})\n",
-                point.emoji(),
-                point.name,
-                point.x,
-                point.y
+            events::emit(
+                EventKind::Occupied,
+                guild_id,
+                world_id,
+                point.id,
+                user_id,
+                None,
+            );
+            ctx.reply(tr!(
+                locale,
+                "occupy.occupied_reply",
+                point = point.label(locale)
             ))
             .await?;
+
+            let guild_locale = i18n::guild_locale(db, guild_id).await;
+
+            notify::announce(
+                ctx.http(),
+                db,
+                guild_id,
+                world_id,
+                &point,
+                NotifyEvent::Occupy,
+                &tr!(
+                    guild_locale,
+                    "occupy.occupied",
+                    user = mention_player(db, guild_id, world_id, user_id).await,
+                    point = point.label(guild_locale)
+                ),
+            )
+            .await;
             Ok(())
         }
     }
 }
 
 /// 強制佔領一座礦點
-#[poise::command(
-    slash_command,
-    rename = "強制佔領",
-    default_member_permissions = "MANAGE_GUILD"
-)]
+#[poise::command(slash_command, rename = "強制佔領", check = "require_officer")]
 async fn force_occupy(
     ctx: Context<'_>,
-    #[rename = "玩家"]
-    #[description = "佔領的玩家"]
-    user: User,
     #[rename = "礦點"]
     #[description = "佔領的礦點編號"]
     point_id: i32,
+    #[rename = "玩家"]
+    #[description = "佔領的玩家"]
+    user: Option<User>,
+    #[rename = "角色"]
+    #[description = "佔領玩家的遊戲角色名稱"]
+    #[autocomplete = "autocomplete_character"]
+    character: Option<String>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
 ) -> Result<()> {
     let guild_id = ctx.guild_id().context("Missing guild id")?.get();
-    let user_id = user.id.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let Some(user_id) = resolve_player(ctx, world_id, user, character, false).await? else {
+        return Ok(());
+    };
+    let team_id = member_team(ctx, user_id).await?;
     let db = ctx.data();
 
     let point = OrePoint::iter()
         .find(|p| p.id == point_id)
         .context("找不到礦點")?;
+    let locale = i18n::locale(ctx).await;
+
+    let previous = db.get_occupy_data(guild_id, world_id, point_id).await?;
+    if let Some(previous) = &previous {
+        let embed = confirm::embed(
+            tr!(locale, "confirm.force_occupy"),
+            tr!(
+                locale,
+                "confirm.force_occupy.description",
+                point = point.label(locale)
+            ),
+        );
+        let embed = confirm::occupation_fields(ctx, embed, previous).await;
+        if !confirm::confirm(ctx, embed).await? {
+            return Ok(());
+        }
+    }
+    // 確認期間礦點可能已變動，重新讀取以記錄正確的復原資料
+    let previous = db.get_occupy_data(guild_id, world_id, point_id).await?;
+    let thread_id = previous.as_ref().and_then(|data| data.battle_thread_id);
 
     let data = OccupyData {
         ore_point_id: point_id,
         guild_id,
+        world_id,
         user_id,
         due_time: Utc::now()
             .checked_add_days(Days::new(14))
             .context("Failed to add days")?,
         battle_user_id: None,
+        team_id,
+        battle_team_id: None,
+        battle_time: None,
+        battle_thread_id: None,
+        shield_until: None,
     };
 
     db.force_occupy(data).await?;
-    ctx.reply(format!(
-        "<@{}> 已佔領 {} {} ({}, {})\n",
+    db.add_history(HistoryData {
+        guild_id,
+        world_id,
+        team_id,
+        ore_point_id: point_id,
         user_id,
-        point.emoji(),
-        point.name,
-        point.x,
-        point.y
+        target_user_id: None,
+        action: HistoryAction::ForceOccupy,
+    })
+    .await?;
+    undo::record(
+        db,
+        guild_id,
+        world_id,
+        point_id,
+        ctx.author().id.get(),
+        HistoryAction::ForceOccupy,
+        previous,
+    )
+    .await?;
+    events::emit(
+        EventKind::Forced,
+        guild_id,
+        world_id,
+        point_id,
+        user_id,
+        None,
+    );
+
+    let guild_locale = i18n::guild_locale(db, guild_id).await;
+    let user = mention_player(db, guild_id, world_id, user_id).await;
+    ctx.reply(tr!(
+        locale,
+        "occupy.occupied",
+        user = user,
+        point = point.label(locale)
     ))
     .await?;
+    let content = tr!(
+        guild_locale,
+        "occupy.occupied",
+        user = user,
+        point = point.label(guild_locale)
+    );
+    notify::announce(
+        ctx.http(),
+        db,
+        guild_id,
+        world_id,
+        &point,
+        NotifyEvent::Occupy,
+        &content,
+    )
+    .await;
+    // 強制佔領取消進行中的挑戰
+    if let Some(thread_id) = thread_id {
+        let content = (thread_id != ctx.channel_id().get()).then_some(content);
+        challenge::close_thread(ctx.http(), thread_id, content).await;
+    }
+    Ok(())
+}
+
+/// 放棄佔領的礦點
+#[poise::command(
+    slash_command,
+    rename = "釋放礦點",
+    check = "require_member",
+    ephemeral
+)]
+async fn release(
+    ctx: Context<'_>,
+    #[rename = "礦點"]
+    #[description = "釋放的礦點編號"]
+    point_id: i32,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let user_id = ctx.author().id.get();
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+
+    let point = OrePoint::iter()
+        .find(|p| p.id == point_id)
+        .context("找不到礦點")?;
+
+    let Some(data) = db.get_occupy_data(guild_id, world_id, point.id).await? else {
+        ctx.reply(tr!(locale, "release.not_occupied")).await?;
+        return Ok(());
+    };
+    // 幹部可以釋放其他玩家的礦點
+    let is_officer = permission::author_level(ctx)
+        .await?
+        .is_some_and(|level| level >= BotRole::Officer);
+    if data.user_id != user_id && !is_officer {
+        ctx.reply(tr!(locale, "release.not_owner")).await?;
+        return Ok(());
+    }
+    if data.battle_user_id.is_some() {
+        ctx.reply(tr!(locale, "release.in_challenge")).await?;
+        return Ok(());
+    }
+
+    let embed = confirm::embed(
+        tr!(locale, "confirm.release"),
+        tr!(
+            locale,
+            "confirm.release.description",
+            point = point.label(locale)
+        ),
+    );
+    let embed = confirm::occupation_fields(ctx, embed, &data).await;
+    if !confirm::confirm(ctx, embed).await? {
+        return Ok(());
+    }
+
+    // 確認期間礦點可能已被挑戰或轉手，只在佔領者與挑戰狀態不變時釋放
+    let Some(data) = db
+        .release_occupy(guild_id, world_id, point.id, data.user_id)
+        .await?
+    else {
+        ctx.reply(tr!(locale, "release.changed")).await?;
+        return Ok(());
+    };
+    db.add_history(HistoryData {
+        guild_id,
+        world_id,
+        team_id: data.team_id,
+        ore_point_id: point.id,
+        user_id: data.user_id,
+        target_user_id: None,
+        action: HistoryAction::Release,
+    })
+    .await?;
+    events::emit(
+        EventKind::Released,
+        guild_id,
+        world_id,
+        point.id,
+        data.user_id,
+        None,
+    );
+    if data.user_id != user_id {
+        undo::record(
+            db,
+            guild_id,
+            world_id,
+            point.id,
+            user_id,
+            HistoryAction::Release,
+            Some(data),
+        )
+        .await?;
+    }
+
+    ctx.reply(tr!(locale, "release.released", point = point.label(locale)))
+        .await?;
     Ok(())
 }
 
 /// 列出所有的礦點
-#[poise::command(slash_command, rename = "礦點", ephemeral)]
+#[poise::command(slash_command, rename = "礦點", check = "require_member", ephemeral)]
 async fn list_points(
     ctx: Context<'_>,
     #[min = 1]
@@ -190,12 +564,20 @@ async fn list_points(
     #[rename = "每頁礦點數量"]
     #[description = "每頁礦點數量"]
     page_size: Option<u32>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
 ) -> Result<()> {
     let db = ctx.data();
     let guild_id = ctx.guild_id().context("err")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
     let page_size = page_size.unwrap_or(20);
 
-    let content = list::list(db, guild_id, 0, page_size).await?;
+    let locale = i18n::locale(ctx).await;
+    let content = list::list(db, guild_id, world_id, 0, page_size, locale).await?;
 
     let reply = CreateReply::default()
         .embed(content.embed)
@@ -208,27 +590,60 @@ async fn list_points(
     Ok(())
 }
 
-/// 列出所有的礦點
-#[poise::command(
-    slash_command,
-    rename = "挑戰通知",
-    default_member_permissions = "MANAGE_GUILD",
-    ephemeral
-)]
+/// 設定通知的身分組
+#[poise::command(slash_command, rename = "挑戰通知", check = "require_admin", ephemeral)]
 async fn set_notify(
     ctx: Context<'_>,
     #[rename = "身分組"]
-    #[description = "要通知的身分組"]
-    role: Role,
+    #[description = "要通知的身分組，留空則取消通知"]
+    role: Option<Role>,
+    #[rename = "礦種"]
+    #[description = "只在此礦種的礦點通知，留空則通知所有礦點"]
+    #[autocomplete = "autocomplete_ore_type"]
+    ore_type: Option<String>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
 ) -> Result<()> {
     let db = ctx.data();
     let guild_id = ctx.guild_id().context("err")?.get();
-    let role_id = role.id.get();
-
-    db.set_guild_notify_role(guild_id, role_id).await?;
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let role_id = role.map(|role| role.id.get());
+    let locale = i18n::locale(ctx).await;
+
+    let target = match ore_type {
+        Some(name) => {
+            let ore_type = OreType::iter()
+                .find(|ore_type| ore_type.name == name)
+                .context("找不到礦種")?;
+            db.set_ore_type_notify_role(guild_id, world_id, ore_type.id, role_id)
+                .await?;
+            tr!(
+                locale,
+                "notify.ore_type",
+                ore_type = format!("<{}> {}", ore_type.emoji, ore_type.localized_name(locale))
+            )
+        }
+        None => {
+            db.set_guild_notify_role(guild_id, world_id, role_id)
+                .await?;
+            tr!(locale, "notify.all_points")
+        }
+    };
 
-    ctx.reply(format!("發起挑戰時將會通知 <@&{}>", role_id))
-        .await?;
+    ctx.reply(match role_id {
+        Some(role_id) => tr!(
+            locale,
+            "notify.enabled",
+            target = target,
+            role = format!("<@&{role_id}>")
+        ),
+        None => tr!(locale, "notify.disabled", target = target),
+    })
+    .await?;
 
     Ok(())
 }
@@ -312,32 +727,59 @@ async fn test2(ctx: Context<'_>, msg: Message) -> Result<()> {
     Ok(())
 }
 
-pub fn get_commands() -> Vec<Command<BotDB, Error>> {
-    let mut occupy_command = occupy();
-    let mut force_occupy_command = force_occupy();
-
-    let ore_point_type_setter: Option<fn(CreateCommandOption) -> CreateCommandOption> =
-        Some(|option| {
+/// 設定礦點編號參數的範圍
+fn set_ore_point_range(command: &mut Command<BotDB, Error>) {
+    for parameter in command
+        .parameters
+        .iter_mut()
+        .filter(|parameter| parameter.name == "礦點")
+    {
+        parameter.type_setter = Some(|option| {
             option
                 .kind(CommandOptionType::Integer)
                 .min_int_value(1)
                 .max_int_value(OrePoint::iter().count() as u64)
         });
+    }
+    command.subcommands.iter_mut().for_each(set_ore_point_range);
+}
 
-    // Set max ore point id
-
-    occupy_command.parameters.first_mut().unwrap().type_setter = ore_point_type_setter;
-    force_occupy_command
-        .parameters
-        .get_mut(1)
-        .unwrap()
-        .type_setter = ore_point_type_setter;
-
-    vec![
+pub fn get_commands() -> Vec<Command<BotDB, Error>> {
+    let mut commands = vec![
         // init(),
         set_notify(),
         list_points(),
-        occupy_command,
-        force_occupy_command,
-    ]
+        occupy(),
+        force_occupy(),
+        release(),
+        due::due_time(),
+        freeze::freeze(),
+        season::new_season(),
+        season::season_history(),
+        world::world(),
+        team::team(),
+        challenge::resolve_challenge(),
+        challenge::propose_battle(),
+        challenge::withdraw_challenge(),
+        challenge::join_queue(),
+        challenge::leave_queue(),
+        config::config(),
+        webhook::webhook(),
+        api::api(),
+        server::server(),
+        link::link_character(),
+        link::character(),
+        permission::permission(),
+        leaderboard::ranking(),
+        audit::audit_log(),
+        transfer::transfer(),
+        undo::undo(),
+        vacation::vacation(),
+    ];
+
+    // Set max ore point id
+    commands.iter_mut().for_each(set_ore_point_range);
+    i18n::localize_commands(&mut commands);
+
+    commands
 }
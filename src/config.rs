@@ -0,0 +1,286 @@
+use crate::{
+    commands::Context,
+    i18n::{self, tr, Locale},
+    notify::NotifyEvent,
+    permission::require_admin,
+};
+use anyhow::{Context as _, Result};
+use poise::{serenity_prelude::GuildChannel, ChoiceParameter};
+
+/// 伺服器設定
+#[poise::command(
+    slash_command,
+    rename = "設定",
+    check = "require_admin",
+    subcommands(
+        "proposal_timeout",
+        "queue_counts_limit",
+        "challenge_channel",
+        "notify_channel",
+        "notify_event",
+        "locale",
+        "log_retention",
+        "shield_hours",
+        "challenge_cooldown",
+        "vacation_max_days"
+    ),
+    subcommand_required
+)]
+pub async fn config(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 設定約戰的回應時限
+#[poise::command(slash_command, rename = "約戰時限", ephemeral)]
+async fn proposal_timeout(
+    ctx: Context<'_>,
+    #[min = 1]
+    #[max = 168]
+    #[rename = "小時"]
+    #[description = "佔領者未於時限內選擇挑戰時段時視為棄權"]
+    hours: i32,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+
+    ctx.data().set_proposal_timeout(guild_id, hours).await?;
+    let locale = i18n::locale(ctx).await;
+    ctx.reply(tr!(locale, "config.proposal_timeout", hours = hours))
+        .await?;
+    Ok(())
+}
+
+/// 設定排隊中的挑戰是否計入同類礦點上限
+#[poise::command(slash_command, rename = "排隊計入上限", ephemeral)]
+async fn queue_counts_limit(
+    ctx: Context<'_>,
+    #[rename = "啟用"]
+    #[description = "排隊中的挑戰是否計入同類礦點上限"]
+    enabled: bool,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+
+    ctx.data().set_queue_counts_limit(guild_id, enabled).await?;
+    let locale = i18n::locale(ctx).await;
+    ctx.reply(if enabled {
+        tr!(locale, "config.queue_counts")
+    } else {
+        tr!(locale, "config.queue_not_counts")
+    })
+    .await?;
+    Ok(())
+}
+
+/// 設定建立挑戰討論串的頻道
+#[poise::command(slash_command, rename = "挑戰頻道", ephemeral)]
+async fn challenge_channel(
+    ctx: Context<'_>,
+    #[rename = "頻道"]
+    #[description = "建立挑戰討論串的文字或論壇頻道，留空則不建立討論串"]
+    #[channel_types("Text", "Forum")]
+    channel: Option<GuildChannel>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let channel_id = channel.map(|channel| channel.id.get());
+
+    ctx.data()
+        .set_challenge_channel(guild_id, channel_id)
+        .await?;
+    let locale = i18n::locale(ctx).await;
+    ctx.reply(
+        channel_id.map_or(tr!(locale, "config.no_challenge_channel"), |channel_id| {
+            tr!(
+                locale,
+                "config.challenge_channel",
+                channel = format!("<#{channel_id}>")
+            )
+        }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// 設定發送事件通知的頻道
+#[poise::command(slash_command, rename = "通知頻道", ephemeral)]
+async fn notify_channel(
+    ctx: Context<'_>,
+    #[rename = "頻道"]
+    #[description = "發送事件通知的頻道，留空則於指令回覆中通知"]
+    #[channel_types("Text", "News")]
+    channel: Option<GuildChannel>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let channel_id = channel.map(|channel| channel.id.get());
+
+    ctx.data().set_notify_channel(guild_id, channel_id).await?;
+    let locale = i18n::locale(ctx).await;
+    ctx.reply(
+        channel_id.map_or(tr!(locale, "config.no_notify_channel"), |channel_id| {
+            tr!(
+                locale,
+                "config.notify_channel",
+                channel = format!("<#{channel_id}>")
+            )
+        }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// 設定要通知的事件
+#[poise::command(slash_command, rename = "通知事件", ephemeral)]
+async fn notify_event(
+    ctx: Context<'_>,
+    #[rename = "事件"]
+    #[description = "通知的事件"]
+    event: NotifyEvent,
+    #[rename = "啟用"]
+    #[description = "是否通知此事件"]
+    enabled: bool,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let db = ctx.data();
+
+    let config = db.get_guild_config(guild_id).await?;
+    let events = event.toggle(config.notify_events, enabled);
+    db.set_notify_events(guild_id, events).await?;
+
+    let locale = i18n::locale(ctx).await;
+    let enabled_events = NotifyEvent::all()
+        .into_iter()
+        .filter(|event| event.enabled(events))
+        .map(|event| i18n::choice(locale, event))
+        .collect::<Vec<_>>();
+    ctx.reply(if enabled_events.is_empty() {
+        tr!(locale, "config.no_events")
+    } else {
+        tr!(
+            locale,
+            "config.events",
+            events = enabled_events.join(&tr!(locale, "list.separator"))
+        )
+    })
+    .await?;
+    Ok(())
+}
+
+/// 設定伺服器預設的語言
+#[poise::command(slash_command, rename = "語言", ephemeral)]
+async fn locale(
+    ctx: Context<'_>,
+    #[rename = "語言"]
+    #[description = "頻道公告與不支援的使用者語言所使用的語言"]
+    locale: Locale,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+
+    ctx.data().set_guild_locale(guild_id, locale.code()).await?;
+    ctx.reply(tr!(
+        i18n::locale(ctx).await,
+        "config.locale",
+        locale = locale.name()
+    ))
+    .await?;
+    Ok(())
+}
+
+/// 設定指令紀錄的保留天數
+#[poise::command(slash_command, rename = "紀錄保留", ephemeral)]
+async fn log_retention(
+    ctx: Context<'_>,
+    #[min = 1]
+    #[max = 3650]
+    #[rename = "天數"]
+    #[description = "指令紀錄保留的天數，留空則永久保留"]
+    days: Option<i32>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+
+    ctx.data().set_log_retention(guild_id, days).await?;
+    let locale = i18n::locale(ctx).await;
+    ctx.reply(match days {
+        Some(days) => tr!(locale, "config.log_retention", days = days),
+        None => tr!(locale, "config.log_forever"),
+    })
+    .await?;
+    Ok(())
+}
+
+/// 設定防守成功後的保護時間
+#[poise::command(slash_command, rename = "防守保護", ephemeral)]
+async fn shield_hours(
+    ctx: Context<'_>,
+    #[min = 1]
+    #[max = 336]
+    #[rename = "小時"]
+    #[description = "防守成功後無法被登記挑戰的時數，留空則不保護"]
+    hours: Option<i32>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+
+    ctx.data().set_shield_hours(guild_id, hours).await?;
+    let locale = i18n::locale(ctx).await;
+    ctx.reply(match hours {
+        Some(hours) => tr!(locale, "config.shield", hours = hours),
+        None => tr!(locale, "config.no_shield"),
+    })
+    .await?;
+    Ok(())
+}
+
+/// 設定挑戰落敗或撤回後的冷卻時間
+#[poise::command(slash_command, rename = "挑戰冷卻", ephemeral)]
+async fn challenge_cooldown(
+    ctx: Context<'_>,
+    #[min = 1]
+    #[max = 336]
+    #[rename = "同礦點"]
+    #[description = "再次挑戰同一礦點需等待的時數，留空則不限制"]
+    point_hours: Option<i32>,
+    #[min = 1]
+    #[max = 336]
+    #[rename = "所有礦點"]
+    #[description = "再次挑戰任何礦點需等待的時數，留空則不限制"]
+    player_hours: Option<i32>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+
+    ctx.data()
+        .set_challenge_cooldown(guild_id, point_hours, player_hours)
+        .await?;
+    let locale = i18n::locale(ctx).await;
+    let describe = |hours: Option<i32>| {
+        hours.map_or(tr!(locale, "config.unlimited"), |hours| {
+            tr!(locale, "config.hours", hours = hours)
+        })
+    };
+    ctx.reply(tr!(
+        locale,
+        "config.cooldown",
+        point = describe(point_hours),
+        player = describe(player_hours)
+    ))
+    .await?;
+    Ok(())
+}
+
+/// 設定休假保護的最長天數
+#[poise::command(slash_command, rename = "休假上限", ephemeral)]
+async fn vacation_max_days(
+    ctx: Context<'_>,
+    #[min = 1]
+    #[max = 90]
+    #[rename = "天數"]
+    #[description = "休假期間保護礦點的最長天數，留空則只能將礦點交給代理人"]
+    days: Option<i32>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+
+    ctx.data().set_vacation_max_days(guild_id, days).await?;
+    let locale = i18n::locale(ctx).await;
+    ctx.reply(match days {
+        Some(days) => tr!(locale, "vacation.max_days", days = days),
+        None => tr!(locale, "config.no_vacation"),
+    })
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use crate::{
+    commands::Context,
+    db::OccupyData,
+    i18n::{self, tr},
+    link::mention_player,
+};
+use anyhow::Result;
+use poise::{
+    serenity_prelude::{
+        ButtonStyle, Color, ComponentInteractionCollector, CreateActionRow, CreateButton,
+        CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
+    },
+    CreateReply,
+};
+use std::time::Duration;
+
+/// 等待使用者確認的時間
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 確認訊息的內容
+pub fn embed(title: impl Into<String>, description: impl Into<String>) -> CreateEmbed {
+    CreateEmbed::new()
+        .color(Color::ORANGE)
+        .title(title)
+        .description(description)
+}
+
+/// 加入礦點目前的佔領者、挑戰者與到期時間
+pub async fn occupation_fields(
+    ctx: Context<'_>,
+    embed: CreateEmbed,
+    data: &OccupyData,
+) -> CreateEmbed {
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+    let owner = mention_player(db, data.guild_id, data.world_id, data.user_id).await;
+    let challenger = match data.battle_user_id {
+        Some(user_id) => mention_player(db, data.guild_id, data.world_id, user_id).await,
+        None => tr!(locale, "confirm.none"),
+    };
+    embed
+        .field(tr!(locale, "confirm.owner"), owner, true)
+        .field(tr!(locale, "confirm.challenger"), challenger, true)
+        .field(
+            tr!(locale, "confirm.due_time"),
+            format!("<t:{}:R>", data.due_time.timestamp()),
+            true,
+        )
+}
+
+/// 顯示確認與取消按鈕並等待使用者回應，確認時回傳 true，取消或逾時回傳 false
+pub async fn confirm(ctx: Context<'_>, embed: CreateEmbed) -> Result<bool> {
+    // 按鈕由收集器處理，不經過一般的元件互動
+    let confirm_id = format!("confirm:{}:yes", ctx.id());
+    let cancel_id = format!("confirm:{}:no", ctx.id());
+    let locale = i18n::locale(ctx).await;
+
+    let reply = ctx
+        .send(
+            CreateReply::default()
+                .embed(embed)
+                .components(vec![CreateActionRow::Buttons(vec![
+                    CreateButton::new(&confirm_id)
+                        .label(tr!(locale, "confirm.confirm"))
+                        .style(ButtonStyle::Danger),
+                    CreateButton::new(&cancel_id)
+                        .label(tr!(locale, "confirm.cancel"))
+                        .style(ButtonStyle::Secondary),
+                ])])
+                .reply(true)
+                .ephemeral(true),
+        )
+        .await?;
+    let message = reply.message().await?;
+
+    let interaction = ComponentInteractionCollector::new(ctx.serenity_context())
+        .author_id(ctx.author().id)
+        .message_id(message.id)
+        .timeout(CONFIRM_TIMEOUT)
+        .await;
+
+    let Some(interaction) = interaction else {
+        reply
+            .edit(
+                ctx,
+                CreateReply::default()
+                    .content(tr!(locale, "confirm.timeout"))
+                    .components(Vec::new()),
+            )
+            .await?;
+        return Ok(false);
+    };
+
+    let confirmed = interaction.data.custom_id == confirm_id;
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(if confirmed {
+                        tr!(locale, "confirm.confirmed")
+                    } else {
+                        tr!(locale, "confirm.cancelled")
+                    })
+                    .components(Vec::new()),
+            ),
+        )
+        .await?;
+    Ok(confirmed)
+}
@@ -1,22 +1,41 @@
-use crate::structs::{ListResult, OrePoint, OreType};
+use crate::{
+    metrics,
+    structs::{HistoryAction, ListResult, OrePoint, OreType},
+    undo,
+};
 use chrono::{DateTime, Utc};
-use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use sqlx::{FromRow, PgExecutor, PgPool, Postgres, Transaction};
+use std::{future::Future, time::Instant};
 
 #[derive(FromRow)]
 struct OccupyDB {
     ore_point_id: i32,
     guild_id: i64,
+    world_id: i32,
     user_id: i64,
     due_time: DateTime<Utc>,
     battle_user_id: Option<i64>,
+    team_id: Option<i32>,
+    battle_team_id: Option<i32>,
+    battle_time: Option<DateTime<Utc>>,
+    battle_thread_id: Option<i64>,
+    shield_until: Option<DateTime<Utc>>,
 }
 
+#[derive(Clone)]
 pub struct OccupyData {
     pub ore_point_id: i32,
     pub guild_id: u64,
+    pub world_id: i32,
     pub user_id: u64,
     pub due_time: DateTime<Utc>,
     pub battle_user_id: Option<u64>,
+    pub team_id: Option<i32>,
+    pub battle_team_id: Option<i32>,
+    pub battle_time: Option<DateTime<Utc>>,
+    pub battle_thread_id: Option<u64>,
+    /// 防守成功後的保護期限
+    pub shield_until: Option<DateTime<Utc>>,
 }
 
 impl From<OccupyDB> for OccupyData {
@@ -24,9 +43,15 @@ impl From<OccupyDB> for OccupyData {
         OccupyData {
             ore_point_id: value.ore_point_id,
             guild_id: value.guild_id as u64,
+            world_id: value.world_id,
             user_id: value.user_id as u64,
             due_time: value.due_time,
             battle_user_id: value.battle_user_id.map(|x| x as u64),
+            team_id: value.team_id,
+            battle_team_id: value.battle_team_id,
+            battle_time: value.battle_time,
+            battle_thread_id: value.battle_thread_id.map(|x| x as u64),
+            shield_until: value.shield_until,
         }
     }
 }
@@ -36,13 +61,570 @@ impl From<OccupyData> for OccupyDB {
         OccupyDB {
             ore_point_id: value.ore_point_id,
             guild_id: value.guild_id as i64,
+            world_id: value.world_id,
             user_id: value.user_id as i64,
             due_time: value.due_time,
             battle_user_id: value.battle_user_id.map(|x| x as i64),
+            team_id: value.team_id,
+            battle_team_id: value.battle_team_id,
+            battle_time: value.battle_time,
+            battle_thread_id: value.battle_thread_id.map(|x| x as i64),
+            shield_until: value.shield_until,
+        }
+    }
+}
+
+#[cfg(test)]
+impl OccupyData {
+    /// 測試用的佔領資料，玩家 10 佔領伺服器 1 世界 0 的礦點 1，沒有隊伍與挑戰者
+    pub fn fixture(due_time: DateTime<Utc>) -> Self {
+        OccupyData {
+            ore_point_id: 1,
+            guild_id: 1,
+            world_id: 0,
+            user_id: 10,
+            due_time,
+            battle_user_id: None,
+            team_id: None,
+            battle_team_id: None,
+            battle_time: None,
+            battle_thread_id: None,
+            shield_until: None,
+        }
+    }
+}
+
+pub struct HistoryData {
+    pub guild_id: u64,
+    pub world_id: i32,
+    pub team_id: Option<i32>,
+    pub ore_point_id: i32,
+    pub user_id: u64,
+    pub target_user_id: Option<u64>,
+    pub action: HistoryAction,
+}
+
+#[derive(FromRow)]
+struct SeasonDB {
+    number: i32,
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+}
+
+pub struct SeasonData {
+    pub number: i32,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl From<SeasonDB> for SeasonData {
+    fn from(value: SeasonDB) -> Self {
+        SeasonData {
+            number: value.number,
+            start_time: value.start_time,
+            end_time: value.end_time,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct TeamDB {
+    id: i32,
+    name: String,
+    role_id: i64,
+}
+
+pub struct TeamData {
+    pub id: i32,
+    pub name: String,
+    pub role_id: u64,
+}
+
+impl From<TeamDB> for TeamData {
+    fn from(value: TeamDB) -> Self {
+        TeamData {
+            id: value.id,
+            name: value.name,
+            role_id: value.role_id as u64,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct HistoryRecordDB {
+    ore_point_id: i32,
+    user_id: i64,
+    target_user_id: Option<i64>,
+    team_id: Option<i32>,
+    action: String,
+    time: DateTime<Utc>,
+}
+
+pub struct HistoryRecord {
+    pub ore_point_id: i32,
+    pub user_id: u64,
+    pub target_user_id: Option<u64>,
+    pub team_id: Option<i32>,
+    pub action: String,
+    pub time: DateTime<Utc>,
+}
+
+impl From<HistoryRecordDB> for HistoryRecord {
+    fn from(value: HistoryRecordDB) -> Self {
+        HistoryRecord {
+            ore_point_id: value.ore_point_id,
+            user_id: value.user_id as u64,
+            target_user_id: value.target_user_id.map(|x| x as u64),
+            team_id: value.team_id,
+            action: value.action,
+            time: value.time,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct RankingRecordDB {
+    season_id: Option<i32>,
+    season_end: Option<DateTime<Utc>>,
+    ore_point_id: i32,
+    user_id: i64,
+    target_user_id: Option<i64>,
+    team_id: Option<i32>,
+    action: String,
+    time: DateTime<Utc>,
+}
+
+/// 計算排行榜用的佔領紀錄，包含所屬賽季的結束時間
+pub struct RankingRecord {
+    pub season_id: Option<i32>,
+    pub season_end: Option<DateTime<Utc>>,
+    pub ore_point_id: i32,
+    pub user_id: u64,
+    pub target_user_id: Option<u64>,
+    pub team_id: Option<i32>,
+    pub action: String,
+    pub time: DateTime<Utc>,
+}
+
+impl From<RankingRecordDB> for RankingRecord {
+    fn from(value: RankingRecordDB) -> Self {
+        RankingRecord {
+            season_id: value.season_id,
+            season_end: value.season_end,
+            ore_point_id: value.ore_point_id,
+            user_id: value.user_id as u64,
+            target_user_id: value.target_user_id.map(|x| x as u64),
+            team_id: value.team_id,
+            action: value.action,
+            time: value.time,
+        }
+    }
+}
+
+pub struct CommandLog<'a> {
+    pub guild_id: Option<u64>,
+    pub channel_id: u64,
+    pub user_id: u64,
+    pub content: &'a str,
+    /// 指令的完整名稱，元件互動為 None
+    pub command: Option<&'a str>,
+    pub ore_point_id: Option<i32>,
+}
+
+/// 查詢指令紀錄的條件
+#[derive(Default)]
+pub struct LogFilter {
+    pub user_id: Option<u64>,
+    pub channel_id: Option<u64>,
+    pub command: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl LogFilter {
+    /// 以指令名稱開頭的 LIKE 樣式，跳脫輸入中的萬用字元
+    pub fn command_pattern(&self) -> Option<String> {
+        let command = self.command.as_deref()?;
+        let mut pattern = String::with_capacity(command.len() + 1);
+        for c in command.chars() {
+            if matches!(c, '\\' | '%' | '_') {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+        pattern.push('%');
+        Some(pattern)
+    }
+}
+
+#[derive(FromRow)]
+struct LogRecordDB {
+    channel_id: i64,
+    user_id: i64,
+    content: String,
+    ore_point_id: Option<i32>,
+    success: Option<bool>,
+    error: Option<String>,
+    time: DateTime<Utc>,
+}
+
+pub struct LogRecord {
+    pub channel_id: u64,
+    pub user_id: u64,
+    pub content: String,
+    pub ore_point_id: Option<i32>,
+    /// 執行結果，尚未完成或未紀錄時為 None
+    pub success: Option<bool>,
+    pub error: Option<String>,
+    pub time: DateTime<Utc>,
+}
+
+impl From<LogRecordDB> for LogRecord {
+    fn from(value: LogRecordDB) -> Self {
+        LogRecord {
+            channel_id: value.channel_id as u64,
+            user_id: value.user_id as u64,
+            content: value.content,
+            ore_point_id: value.ore_point_id,
+            success: value.success,
+            error: value.error,
+            time: value.time,
+        }
+    }
+}
+
+/// 管理操作與操作前後的礦點狀態
+pub struct AdminActionData {
+    pub guild_id: u64,
+    pub world_id: i32,
+    pub ore_point_id: i32,
+    pub user_id: u64,
+    pub action: HistoryAction,
+    pub previous: Option<OccupyData>,
+    pub current: Option<OccupyData>,
+}
+
+#[derive(FromRow)]
+struct AdminActionDB {
+    id: i32,
+    guild_id: i64,
+    world_id: i32,
+    season_id: Option<i32>,
+    ore_point_id: i32,
+    user_id: i64,
+    action: String,
+    previous_user_id: Option<i64>,
+    previous_due_time: Option<DateTime<Utc>>,
+    previous_team_id: Option<i32>,
+    previous_battle_user_id: Option<i64>,
+    previous_battle_team_id: Option<i32>,
+    previous_battle_time: Option<DateTime<Utc>>,
+    previous_battle_thread_id: Option<i64>,
+    previous_shield_until: Option<DateTime<Utc>>,
+    current_user_id: Option<i64>,
+    current_due_time: Option<DateTime<Utc>>,
+    current_battle_user_id: Option<i64>,
+    reverted: bool,
+    time: DateTime<Utc>,
+}
+
+pub struct AdminAction {
+    pub id: i32,
+    pub guild_id: u64,
+    pub world_id: i32,
+    /// 操作所屬的已結束賽季，當前賽季的操作為 None
+    pub season_id: Option<i32>,
+    pub ore_point_id: i32,
+    pub user_id: u64,
+    pub action: String,
+    /// 操作前的佔領資料，礦點原本無人佔領時為 None
+    pub previous: Option<OccupyData>,
+    pub current_user_id: Option<u64>,
+    pub current_due_time: Option<DateTime<Utc>>,
+    pub current_battle_user_id: Option<u64>,
+    pub reverted: bool,
+    pub time: DateTime<Utc>,
+}
+
+impl From<AdminActionDB> for AdminAction {
+    fn from(value: AdminActionDB) -> Self {
+        let previous = match (value.previous_user_id, value.previous_due_time) {
+            (Some(user_id), Some(due_time)) => Some(OccupyData {
+                ore_point_id: value.ore_point_id,
+                guild_id: value.guild_id as u64,
+                world_id: value.world_id,
+                user_id: user_id as u64,
+                due_time,
+                battle_user_id: value.previous_battle_user_id.map(|x| x as u64),
+                team_id: value.previous_team_id,
+                battle_team_id: value.previous_battle_team_id,
+                battle_time: value.previous_battle_time,
+                battle_thread_id: value.previous_battle_thread_id.map(|x| x as u64),
+                shield_until: value.previous_shield_until,
+            }),
+            _ => None,
+        };
+        AdminAction {
+            id: value.id,
+            guild_id: value.guild_id as u64,
+            world_id: value.world_id,
+            season_id: value.season_id,
+            ore_point_id: value.ore_point_id,
+            user_id: value.user_id as u64,
+            action: value.action,
+            previous,
+            current_user_id: value.current_user_id.map(|x| x as u64),
+            current_due_time: value.current_due_time,
+            current_battle_user_id: value.current_battle_user_id.map(|x| x as u64),
+            reverted: value.reverted,
+            time: value.time,
+        }
+    }
+}
+
+/// 遊戲伺服器的連線設定
+#[derive(FromRow)]
+pub struct GameServerConfig {
+    pub rest_url: Option<String>,
+    pub rest_password: Option<String>,
+    pub rcon_address: Option<String>,
+    pub rcon_password: Option<String>,
+    pub require_online: bool,
+}
+
+#[derive(FromRow)]
+struct PlayerLinkDB {
+    user_id: i64,
+    character_name: String,
+    player_uid: Option<String>,
+    steam_id: Option<String>,
+    verified: bool,
+}
+
+pub struct PlayerLink {
+    pub user_id: u64,
+    pub character_name: String,
+    pub player_uid: Option<String>,
+    pub steam_id: Option<String>,
+    pub verified: bool,
+}
+
+impl From<PlayerLinkDB> for PlayerLink {
+    fn from(value: PlayerLinkDB) -> Self {
+        PlayerLink {
+            user_id: value.user_id as u64,
+            character_name: value.character_name,
+            player_uid: value.player_uid,
+            steam_id: value.steam_id,
+            verified: value.verified,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct OccupationCountDB {
+    guild_id: i64,
+    world_id: i32,
+    occupied: i64,
+    challenged: i64,
+}
+
+pub struct OccupationCount {
+    pub guild_id: u64,
+    pub world_id: i32,
+    pub occupied: u32,
+    pub challenged: u32,
+}
+
+impl From<OccupationCountDB> for OccupationCount {
+    fn from(value: OccupationCountDB) -> Self {
+        OccupationCount {
+            guild_id: value.guild_id as u64,
+            world_id: value.world_id,
+            occupied: value.occupied as u32,
+            challenged: value.challenged as u32,
+        }
+    }
+}
+
+#[derive(Clone, FromRow)]
+pub struct WebhookData {
+    pub id: i32,
+    pub url: String,
+    pub secret: String,
+}
+
+#[derive(FromRow)]
+struct BattleProposalDB {
+    guild_id: i64,
+    world_id: i32,
+    ore_point_id: i32,
+    channel_id: i64,
+    slots: Vec<DateTime<Utc>>,
+}
+
+pub struct BattleProposal {
+    pub guild_id: u64,
+    pub world_id: i32,
+    pub ore_point_id: i32,
+    pub channel_id: u64,
+    pub slots: Vec<DateTime<Utc>>,
+}
+
+impl From<BattleProposalDB> for BattleProposal {
+    fn from(value: BattleProposalDB) -> Self {
+        BattleProposal {
+            guild_id: value.guild_id as u64,
+            world_id: value.world_id,
+            ore_point_id: value.ore_point_id,
+            channel_id: value.channel_id as u64,
+            slots: value.slots,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct BattleReminderDB {
+    guild_id: i64,
+    world_id: i32,
+    ore_point_id: i32,
+    battle_channel_id: i64,
+    user_id: i64,
+    battle_user_id: i64,
+    battle_time: DateTime<Utc>,
+}
+
+/// 即將開始的挑戰
+pub struct BattleReminder {
+    pub guild_id: u64,
+    pub world_id: i32,
+    pub ore_point_id: i32,
+    pub channel_id: u64,
+    pub user_id: u64,
+    pub battle_user_id: u64,
+    pub battle_time: DateTime<Utc>,
+}
+
+impl From<BattleReminderDB> for BattleReminder {
+    fn from(value: BattleReminderDB) -> Self {
+        BattleReminder {
+            guild_id: value.guild_id as u64,
+            world_id: value.world_id,
+            ore_point_id: value.ore_point_id,
+            channel_id: value.battle_channel_id as u64,
+            user_id: value.user_id as u64,
+            battle_user_id: value.battle_user_id as u64,
+            battle_time: value.battle_time,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct VacationDB {
+    guild_id: i64,
+    world_id: i32,
+    user_id: i64,
+    end_time: DateTime<Utc>,
+    delegate_user_id: Option<i64>,
+}
+
+pub struct VacationData {
+    pub guild_id: u64,
+    pub world_id: i32,
+    pub user_id: u64,
+    pub end_time: DateTime<Utc>,
+    /// 接手礦點的隊友，未指定時在休假期間保護礦點
+    pub delegate_user_id: Option<u64>,
+}
+
+impl From<VacationDB> for VacationData {
+    fn from(value: VacationDB) -> Self {
+        VacationData {
+            guild_id: value.guild_id as u64,
+            world_id: value.world_id,
+            user_id: value.user_id as u64,
+            end_time: value.end_time,
+            delegate_user_id: value.delegate_user_id.map(|x| x as u64),
+        }
+    }
+}
+
+pub struct QueueData {
+    pub guild_id: u64,
+    pub world_id: i32,
+    pub ore_point_id: i32,
+    pub user_id: u64,
+    pub team_id: Option<i32>,
+}
+
+#[derive(FromRow)]
+pub struct GuildConfig {
+    pub proposal_timeout_hours: i32,
+    pub queue_counts_limit: bool,
+    pub challenge_channel_id: Option<i64>,
+    pub notify_channel_id: Option<i64>,
+    pub notify_events: i32,
+    pub locale: Option<String>,
+    pub log_retention_days: Option<i32>,
+    pub frozen_at: Option<DateTime<Utc>>,
+    pub shield_hours: Option<i32>,
+    pub point_cooldown_hours: Option<i32>,
+    pub player_cooldown_hours: Option<i32>,
+    pub vacation_max_days: Option<i32>,
+}
+
+impl Default for GuildConfig {
+    fn default() -> Self {
+        GuildConfig {
+            proposal_timeout_hours: 24,
+            queue_counts_limit: false,
+            challenge_channel_id: None,
+            notify_channel_id: None,
+            notify_events: 2,
+            locale: None,
+            log_retention_days: None,
+            frozen_at: None,
+            shield_hours: None,
+            point_cooldown_hours: None,
+            player_cooldown_hours: None,
+            vacation_max_days: None,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct ChallengeableDB {
+    guild_id: i64,
+    world_id: i32,
+    ore_point_id: i32,
+    user_id: i64,
+}
+
+pub struct Challengeable {
+    pub guild_id: u64,
+    pub world_id: i32,
+    pub ore_point_id: i32,
+    pub user_id: u64,
+}
+
+impl From<ChallengeableDB> for Challengeable {
+    fn from(value: ChallengeableDB) -> Self {
+        Challengeable {
+            guild_id: value.guild_id as u64,
+            world_id: value.world_id,
+            ore_point_id: value.ore_point_id,
+            user_id: value.user_id as u64,
         }
     }
 }
 
+pub struct SeasonSchedule {
+    pub guild_id: u64,
+    pub world_id: i32,
+    pub channel_id: u64,
+    pub start_time: DateTime<Utc>,
+}
+
 #[derive(Clone)]
 pub struct BotDB {
     pool: PgPool,
@@ -55,152 +637,2197 @@ impl BotDB {
         Self { pool }
     }
 
-    pub async fn occupy(&self, data: OccupyData) -> SqlResult {
-        let data: OccupyDB = data.into();
-        sqlx::query(
-            "INSERT INTO occupy_table(ore_point_id, user_id, due_time, guild_id) VALUES ($1, $2, $3, $4)",
-        )
-        .bind(data.ore_point_id)
-        .bind(data.user_id)
-        .bind(data.due_time)
-        .bind(data.guild_id)
-        .execute(&self.pool)
-        .await?;
-        Ok(())
-    }
+    /// 測試用的資料庫，重建 `TEST_DATABASE_URL` 上名為 `occupy_test_{name}` 的資料庫，未設定時回傳 None
+    #[cfg(test)]
+    pub async fn test(name: &str) -> Option<Self> {
+        use sqlx::{postgres::PgConnectOptions, Executor};
+        use std::str::FromStr;
 
-    pub async fn get_ore_types(&self) -> SqlResult<Vec<OreType>> {
-        sqlx::query_as("SELECT * FROM ore_type ORDER BY id")
-            .fetch_all(&self.pool)
+        let url = std::env::var("TEST_DATABASE_URL").ok()?;
+        let options = PgConnectOptions::from_str(&url).unwrap();
+        let database = format!("occupy_test_{name}");
+        let admin = PgPool::connect_with(options.clone()).await.unwrap();
+        admin
+            .execute(format!("DROP DATABASE IF EXISTS {database} WITH (FORCE)").as_str())
             .await
-    }
+            .unwrap();
+        admin
+            .execute(format!("CREATE DATABASE {database}").as_str())
+            .await
+            .unwrap();
+        admin.close().await;
 
-    pub async fn get_ore_points(&self) -> SqlResult<Vec<OrePoint>> {
-        sqlx::query_as("SELECT * FROM ore_point ORDER BY id")
-            .fetch_all(&self.pool)
+        let pool = PgPool::connect_with(options.database(&database))
             .await
+            .unwrap();
+        pool.execute(include_str!("../schema.sql")).await.unwrap();
+        Some(Self { pool })
     }
 
-    pub async fn has_occupy_type(
-        &self,
-        guild_id: u64,
-        user_id: u64,
-        ore_type: i32,
-    ) -> SqlResult<bool> {
-        let row = sqlx::query("SELECT * FROM occupy_table INNER JOIN ore_point ON occupy_table.ore_point_id = ore_point.id WHERE guild_id = $1 AND ( user_id = $2 OR battle_user_id = $2 ) AND ((ore_type & $3) <> 0) LIMIT 1")
-            .bind(guild_id as i64)
-            .bind(user_id as i64)
-            .bind(ore_type)
-            .fetch_optional(&self.pool)
-            .await?;
-        Ok(row.is_some())
+    /// 不連線資料庫的 BotDB，查詢會很快失敗
+    #[cfg(test)]
+    pub fn offline() -> Self {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(100))
+            .connect_lazy("postgres://127.0.0.1:1/test")
+            .unwrap();
+        Self { pool }
     }
 
-    pub async fn get_occupy_data(
+    /// 記錄查詢的耗時與錯誤次數
+    async fn timed<T>(
         &self,
-        guild_id: u64,
-        ore_point_id: i32,
-    ) -> SqlResult<Option<OccupyData>> {
-        let row: Option<OccupyDB> =
-            sqlx::query_as("SELECT * FROM occupy_table WHERE guild_id = $1 AND ore_point_id = $2")
-                .bind(guild_id as i64)
-                .bind(ore_point_id)
-                .fetch_optional(&self.pool)
+        method: &'static str,
+        query: impl Future<Output = SqlResult<T>>,
+    ) -> SqlResult<T> {
+        let start = Instant::now();
+        let result = query.await;
+        metrics::observe_db(method, start.elapsed(), result.is_err());
+        result
+    }
+
+    pub async fn ping(&self) -> SqlResult {
+        self.timed("ping", async move {
+            sqlx::query("SELECT 1").execute(&self.pool).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 統計各伺服器與世界被佔領及挑戰中的礦點數量
+    pub async fn get_occupation_counts(&self) -> SqlResult<Vec<OccupationCount>> {
+        self.timed("get_occupation_counts", async move {
+            let rows: Vec<OccupationCountDB> = sqlx::query_as("SELECT guild_id, world_id, COUNT(*) AS occupied, COUNT(battle_user_id) AS challenged FROM occupy_table GROUP BY guild_id, world_id")
+                .fetch_all(&self.pool)
                 .await?;
-        Ok(row.map(|x| x.into()))
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
     }
 
-    pub async fn update_occupy_data(&self, data: OccupyData) -> SqlResult {
-        let data: OccupyDB = data.into();
-        sqlx::query("UPDATE occupy_table SET user_id = $1, due_time = $2, battle_user_id = $3 WHERE guild_id = $4 AND ore_point_id = $5")
+    pub async fn occupy(&self, data: OccupyData) -> SqlResult {
+        self.timed("occupy", async move {
+            let data: OccupyDB = data.into();
+            sqlx::query(
+                "INSERT INTO occupy_table(ore_point_id, user_id, due_time, guild_id, world_id, team_id) VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(data.ore_point_id)
             .bind(data.user_id)
             .bind(data.due_time)
-            .bind(data.battle_user_id)
             .bind(data.guild_id)
-            .bind(data.ore_point_id)
+            .bind(data.world_id)
+            .bind(data.team_id)
             .execute(&self.pool)
             .await?;
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
+    pub async fn get_ore_types(&self) -> SqlResult<Vec<OreType>> {
+        self.timed("get_ore_types", async move {
+            sqlx::query_as("SELECT * FROM ore_type ORDER BY id")
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    pub async fn get_ore_points(&self) -> SqlResult<Vec<OrePoint>> {
+        self.timed("get_ore_points", async move {
+            sqlx::query_as("SELECT * FROM ore_point ORDER BY id")
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    /// 取得礦點與礦種名稱的翻譯 (類別, 編號, 語言, 名稱)
+    pub async fn get_translations(&self) -> SqlResult<Vec<(String, i32, String, String)>> {
+        self.timed("get_translations", async move {
+            sqlx::query_as("SELECT category, id, locale, name FROM translation")
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    pub async fn has_occupy_type(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        user_id: u64,
+        team_id: Option<i32>,
+        ore_type: i32,
+    ) -> SqlResult<bool> {
+        self.timed("has_occupy_type", async move {
+            let row = sqlx::query("SELECT * FROM occupy_table INNER JOIN ore_point ON occupy_table.ore_point_id = ore_point.id WHERE guild_id = $1 AND world_id = $2 AND ( user_id = $3 OR battle_user_id = $3 OR team_id = $5 OR battle_team_id = $5 ) AND ((ore_type & $4) <> 0) LIMIT 1")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(user_id as i64)
+                .bind(ore_type)
+                .bind(team_id)
+                .fetch_optional(&self.pool)
+                .await?;
+            Ok(row.is_some())
+        })
+        .await
+    }
+
+    pub async fn has_queued_type(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        user_id: u64,
+        team_id: Option<i32>,
+        ore_type: i32,
+    ) -> SqlResult<bool> {
+        self.timed("has_queued_type", async move {
+            let row = sqlx::query("SELECT * FROM challenge_queue INNER JOIN ore_point ON challenge_queue.ore_point_id = ore_point.id WHERE guild_id = $1 AND world_id = $2 AND ( user_id = $3 OR team_id = $5 ) AND ((ore_type & $4) <> 0) LIMIT 1")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(user_id as i64)
+                .bind(ore_type)
+                .bind(team_id)
+                .fetch_optional(&self.pool)
+                .await?;
+            Ok(row.is_some())
+        })
+        .await
+    }
+
+    pub async fn get_occupy_data(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        ore_point_id: i32,
+    ) -> SqlResult<Option<OccupyData>> {
+        self.timed("get_occupy_data", async move {
+            let row: Option<OccupyDB> = sqlx::query_as(
+                "SELECT * FROM occupy_table WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3",
+            )
+            .bind(guild_id as i64)
+            .bind(world_id)
+            .bind(ore_point_id)
+            .fetch_optional(&self.pool)
+            .await?;
+            Ok(row.map(|x| x.into()))
+        })
+        .await
+    }
+
+    pub async fn get_occupations(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+    ) -> SqlResult<Vec<OccupyData>> {
+        self.timed("get_occupations", async move {
+            let rows: Vec<OccupyDB> =
+                sqlx::query_as("SELECT * FROM occupy_table WHERE guild_id = $1 AND world_id = $2")
+                    .bind(guild_id as i64)
+                    .bind(world_id)
+                    .fetch_all(&self.pool)
+                    .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
+    }
+
+    /// 取得伺服器所有世界的佔領資料
+    pub async fn get_guild_occupations(&self, guild_id: u64) -> SqlResult<Vec<OccupyData>> {
+        self.timed("get_guild_occupations", async move {
+            let rows: Vec<OccupyDB> =
+                sqlx::query_as("SELECT * FROM occupy_table WHERE guild_id = $1")
+                    .bind(guild_id as i64)
+                    .fetch_all(&self.pool)
+                    .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
+    }
+
+    pub async fn update_occupy_data(&self, data: OccupyData) -> SqlResult {
+        self.timed("update_occupy_data", async move {
+            let data: OccupyDB = data.into();
+            sqlx::query("UPDATE occupy_table SET user_id = $1, due_time = $2, battle_user_id = $3, team_id = $4, battle_team_id = $5, battle_time = $9, battle_thread_id = $10, shield_until = $11 WHERE guild_id = $6 AND world_id = $7 AND ore_point_id = $8")
+                .bind(data.user_id)
+                .bind(data.due_time)
+                .bind(data.battle_user_id)
+                .bind(data.team_id)
+                .bind(data.battle_team_id)
+                .bind(data.guild_id)
+                .bind(data.world_id)
+                .bind(data.ore_point_id)
+                .bind(data.battle_time)
+                .bind(data.battle_thread_id)
+                .bind(data.shield_until)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 結算挑戰，更新礦點、移除約戰並寫入佔領紀錄，挑戰者已不是 `battle_user_id` 時不結算並回傳 false
+    pub async fn settle_challenge(
+        &self,
+        data: OccupyData,
+        battle_user_id: u64,
+        history: HistoryData,
+    ) -> SqlResult<bool> {
+        self.timed("settle_challenge", async move {
+            let data: OccupyDB = data.into();
+            let mut trans = self.pool.begin().await?;
+
+            let result = sqlx::query("UPDATE occupy_table SET user_id = $1, due_time = $2, battle_user_id = $3, team_id = $4, battle_team_id = $5, battle_time = $9, battle_thread_id = $10, shield_until = $11 WHERE guild_id = $6 AND world_id = $7 AND ore_point_id = $8 AND battle_user_id = $12")
+                .bind(data.user_id)
+                .bind(data.due_time)
+                .bind(data.battle_user_id)
+                .bind(data.team_id)
+                .bind(data.battle_team_id)
+                .bind(data.guild_id)
+                .bind(data.world_id)
+                .bind(data.ore_point_id)
+                .bind(data.battle_time)
+                .bind(data.battle_thread_id)
+                .bind(data.shield_until)
+                .bind(battle_user_id as i64)
+                .execute(&mut *trans)
+                .await?;
+            // 已被其他請求結算
+            if result.rows_affected() == 0 {
+                return Ok(false);
+            }
+
+            sqlx::query("DELETE FROM battle_proposal WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3")
+                .bind(data.guild_id)
+                .bind(data.world_id)
+                .bind(data.ore_point_id)
+                .execute(&mut *trans)
+                .await?;
+
+            sqlx::query("INSERT INTO occupy_history(guild_id, world_id, team_id, ore_point_id, user_id, target_user_id, action) VALUES ($1, $2, $3, $4, $5, $6, $7)")
+                .bind(history.guild_id as i64)
+                .bind(history.world_id)
+                .bind(history.team_id)
+                .bind(history.ore_point_id)
+                .bind(history.user_id as i64)
+                .bind(history.target_user_id.map(|x| x as i64))
+                .bind(history.action.as_str())
+                .execute(&mut *trans)
+                .await?;
+
+            trans.commit().await?;
+            Ok(true)
+        })
+        .await
+    }
+
+    /// 強制佔領礦點，並清除原本的挑戰、約戰與挑戰隊列
     pub async fn force_occupy(&self, data: OccupyData) -> SqlResult {
-        let data: OccupyDB = data.into();
-        sqlx::query(
-            r#"INSERT INTO occupy_table(ore_point_id, user_id, due_time, guild_id) VALUES ($1, $2, $3, $4)
-                ON CONFLICT (ore_point_id, guild_id) DO UPDATE SET user_id = $2, due_time = $3, battle_user_id = NULL
-            "#,
-        )
-        .bind(data.ore_point_id)
-        .bind(data.user_id)
-        .bind(data.due_time)
-        .bind(data.guild_id)
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+        self.timed("force_occupy", async move {
+            let data: OccupyDB = data.into();
+            let mut trans = self.pool.begin().await?;
+
+            for table in ["battle_proposal", "challenge_queue"] {
+                sqlx::query(&format!(
+                    "DELETE FROM {table} WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3"
+                ))
+                .bind(data.guild_id)
+                .bind(data.world_id)
+                .bind(data.ore_point_id)
+                .execute(&mut *trans)
+                .await?;
+            }
+            sqlx::query(
+                r#"INSERT INTO occupy_table(ore_point_id, user_id, due_time, guild_id, world_id, team_id) VALUES ($1, $2, $3, $4, $5, $6)
+                    ON CONFLICT (guild_id, world_id, ore_point_id) DO UPDATE SET user_id = $2, due_time = $3, team_id = $6, battle_user_id = NULL, battle_team_id = NULL, battle_time = NULL, battle_thread_id = NULL, shield_until = NULL
+                "#,
+            )
+            .bind(data.ore_point_id)
+            .bind(data.user_id)
+            .bind(data.due_time)
+            .bind(data.guild_id)
+            .bind(data.world_id)
+            .bind(data.team_id)
+            .execute(&mut *trans)
+            .await?;
+
+            trans.commit().await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 調整多座礦點的到期時間，並在同一交易中記錄佔領紀錄與管理操作
+    ///
+    /// 佔領者或到期時間已變更的礦點會略過，回傳實際調整的礦點數
+    pub async fn adjust_due_times(
+        &self,
+        user_id: u64,
+        adjustments: Vec<(OccupyData, DateTime<Utc>)>,
+    ) -> SqlResult<usize> {
+        self.timed("adjust_due_times", async move {
+            let mut trans = self.pool.begin().await?;
+            let mut count = 0;
+
+            for (data, due_time) in adjustments {
+                let updated = sqlx::query("UPDATE occupy_table SET due_time = $4 WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3 AND user_id = $5 AND due_time = $6")
+                    .bind(data.guild_id as i64)
+                    .bind(data.world_id)
+                    .bind(data.ore_point_id)
+                    .bind(due_time)
+                    .bind(data.user_id as i64)
+                    .bind(data.due_time)
+                    .execute(&mut *trans)
+                    .await?;
+                if updated.rows_affected() == 0 {
+                    continue;
+                }
+                count += 1;
+
+                sqlx::query("INSERT INTO occupy_history(guild_id, world_id, team_id, ore_point_id, user_id, action) VALUES ($1, $2, $3, $4, $5, $6)")
+                    .bind(data.guild_id as i64)
+                    .bind(data.world_id)
+                    .bind(data.team_id)
+                    .bind(data.ore_point_id)
+                    .bind(data.user_id as i64)
+                    .bind(HistoryAction::AdjustDue.as_str())
+                    .execute(&mut *trans)
+                    .await?;
+
+                insert_admin_action(
+                    &mut *trans,
+                    AdminActionData {
+                        guild_id: data.guild_id,
+                        world_id: data.world_id,
+                        ore_point_id: data.ore_point_id,
+                        user_id,
+                        action: HistoryAction::AdjustDue,
+                        current: Some(OccupyData {
+                            due_time,
+                            ..data.clone()
+                        }),
+                        previous: Some(data),
+                    },
+                )
+                .await?;
+            }
+
+            trans.commit().await?;
+            Ok(count)
+        })
+        .await
+    }
+
+    /// 釋放礦點，並清除約戰與挑戰隊列，礦點已不是 `user_id` 佔領或有挑戰者時不釋放並回傳 None
+    pub async fn release_occupy(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        ore_point_id: i32,
+        user_id: u64,
+    ) -> SqlResult<Option<OccupyData>> {
+        self.timed("release_occupy", async move {
+            let mut trans = self.pool.begin().await?;
+
+            let released: Option<OccupyDB> = sqlx::query_as("DELETE FROM occupy_table WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3 AND user_id = $4 AND battle_user_id IS NULL RETURNING *")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(ore_point_id)
+                .bind(user_id as i64)
+                .fetch_optional(&mut *trans)
+                .await?;
+            let Some(released) = released else {
+                return Ok(None);
+            };
+
+            for table in ["battle_proposal", "challenge_queue"] {
+                sqlx::query(&format!(
+                    "DELETE FROM {table} WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3"
+                ))
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(ore_point_id)
+                .execute(&mut *trans)
+                .await?;
+            }
+
+            trans.commit().await?;
+            Ok(Some(released.into()))
+        })
+        .await
+    }
+
+    pub async fn get_point_data(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        start: u32,
+        length: u32,
+    ) -> SqlResult<Vec<ListResult>> {
+        self.timed("get_point_data", async move {
+            let row: Vec<ListResultDB> = sqlx::query_as(
+                r#"SELECT ore_point.*, occupy_table.user_id, occupy_table.due_time, occupy_table.battle_user_id, occupy_table.battle_time, occupy_table.shield_until, vacation.end_time AS leave_until, owner_team.name AS team_name, battle_team.name AS battle_team_name,
+                    (SELECT COUNT(*) FROM challenge_queue WHERE challenge_queue.guild_id = $1 AND challenge_queue.world_id = $2 AND challenge_queue.ore_point_id = ore_point.id) AS queue_count
+                    FROM ore_point
+                    LEFT JOIN occupy_table ON occupy_table.ore_point_id = ore_point.id AND occupy_table.guild_id = $1 AND occupy_table.world_id = $2
+                    LEFT JOIN vacation ON vacation.guild_id = $1 AND vacation.world_id = $2 AND vacation.user_id = occupy_table.user_id AND vacation.delegate_user_id IS NULL AND vacation.end_time > now()
+                    LEFT JOIN team AS owner_team ON owner_team.id = occupy_table.team_id
+                    LEFT JOIN team AS battle_team ON battle_team.id = occupy_table.battle_team_id
+                    ORDER BY ore_point.id OFFSET $3 LIMIT $4
+                "#,
+            )
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(start as i64)
+                .bind(length as i64)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(row.into_iter().map(|x| x.into()).collect())
+        })
+        .await
+    }
+
+    pub async fn get_point_count(&self) -> SqlResult<u32> {
+        self.timed("get_point_count", async move {
+            let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM ore_point")
+                .fetch_one(&self.pool)
+                .await?;
+            Ok(count as u32)
+        })
+        .await
+    }
+
+    pub async fn set_guild_notify_role(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        role_id: Option<u64>,
+    ) -> SqlResult {
+        self.timed("set_guild_notify_role", async move {
+            match role_id {
+                Some(role_id) => sqlx::query("INSERT INTO battle_notify_role(guild_id, world_id, role_id) VALUES ($1, $2, $3) ON CONFLICT (guild_id, world_id) DO UPDATE SET role_id = $3")
+                    .bind(guild_id as i64)
+                    .bind(world_id)
+                    .bind(role_id as i64),
+                None => sqlx::query("DELETE FROM battle_notify_role WHERE guild_id = $1 AND world_id = $2")
+                    .bind(guild_id as i64)
+                    .bind(world_id),
+            }
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn set_ore_type_notify_role(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        ore_type: i32,
+        role_id: Option<u64>,
+    ) -> SqlResult {
+        self.timed("set_ore_type_notify_role", async move {
+            match role_id {
+                Some(role_id) => sqlx::query("INSERT INTO ore_type_notify_role(guild_id, world_id, ore_type, role_id) VALUES ($1, $2, $3, $4) ON CONFLICT (guild_id, world_id, ore_type) DO UPDATE SET role_id = $4")
+                    .bind(guild_id as i64)
+                    .bind(world_id)
+                    .bind(ore_type)
+                    .bind(role_id as i64),
+                None => sqlx::query("DELETE FROM ore_type_notify_role WHERE guild_id = $1 AND world_id = $2 AND ore_type = $3")
+                    .bind(guild_id as i64)
+                    .bind(world_id)
+                    .bind(ore_type),
+            }
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 取得礦點的通知身分組，包含世界的通知身分組與符合礦種的身分組
+    pub async fn get_notify_roles(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        ore_type: i32,
+    ) -> SqlResult<Vec<u64>> {
+        self.timed("get_notify_roles", async move {
+            let rows: Vec<(i64,)> = sqlx::query_as("SELECT role_id FROM battle_notify_role WHERE guild_id = $1 AND world_id = $2 UNION SELECT role_id FROM ore_type_notify_role WHERE guild_id = $1 AND world_id = $2 AND (ore_type & $3) <> 0")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(ore_type)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows.into_iter().map(|x| x.0 as u64).collect())
+        })
+        .await
+    }
+
+    /// 紀錄指令或元件互動，回傳紀錄編號以便稍後寫入結果
+    pub async fn write_log(&self, data: CommandLog<'_>) -> SqlResult<i64> {
+        self.timed("write_log", async move {
+            let (id,): (i64,) = sqlx::query_as("INSERT INTO command_log(guild_id, channel_id, user_id, content, command, ore_point_id) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id")
+                .bind(data.guild_id.map(|x| x as i64))
+                .bind(data.channel_id as i64)
+                .bind(data.user_id as i64)
+                .bind(data.content)
+                .bind(data.command)
+                .bind(data.ore_point_id)
+                .fetch_one(&self.pool)
+                .await?;
+            Ok(id)
+        })
+        .await
+    }
+
+    pub async fn set_log_result(&self, id: i64, error: Option<&str>) -> SqlResult {
+        self.timed("set_log_result", async move {
+            sqlx::query("UPDATE command_log SET success = $2, error = $3 WHERE id = $1")
+                .bind(id)
+                .bind(error.is_none())
+                .bind(error)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 依條件查詢伺服器的指令紀錄，由新到舊排列
+    pub async fn get_logs(
+        &self,
+        guild_id: u64,
+        filter: &LogFilter,
+        offset: u32,
+        limit: u32,
+    ) -> SqlResult<Vec<LogRecord>> {
+        self.timed("get_logs", async move {
+            let rows: Vec<LogRecordDB> = sqlx::query_as("SELECT channel_id, user_id, content, ore_point_id, success, error, time FROM command_log WHERE guild_id = $1 AND ($2::bigint IS NULL OR user_id = $2) AND ($3::bigint IS NULL OR channel_id = $3) AND ($4::text IS NULL OR command LIKE $4) AND ($5::timestamptz IS NULL OR time >= $5) AND ($6::timestamptz IS NULL OR time < $6) ORDER BY id DESC OFFSET $7 LIMIT $8")
+                .bind(guild_id as i64)
+                .bind(filter.user_id.map(|x| x as i64))
+                .bind(filter.channel_id.map(|x| x as i64))
+                .bind(filter.command_pattern())
+                .bind(filter.since)
+                .bind(filter.until)
+                .bind(offset as i64)
+                .bind(limit as i64)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
+    }
+
+    pub async fn count_logs(&self, guild_id: u64, filter: &LogFilter) -> SqlResult<u32> {
+        self.timed("count_logs", async move {
+            let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM command_log WHERE guild_id = $1 AND ($2::bigint IS NULL OR user_id = $2) AND ($3::bigint IS NULL OR channel_id = $3) AND ($4::text IS NULL OR command LIKE $4) AND ($5::timestamptz IS NULL OR time >= $5) AND ($6::timestamptz IS NULL OR time < $6)")
+                .bind(guild_id as i64)
+                .bind(filter.user_id.map(|x| x as i64))
+                .bind(filter.channel_id.map(|x| x as i64))
+                .bind(filter.command_pattern())
+                .bind(filter.since)
+                .bind(filter.until)
+                .fetch_one(&self.pool)
+                .await?;
+            Ok(count as u32)
+        })
+        .await
+    }
+
+    /// 刪除超過伺服器保留期限的指令紀錄，回傳刪除的數量
+    pub async fn purge_logs(&self, now: DateTime<Utc>) -> SqlResult<u64> {
+        self.timed("purge_logs", async move {
+            let result = sqlx::query("DELETE FROM command_log USING guild_config WHERE command_log.guild_id = guild_config.guild_id AND guild_config.log_retention_days IS NOT NULL AND command_log.time < $1 - make_interval(days => guild_config.log_retention_days)")
+                .bind(now)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+
+    pub async fn set_log_retention(&self, guild_id: u64, days: Option<i32>) -> SqlResult {
+        self.timed("set_log_retention", async move {
+            sqlx::query("INSERT INTO guild_config(guild_id, log_retention_days) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET log_retention_days = $2")
+                .bind(guild_id as i64)
+                .bind(days)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn add_history(&self, data: HistoryData) -> SqlResult {
+        self.timed("add_history", async move {
+            sqlx::query("INSERT INTO occupy_history(guild_id, world_id, team_id, ore_point_id, user_id, target_user_id, action) VALUES ($1, $2, $3, $4, $5, $6, $7)")
+                .bind(data.guild_id as i64)
+                .bind(data.world_id)
+                .bind(data.team_id)
+                .bind(data.ore_point_id)
+                .bind(data.user_id as i64)
+                .bind(data.target_user_id.map(|x| x as i64))
+                .bind(data.action.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn add_admin_action(&self, data: AdminActionData) -> SqlResult {
+        self.timed("add_admin_action", insert_admin_action(&self.pool, data))
+            .await
+    }
+
+    /// 取得伺服器最近的管理操作，由新到舊排列
+    pub async fn get_admin_actions(
+        &self,
+        guild_id: u64,
+        limit: u32,
+    ) -> SqlResult<Vec<AdminAction>> {
+        self.timed("get_admin_actions", async move {
+            let rows: Vec<AdminActionDB> = sqlx::query_as(
+                "SELECT * FROM admin_action WHERE guild_id = $1 ORDER BY id DESC LIMIT $2",
+            )
+            .bind(guild_id as i64)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
+    }
+
+    pub async fn get_admin_action(&self, guild_id: u64, id: i32) -> SqlResult<Option<AdminAction>> {
+        self.timed("get_admin_action", async move {
+            let row: Option<AdminActionDB> =
+                sqlx::query_as("SELECT * FROM admin_action WHERE guild_id = $1 AND id = $2")
+                    .bind(guild_id as i64)
+                    .bind(id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+            Ok(row.map(|x| x.into()))
+        })
+        .await
+    }
+
+    /// 將礦點還原為管理操作前的狀態，礦點在操作後已有變動或操作屬於已結束的賽季時不還原並回傳 false
+    pub async fn revert_admin_action(&self, action: &AdminAction) -> SqlResult<bool> {
+        self.timed("revert_admin_action", async move {
+            let mut trans = self.pool.begin().await?;
+
+            let marked = sqlx::query(
+                "UPDATE admin_action SET reverted = TRUE WHERE id = $1 AND NOT reverted AND season_id IS NULL",
+            )
+            .bind(action.id)
+            .execute(&mut *trans)
+            .await?;
+            if marked.rows_affected() == 0 {
+                return Ok(false);
+            }
+
+            let current: Option<OccupyDB> = sqlx::query_as(
+                "SELECT * FROM occupy_table WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3 FOR UPDATE",
+            )
+            .bind(action.guild_id as i64)
+            .bind(action.world_id)
+            .bind(action.ore_point_id)
+            .fetch_optional(&mut *trans)
+            .await?;
+            if !undo::unchanged(action, current.map(OccupyData::from).as_ref()) {
+                return Ok(false);
+            }
+
+            // 標記操作產生的佔領紀錄，不再計入排行榜與冷卻
+            sqlx::query("UPDATE occupy_history SET reverted = TRUE WHERE id = (SELECT id FROM occupy_history WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3 AND action = $4 AND time <= $5 ORDER BY time DESC, id DESC LIMIT 1)")
+                .bind(action.guild_id as i64)
+                .bind(action.world_id)
+                .bind(action.ore_point_id)
+                .bind(&action.action)
+                .bind(action.time)
+                .execute(&mut *trans)
+                .await?;
+
+            match &action.previous {
+                Some(previous) => {
+                    sqlx::query(
+                        r#"INSERT INTO occupy_table(ore_point_id, guild_id, world_id, user_id, due_time, battle_user_id, team_id, battle_team_id, battle_time, battle_thread_id, shield_until) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                            ON CONFLICT (guild_id, world_id, ore_point_id) DO UPDATE SET user_id = $4, due_time = $5, battle_user_id = $6, team_id = $7, battle_team_id = $8, battle_time = $9, battle_thread_id = $10, battle_reminded = FALSE, due_notified_at = NULL, shield_until = $11
+                        "#,
+                    )
+                    .bind(previous.ore_point_id)
+                    .bind(previous.guild_id as i64)
+                    .bind(previous.world_id)
+                    .bind(previous.user_id as i64)
+                    .bind(previous.due_time)
+                    .bind(previous.battle_user_id.map(|x| x as i64))
+                    .bind(previous.team_id)
+                    .bind(previous.battle_team_id)
+                    .bind(previous.battle_time)
+                    .bind(previous.battle_thread_id.map(|x| x as i64))
+                    .bind(previous.shield_until)
+                    .execute(&mut *trans)
+                    .await?;
+                }
+                None => {
+                    sqlx::query(
+                        "DELETE FROM occupy_table WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3",
+                    )
+                    .bind(action.guild_id as i64)
+                    .bind(action.world_id)
+                    .bind(action.ore_point_id)
+                    .execute(&mut *trans)
+                    .await?;
+                }
+            }
+
+            trans.commit().await?;
+            Ok(true)
+        })
+        .await
+    }
+
+    /// 結束目前賽季，封存佔領資料與紀錄並清空礦點，回傳新賽季編號與需要關閉的挑戰討論串
+    pub async fn start_season(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        start_time: DateTime<Utc>,
+    ) -> SqlResult<(i32, Vec<u64>)> {
+        self.timed("start_season", async move {
+            let mut trans = self.pool.begin().await?;
+
+            let current: Option<(i32, i32)> = sqlx::query_as(
+                "SELECT id, number FROM season WHERE guild_id = $1 AND world_id = $2 AND end_time IS NULL",
+            )
+            .bind(guild_id as i64)
+            .bind(world_id)
+            .fetch_optional(&mut *trans)
+            .await?;
+
+            // 尚未有賽季紀錄時，以最早的佔領紀錄作為第一季的開始時間
+            let (season_id, number): (i32, i32) = match current {
+                Some(season) => season,
+                None => {
+                    sqlx::query_as("INSERT INTO season(guild_id, world_id, number, start_time) VALUES ($1, $2, 1, COALESCE((SELECT MIN(time) FROM occupy_history WHERE guild_id = $1 AND world_id = $2), $3)) RETURNING id, number")
+                        .bind(guild_id as i64)
+                        .bind(world_id)
+                        .bind(start_time)
+                        .fetch_one(&mut *trans)
+                        .await?
+                }
+            };
+
+            sqlx::query("INSERT INTO occupy_archive(season_id, ore_point_id, guild_id, world_id, user_id, due_time, battle_user_id, team_id) SELECT $1, ore_point_id, guild_id, world_id, user_id, due_time, battle_user_id, team_id FROM occupy_table WHERE guild_id = $2 AND world_id = $3")
+                .bind(season_id)
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .execute(&mut *trans)
+                .await?;
+
+            sqlx::query("UPDATE occupy_history SET season_id = $1 WHERE guild_id = $2 AND world_id = $3 AND season_id IS NULL")
+                .bind(season_id)
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .execute(&mut *trans)
+                .await?;
+
+            // 礦點已清空，之前的管理操作不再能復原
+            sqlx::query("UPDATE admin_action SET season_id = $1 WHERE guild_id = $2 AND world_id = $3 AND season_id IS NULL")
+                .bind(season_id)
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .execute(&mut *trans)
+                .await?;
+
+            let threads: Vec<(Option<i64>,)> = sqlx::query_as(
+                "DELETE FROM occupy_table WHERE guild_id = $1 AND world_id = $2 RETURNING battle_thread_id",
+            )
+            .bind(guild_id as i64)
+            .bind(world_id)
+            .fetch_all(&mut *trans)
+            .await?;
+
+            sqlx::query("DELETE FROM battle_proposal WHERE guild_id = $1 AND world_id = $2")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .execute(&mut *trans)
+                .await?;
+
+            sqlx::query("DELETE FROM challenge_queue WHERE guild_id = $1 AND world_id = $2")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .execute(&mut *trans)
+                .await?;
+
+            sqlx::query("DELETE FROM vacation WHERE guild_id = $1 AND world_id = $2")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .execute(&mut *trans)
+                .await?;
+
+            sqlx::query("UPDATE season SET end_time = $1 WHERE id = $2")
+                .bind(start_time)
+                .bind(season_id)
+                .execute(&mut *trans)
+                .await?;
+
+            sqlx::query(
+                "INSERT INTO season(guild_id, world_id, number, start_time) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(guild_id as i64)
+            .bind(world_id)
+            .bind(number + 1)
+            .bind(start_time)
+            .execute(&mut *trans)
+            .await?;
+
+            sqlx::query("DELETE FROM season_schedule WHERE guild_id = $1 AND world_id = $2")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .execute(&mut *trans)
+                .await?;
+
+            trans.commit().await?;
+            Ok((
+                number + 1,
+                threads
+                    .into_iter()
+                    .filter_map(|(thread_id,)| thread_id.map(|x| x as u64))
+                    .collect(),
+            ))
+        })
+        .await
+    }
+
+    pub async fn get_seasons(&self, guild_id: u64, world_id: i32) -> SqlResult<Vec<SeasonData>> {
+        self.timed("get_seasons", async move {
+            let rows: Vec<SeasonDB> = sqlx::query_as(
+                "SELECT number, start_time, end_time FROM season WHERE guild_id = $1 AND world_id = $2 ORDER BY number DESC",
+            )
+            .bind(guild_id as i64)
+            .bind(world_id)
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
+    }
+
+    /// 取得賽季結束時的佔領資料 (礦點編號, 佔領者)
+    pub async fn get_season_archive(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        number: i32,
+    ) -> SqlResult<Vec<(i32, u64)>> {
+        self.timed("get_season_archive", async move {
+            let rows: Vec<(i32, i64)> = sqlx::query_as("SELECT occupy_archive.ore_point_id, occupy_archive.user_id FROM occupy_archive INNER JOIN season ON occupy_archive.season_id = season.id WHERE season.guild_id = $1 AND season.world_id = $2 AND season.number = $3 ORDER BY occupy_archive.ore_point_id")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(number)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows.into_iter().map(|(id, user_id)| (id, user_id as u64)).collect())
+        })
+        .await
+    }
+
+    pub async fn schedule_season(&self, data: SeasonSchedule) -> SqlResult {
+        self.timed("schedule_season", async move {
+            sqlx::query("INSERT INTO season_schedule(guild_id, world_id, channel_id, start_time) VALUES ($1, $2, $3, $4) ON CONFLICT (guild_id, world_id) DO UPDATE SET channel_id = $3, start_time = $4")
+                .bind(data.guild_id as i64)
+                .bind(data.world_id)
+                .bind(data.channel_id as i64)
+                .bind(data.start_time)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_season_schedule(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+    ) -> SqlResult<Option<SeasonSchedule>> {
+        self.timed("get_season_schedule", async move {
+            let row: Option<(i64, i32, i64, DateTime<Utc>)> = sqlx::query_as(
+                "SELECT guild_id, world_id, channel_id, start_time FROM season_schedule WHERE guild_id = $1 AND world_id = $2",
+            )
+            .bind(guild_id as i64)
+            .bind(world_id)
+            .fetch_optional(&self.pool)
+            .await?;
+            Ok(row.map(
+                |(guild_id, world_id, channel_id, start_time)| SeasonSchedule {
+                    guild_id: guild_id as u64,
+                    world_id,
+                    channel_id: channel_id as u64,
+                    start_time,
+                },
+            ))
+        })
+        .await
+    }
+
+    /// 取消排定的新賽季，沒有排程時回傳 false
+    pub async fn cancel_season_schedule(&self, guild_id: u64, world_id: i32) -> SqlResult<bool> {
+        self.timed("cancel_season_schedule", async move {
+            let result =
+                sqlx::query("DELETE FROM season_schedule WHERE guild_id = $1 AND world_id = $2")
+                    .bind(guild_id as i64)
+                    .bind(world_id)
+                    .execute(&self.pool)
+                    .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    pub async fn get_due_season_schedules(
+        &self,
+        now: DateTime<Utc>,
+    ) -> SqlResult<Vec<SeasonSchedule>> {
+        self.timed("get_due_season_schedules", async move {
+            let rows: Vec<(i64, i32, i64, DateTime<Utc>)> = sqlx::query_as(
+                "SELECT guild_id, world_id, channel_id, start_time FROM season_schedule WHERE start_time <= $1",
+            )
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows
+                .into_iter()
+                .map(
+                    |(guild_id, world_id, channel_id, start_time)| SeasonSchedule {
+                        guild_id: guild_id as u64,
+                        world_id,
+                        channel_id: channel_id as u64,
+                        start_time,
+                    },
+                )
+                .collect())
+        })
+        .await
+    }
+
+    pub async fn create_world(&self, guild_id: u64, name: &str) -> SqlResult<i32> {
+        self.timed("create_world", async move {
+            let (id,): (i32,) =
+                sqlx::query_as("INSERT INTO world(guild_id, name) VALUES ($1, $2) RETURNING id")
+                    .bind(guild_id as i64)
+                    .bind(name)
+                    .fetch_one(&self.pool)
+                    .await?;
+            Ok(id)
+        })
+        .await
+    }
+
+    /// 刪除世界與頻道綁定，世界仍有佔領資料時不會刪除
+    pub async fn delete_world(&self, guild_id: u64, world_id: i32) -> SqlResult<bool> {
+        self.timed("delete_world", async move {
+            let mut trans = self.pool.begin().await?;
+
+            let occupied: Option<(i32,)> = sqlx::query_as(
+                "SELECT ore_point_id FROM occupy_table WHERE guild_id = $1 AND world_id = $2 LIMIT 1",
+            )
+            .bind(guild_id as i64)
+            .bind(world_id)
+            .fetch_optional(&mut *trans)
+            .await?;
+            if occupied.is_some() {
+                return Ok(false);
+            }
+
+            // 佔領紀錄與賽季封存保留作為歷史，其餘屬於此世界的資料一併刪除
+            for table in [
+                "channel_world",
+                "season_schedule",
+                "battle_proposal",
+                "challenge_queue",
+                "battle_notify_role",
+                "ore_type_notify_role",
+                "game_server",
+                "player_link",
+                "vacation",
+                "admin_action",
+            ] {
+                sqlx::query(&format!(
+                    "DELETE FROM {table} WHERE guild_id = $1 AND world_id = $2"
+                ))
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .execute(&mut *trans)
+                .await?;
+            }
+
+            sqlx::query("DELETE FROM world WHERE guild_id = $1 AND id = $2")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .execute(&mut *trans)
+                .await?;
+
+            trans.commit().await?;
+            Ok(true)
+        })
+        .await
+    }
+
+    pub async fn get_worlds(&self, guild_id: u64) -> SqlResult<Vec<(i32, String)>> {
+        self.timed("get_worlds", async move {
+            sqlx::query_as("SELECT id, name FROM world WHERE guild_id = $1 ORDER BY id")
+                .bind(guild_id as i64)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    pub async fn get_world_id(&self, guild_id: u64, name: &str) -> SqlResult<Option<i32>> {
+        self.timed("get_world_id", async move {
+            let result: Option<(i32,)> =
+                sqlx::query_as("SELECT id FROM world WHERE guild_id = $1 AND name = $2")
+                    .bind(guild_id as i64)
+                    .bind(name)
+                    .fetch_optional(&self.pool)
+                    .await?;
+            Ok(result.map(|x| x.0))
+        })
+        .await
+    }
+
+    pub async fn get_world_name(&self, guild_id: u64, world_id: i32) -> SqlResult<Option<String>> {
+        self.timed("get_world_name", async move {
+            let result: Option<(String,)> =
+                sqlx::query_as("SELECT name FROM world WHERE guild_id = $1 AND id = $2")
+                    .bind(guild_id as i64)
+                    .bind(world_id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+            Ok(result.map(|x| x.0))
+        })
+        .await
+    }
+
+    pub async fn bind_channel_world(
+        &self,
+        guild_id: u64,
+        channel_id: u64,
+        world_id: i32,
+    ) -> SqlResult {
+        self.timed("bind_channel_world", async move {
+            sqlx::query("INSERT INTO channel_world(channel_id, guild_id, world_id) VALUES ($1, $2, $3) ON CONFLICT (channel_id) DO UPDATE SET world_id = $3")
+                .bind(channel_id as i64)
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn unbind_channel_world(&self, channel_id: u64) -> SqlResult {
+        self.timed("unbind_channel_world", async move {
+            sqlx::query("DELETE FROM channel_world WHERE channel_id = $1")
+                .bind(channel_id as i64)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_channel_world(&self, channel_id: u64) -> SqlResult<Option<i32>> {
+        self.timed("get_channel_world", async move {
+            let result: Option<(i32,)> =
+                sqlx::query_as("SELECT world_id FROM channel_world WHERE channel_id = $1")
+                    .bind(channel_id as i64)
+                    .fetch_optional(&self.pool)
+                    .await?;
+            Ok(result.map(|x| x.0))
+        })
+        .await
+    }
+
+    pub async fn create_team(&self, guild_id: u64, name: &str, role_id: u64) -> SqlResult {
+        self.timed("create_team", async move {
+            sqlx::query("INSERT INTO team(guild_id, name, role_id) VALUES ($1, $2, $3)")
+                .bind(guild_id as i64)
+                .bind(name)
+                .bind(role_id as i64)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 刪除隊伍，並移除礦點上的隊伍資料
+    pub async fn delete_team(&self, guild_id: u64, team_id: i32) -> SqlResult {
+        self.timed("delete_team", async move {
+            let mut trans = self.pool.begin().await?;
+
+            sqlx::query("UPDATE occupy_table SET team_id = NULL WHERE guild_id = $1 AND team_id = $2")
+                .bind(guild_id as i64)
+                .bind(team_id)
+                .execute(&mut *trans)
+                .await?;
+
+            sqlx::query("UPDATE occupy_table SET battle_team_id = NULL WHERE guild_id = $1 AND battle_team_id = $2")
+                .bind(guild_id as i64)
+                .bind(team_id)
+                .execute(&mut *trans)
+                .await?;
+
+            sqlx::query("UPDATE challenge_queue SET team_id = NULL WHERE guild_id = $1 AND team_id = $2")
+                .bind(guild_id as i64)
+                .bind(team_id)
+                .execute(&mut *trans)
+                .await?;
+
+            sqlx::query("DELETE FROM team WHERE guild_id = $1 AND id = $2")
+                .bind(guild_id as i64)
+                .bind(team_id)
+                .execute(&mut *trans)
+                .await?;
+
+            trans.commit().await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 取得玩家最近一次挑戰落敗或撤回挑戰的時間，指定礦點時只計算該礦點
+    pub async fn last_challenge_loss(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        user_id: u64,
+        ore_point_id: Option<i32>,
+    ) -> SqlResult<Option<DateTime<Utc>>> {
+        self.timed("last_challenge_loss", async move {
+            let (time,): (Option<DateTime<Utc>>,) = sqlx::query_as("SELECT MAX(time) FROM occupy_history WHERE guild_id = $1 AND world_id = $2 AND NOT reverted AND ($4::integer IS NULL OR ore_point_id = $4) AND ((action = $5 AND target_user_id = $3) OR (action = $6 AND user_id = $3))")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(user_id as i64)
+                .bind(ore_point_id)
+                .bind(HistoryAction::ChallengeWon.as_str())
+                .bind(HistoryAction::Withdraw.as_str())
+                .fetch_one(&self.pool)
+                .await?;
+            Ok(time)
+        })
+        .await
+    }
+
+    /// 取得指定時間後結束的凍結期間，依開始時間排列
+    pub async fn get_freezes(
+        &self,
+        guild_id: u64,
+        since: DateTime<Utc>,
+    ) -> SqlResult<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+        self.timed("get_freezes", async move {
+            let rows = sqlx::query_as("SELECT start_time, end_time FROM freeze_history WHERE guild_id = $1 AND end_time > $2 ORDER BY start_time")
+                .bind(guild_id as i64)
+                .bind(since)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    /// 取得世界當前賽季的佔領紀錄，由新到舊排列
+    pub async fn get_history(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        user_id: Option<u64>,
+        before: Option<DateTime<Utc>>,
+        limit: u32,
+    ) -> SqlResult<Vec<HistoryRecord>> {
+        self.timed("get_history", async move {
+            let rows: Vec<HistoryRecordDB> = sqlx::query_as("SELECT ore_point_id, user_id, target_user_id, team_id, action, time FROM occupy_history WHERE guild_id = $1 AND world_id = $2 AND season_id IS NULL AND ($3::bigint IS NULL OR user_id = $3 OR target_user_id = $3) AND ($4::timestamptz IS NULL OR time < $4) ORDER BY time DESC LIMIT $5")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(user_id.map(|x| x as i64))
+                .bind(before)
+                .bind(limit as i64)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
+    }
+
+    /// 取得計算排行榜的佔領紀錄，由舊到新排列
+    ///
+    /// `since` 有值時只取得結束於該時間之後的賽季，`current_season` 為真時只取得當前賽季
+    pub async fn get_ranking_history(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        since: Option<DateTime<Utc>>,
+        current_season: bool,
+    ) -> SqlResult<Vec<RankingRecord>> {
+        self.timed("get_ranking_history", async move {
+            let rows: Vec<RankingRecordDB> = sqlx::query_as("SELECT occupy_history.season_id, season.end_time AS season_end, occupy_history.ore_point_id, occupy_history.user_id, occupy_history.target_user_id, occupy_history.team_id, occupy_history.action, occupy_history.time FROM occupy_history LEFT JOIN season ON occupy_history.season_id = season.id WHERE occupy_history.guild_id = $1 AND occupy_history.world_id = $2 AND NOT occupy_history.reverted AND (occupy_history.season_id IS NULL OR (NOT $4 AND ($3::timestamptz IS NULL OR season.end_time >= $3))) ORDER BY occupy_history.time, occupy_history.id")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(since)
+                .bind(current_season)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
+    }
+
+    /// 統計玩家在世界當前賽季各動作的次數
+    pub async fn get_action_counts(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        user_id: u64,
+    ) -> SqlResult<Vec<(String, u32)>> {
+        self.timed("get_action_counts", async move {
+            let rows: Vec<(String, i64)> = sqlx::query_as("SELECT action, COUNT(*) FROM occupy_history WHERE guild_id = $1 AND world_id = $2 AND season_id IS NULL AND user_id = $3 AND NOT reverted GROUP BY action ORDER BY action")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(user_id as i64)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(action, count)| (action, count as u32))
+                .collect())
+        })
+        .await
+    }
+
+    pub async fn create_api_token(&self, guild_id: u64, name: &str, token_hash: &str) -> SqlResult {
+        self.timed("create_api_token", async move {
+            sqlx::query("INSERT INTO api_token(guild_id, name, token_hash) VALUES ($1, $2, $3)")
+                .bind(guild_id as i64)
+                .bind(name)
+                .bind(token_hash)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn delete_api_token(&self, guild_id: u64, name: &str) -> SqlResult<bool> {
+        self.timed("delete_api_token", async move {
+            let result = sqlx::query("DELETE FROM api_token WHERE guild_id = $1 AND name = $2")
+                .bind(guild_id as i64)
+                .bind(name)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    pub async fn get_api_tokens(&self, guild_id: u64) -> SqlResult<Vec<(String, DateTime<Utc>)>> {
+        self.timed("get_api_tokens", async move {
+            sqlx::query_as(
+                "SELECT name, created_at FROM api_token WHERE guild_id = $1 ORDER BY created_at",
+            )
+            .bind(guild_id as i64)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    /// 取得 API 金鑰所屬的伺服器
+    pub async fn get_api_token_guild(&self, token_hash: &str) -> SqlResult<Option<u64>> {
+        self.timed("get_api_token_guild", async move {
+            let result: Option<(i64,)> =
+                sqlx::query_as("SELECT guild_id FROM api_token WHERE token_hash = $1")
+                    .bind(token_hash)
+                    .fetch_optional(&self.pool)
+                    .await?;
+            Ok(result.map(|x| x.0 as u64))
+        })
+        .await
+    }
+
+    pub async fn create_webhook(&self, guild_id: u64, url: &str, secret: &str) -> SqlResult<i32> {
+        self.timed("create_webhook", async move {
+            let (id,): (i32,) = sqlx::query_as(
+                "INSERT INTO webhook(guild_id, url, secret) VALUES ($1, $2, $3) RETURNING id",
+            )
+            .bind(guild_id as i64)
+            .bind(url)
+            .bind(secret)
+            .fetch_one(&self.pool)
+            .await?;
+            Ok(id)
+        })
+        .await
+    }
+
+    pub async fn delete_webhook(&self, guild_id: u64, id: i32) -> SqlResult<bool> {
+        self.timed("delete_webhook", async move {
+            let result = sqlx::query("DELETE FROM webhook WHERE guild_id = $1 AND id = $2")
+                .bind(guild_id as i64)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    pub async fn get_webhooks(&self, guild_id: u64) -> SqlResult<Vec<WebhookData>> {
+        self.timed("get_webhooks", async move {
+            sqlx::query_as("SELECT id, url, secret FROM webhook WHERE guild_id = $1 ORDER BY id")
+                .bind(guild_id as i64)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    pub async fn get_teams(&self, guild_id: u64) -> SqlResult<Vec<TeamData>> {
+        self.timed("get_teams", async move {
+            let rows: Vec<TeamDB> = sqlx::query_as(
+                "SELECT id, name, role_id FROM team WHERE guild_id = $1 ORDER BY id",
+            )
+            .bind(guild_id as i64)
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
     }
 
-    pub async fn get_point_data(
+    pub async fn get_guild_config(&self, guild_id: u64) -> SqlResult<GuildConfig> {
+        self.timed("get_guild_config", async move {
+            let config: Option<GuildConfig> =
+                sqlx::query_as("SELECT * FROM guild_config WHERE guild_id = $1")
+                    .bind(guild_id as i64)
+                    .fetch_optional(&self.pool)
+                    .await?;
+            Ok(config.unwrap_or_default())
+        })
+        .await
+    }
+
+    /// 開始凍結佔領計時，已在凍結中時回傳 false
+    pub async fn start_freeze(&self, guild_id: u64, now: DateTime<Utc>) -> SqlResult<bool> {
+        self.timed("start_freeze", async move {
+            let result = sqlx::query("INSERT INTO guild_config(guild_id, frozen_at) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET frozen_at = $2 WHERE guild_config.frozen_at IS NULL")
+                .bind(guild_id as i64)
+                .bind(now)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    /// 解除凍結並將佔領到期、防守保護、挑戰提議、冷卻與休假的時間延後凍結的時間，回傳凍結開始的時間
+    pub async fn end_freeze(
         &self,
         guild_id: u64,
-        start: u32,
-        length: u32,
-    ) -> SqlResult<Vec<ListResult>> {
-        let row: Vec<ListResultDB> = sqlx::query_as("SELECT * FROM ore_point LEFT JOIN occupy_table ON occupy_table.ore_point_id = ore_point.id AND occupy_table.guild_id = $1 ORDER BY ore_point.id OFFSET $2 LIMIT $3")
+        now: DateTime<Utc>,
+    ) -> SqlResult<Option<DateTime<Utc>>> {
+        self.timed("end_freeze", async move {
+            let mut trans = self.pool.begin().await?;
+
+            let frozen_at: Option<(Option<DateTime<Utc>>,)> = sqlx::query_as(
+                "SELECT frozen_at FROM guild_config WHERE guild_id = $1 FOR UPDATE",
+            )
             .bind(guild_id as i64)
-            .bind(start as i64)
-            .bind(length as i64)
-            .fetch_all(&self.pool)
+            .fetch_optional(&mut *trans)
             .await?;
-        Ok(row.into_iter().map(|x| x.into()).collect())
+            let Some(frozen_at) = frozen_at.and_then(|(frozen_at,)| frozen_at) else {
+                return Ok(None);
+            };
+
+            sqlx::query("UPDATE occupy_table SET due_time = due_time + ($2 - $3) WHERE guild_id = $1")
+                .bind(guild_id as i64)
+                .bind(now)
+                .bind(frozen_at)
+                .execute(&mut *trans)
+                .await?;
+            sqlx::query("UPDATE occupy_table SET shield_until = shield_until + ($2 - $3) WHERE guild_id = $1 AND shield_until > $3")
+                .bind(guild_id as i64)
+                .bind(now)
+                .bind(frozen_at)
+                .execute(&mut *trans)
+                .await?;
+            // 約定的對戰時間一併延後，凍結期間已發送的提醒需要重新發送
+            sqlx::query("UPDATE occupy_table SET battle_time = battle_time + ($2 - $3), battle_reminded = FALSE WHERE guild_id = $1 AND battle_time > $3")
+                .bind(guild_id as i64)
+                .bind(now)
+                .bind(frozen_at)
+                .execute(&mut *trans)
+                .await?;
+            sqlx::query("UPDATE battle_proposal SET created_at = created_at + ($2 - $3) WHERE guild_id = $1")
+                .bind(guild_id as i64)
+                .bind(now)
+                .bind(frozen_at)
+                .execute(&mut *trans)
+                .await?;
+            sqlx::query("UPDATE vacation SET end_time = end_time + ($2 - $3) WHERE guild_id = $1 AND end_time > $3")
+                .bind(guild_id as i64)
+                .bind(now)
+                .bind(frozen_at)
+                .execute(&mut *trans)
+                .await?;
+            // 冷卻時間由挑戰紀錄推算，記錄凍結期間以延後冷卻
+            sqlx::query("INSERT INTO freeze_history(guild_id, start_time, end_time) VALUES ($1, $2, $3)")
+                .bind(guild_id as i64)
+                .bind(frozen_at)
+                .bind(now)
+                .execute(&mut *trans)
+                .await?;
+            sqlx::query("UPDATE guild_config SET frozen_at = NULL WHERE guild_id = $1")
+                .bind(guild_id as i64)
+                .execute(&mut *trans)
+                .await?;
+
+            trans.commit().await?;
+            Ok(Some(frozen_at))
+        })
+        .await
     }
 
-    pub async fn get_point_count(&self) -> SqlResult<u32> {
-        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM ore_point")
-            .fetch_one(&self.pool)
-            .await?;
-        Ok(count as u32)
+    pub async fn set_shield_hours(&self, guild_id: u64, hours: Option<i32>) -> SqlResult {
+        self.timed("set_shield_hours", async move {
+            sqlx::query("INSERT INTO guild_config(guild_id, shield_hours) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET shield_hours = $2")
+                .bind(guild_id as i64)
+                .bind(hours)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn set_challenge_cooldown(
+        &self,
+        guild_id: u64,
+        point_hours: Option<i32>,
+        player_hours: Option<i32>,
+    ) -> SqlResult {
+        self.timed("set_challenge_cooldown", async move {
+            sqlx::query("INSERT INTO guild_config(guild_id, point_cooldown_hours, player_cooldown_hours) VALUES ($1, $2, $3) ON CONFLICT (guild_id) DO UPDATE SET point_cooldown_hours = $2, player_cooldown_hours = $3")
+                .bind(guild_id as i64)
+                .bind(point_hours)
+                .bind(player_hours)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn set_vacation_max_days(&self, guild_id: u64, days: Option<i32>) -> SqlResult {
+        self.timed("set_vacation_max_days", async move {
+            sqlx::query("INSERT INTO guild_config(guild_id, vacation_max_days) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET vacation_max_days = $2")
+                .bind(guild_id as i64)
+                .bind(days)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn set_proposal_timeout(&self, guild_id: u64, hours: i32) -> SqlResult {
+        self.timed("set_proposal_timeout", async move {
+            sqlx::query("INSERT INTO guild_config(guild_id, proposal_timeout_hours) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET proposal_timeout_hours = $2")
+                .bind(guild_id as i64)
+                .bind(hours)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn set_notify_channel(&self, guild_id: u64, channel_id: Option<u64>) -> SqlResult {
+        self.timed("set_notify_channel", async move {
+            sqlx::query("INSERT INTO guild_config(guild_id, notify_channel_id) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET notify_channel_id = $2")
+                .bind(guild_id as i64)
+                .bind(channel_id.map(|x| x as i64))
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn set_guild_locale(&self, guild_id: u64, locale: &str) -> SqlResult {
+        self.timed("set_guild_locale", async move {
+            sqlx::query("INSERT INTO guild_config(guild_id, locale) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET locale = $2")
+                .bind(guild_id as i64)
+                .bind(locale)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn set_notify_events(&self, guild_id: u64, events: i32) -> SqlResult {
+        self.timed("set_notify_events", async move {
+            sqlx::query("INSERT INTO guild_config(guild_id, notify_events) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET notify_events = $2")
+                .bind(guild_id as i64)
+                .bind(events)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 取得並標記佔領期限已到且尚無挑戰者的礦點
+    pub async fn take_challengeable(&self, now: DateTime<Utc>) -> SqlResult<Vec<Challengeable>> {
+        self.timed("take_challengeable", async move {
+            // 只處理一天內到期的礦點，避免停機後補發過多通知
+            // 凍結中的伺服器在解除凍結後才會到期
+            let rows: Vec<ChallengeableDB> = sqlx::query_as("UPDATE occupy_table SET due_notified_at = due_time WHERE due_time <= $1 AND due_time > $1 - INTERVAL '1 day' AND battle_user_id IS NULL AND (due_notified_at IS NULL OR due_notified_at < due_time) AND (shield_until IS NULL OR shield_until <= $1) AND NOT EXISTS (SELECT 1 FROM vacation WHERE vacation.guild_id = occupy_table.guild_id AND vacation.world_id = occupy_table.world_id AND vacation.user_id = occupy_table.user_id AND vacation.delegate_user_id IS NULL AND vacation.end_time > $1) AND guild_id NOT IN (SELECT guild_id FROM guild_config WHERE frozen_at IS NOT NULL) RETURNING guild_id, world_id, ore_point_id, user_id")
+                .bind(now)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
+    }
+
+    pub async fn set_challenge_channel(&self, guild_id: u64, channel_id: Option<u64>) -> SqlResult {
+        self.timed("set_challenge_channel", async move {
+            sqlx::query("INSERT INTO guild_config(guild_id, challenge_channel_id) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET challenge_channel_id = $2")
+                .bind(guild_id as i64)
+                .bind(channel_id.map(|x| x as i64))
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn set_battle_thread(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        ore_point_id: i32,
+        thread_id: u64,
+    ) -> SqlResult {
+        self.timed("set_battle_thread", async move {
+            sqlx::query("UPDATE occupy_table SET battle_thread_id = $1 WHERE guild_id = $2 AND world_id = $3 AND ore_point_id = $4")
+                .bind(thread_id as i64)
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(ore_point_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn set_queue_counts_limit(&self, guild_id: u64, enabled: bool) -> SqlResult {
+        self.timed("set_queue_counts_limit", async move {
+            sqlx::query("INSERT INTO guild_config(guild_id, queue_counts_limit) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET queue_counts_limit = $2")
+                .bind(guild_id as i64)
+                .bind(enabled)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_game_server(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+    ) -> SqlResult<Option<GameServerConfig>> {
+        self.timed("get_game_server", async move {
+            sqlx::query_as("SELECT rest_url, rest_password, rcon_address, rcon_password, require_online FROM game_server WHERE guild_id = $1 AND world_id = $2")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    pub async fn set_rest_server(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        url: &str,
+        password: &str,
+        require_online: bool,
+    ) -> SqlResult {
+        self.timed("set_rest_server", async move {
+            sqlx::query("INSERT INTO game_server(guild_id, world_id, rest_url, rest_password, require_online) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id, world_id) DO UPDATE SET rest_url = $3, rest_password = $4, require_online = $5")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(url)
+                .bind(password)
+                .bind(require_online)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn set_rcon_server(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        address: &str,
+        password: &str,
+        require_online: bool,
+    ) -> SqlResult {
+        self.timed("set_rcon_server", async move {
+            sqlx::query("INSERT INTO game_server(guild_id, world_id, rcon_address, rcon_password, require_online) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id, world_id) DO UPDATE SET rcon_address = $3, rcon_password = $4, require_online = $5")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(address)
+                .bind(password)
+                .bind(require_online)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn delete_game_server(&self, guild_id: u64, world_id: i32) -> SqlResult<bool> {
+        self.timed("delete_game_server", async move {
+            let result =
+                sqlx::query("DELETE FROM game_server WHERE guild_id = $1 AND world_id = $2")
+                    .bind(guild_id as i64)
+                    .bind(world_id)
+                    .execute(&self.pool)
+                    .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    /// 綁定遊戲角色，重新綁定時需要重新驗證
+    pub async fn link_player(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        user_id: u64,
+        link: PlayerLink,
+    ) -> SqlResult {
+        self.timed("link_player", async move {
+            sqlx::query("INSERT INTO player_link(guild_id, world_id, user_id, character_name, player_uid, steam_id, verified) VALUES ($1, $2, $3, $4, $5, $6, FALSE) ON CONFLICT (guild_id, world_id, user_id) DO UPDATE SET character_name = $4, player_uid = $5, steam_id = $6, verified = FALSE")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(user_id as i64)
+                .bind(link.character_name)
+                .bind(link.player_uid)
+                .bind(link.steam_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
     }
 
-    pub async fn set_guild_notify_role(&self, guild_id: u64, role_id: u64) -> SqlResult {
-        sqlx::query("INSERT INTO battle_notify_role(guild_id, role_id) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET role_id = $2")
+    pub async fn unlink_player(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        user_id: u64,
+    ) -> SqlResult<bool> {
+        self.timed("unlink_player", async move {
+            let result = sqlx::query(
+                "DELETE FROM player_link WHERE guild_id = $1 AND world_id = $2 AND user_id = $3",
+            )
             .bind(guild_id as i64)
-            .bind(role_id as i64)
+            .bind(world_id)
+            .bind(user_id as i64)
             .execute(&self.pool)
             .await?;
-        Ok(())
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    pub async fn set_player_verified(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        user_id: u64,
+        verified: bool,
+    ) -> SqlResult<bool> {
+        self.timed("set_player_verified", async move {
+            let result = sqlx::query("UPDATE player_link SET verified = $4 WHERE guild_id = $1 AND world_id = $2 AND user_id = $3")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(user_id as i64)
+                .bind(verified)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    pub async fn get_player_links(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+    ) -> SqlResult<Vec<PlayerLink>> {
+        self.timed("get_player_links", async move {
+            let rows: Vec<PlayerLinkDB> = sqlx::query_as("SELECT user_id, character_name, player_uid, steam_id, verified FROM player_link WHERE guild_id = $1 AND world_id = $2 ORDER BY character_name")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
     }
 
-    pub async fn get_guild_notify_role(&self, guild_id: u64) -> SqlResult<Option<u64>> {
-        let result: Option<(i64,)> =
-            sqlx::query_as("SELECT role_id FROM battle_notify_role WHERE guild_id = $1")
+    /// 以角色名稱找到綁定的玩家，`include_unverified` 為 false 時只找已驗證的角色
+    pub async fn find_linked_player(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        character_name: &str,
+        include_unverified: bool,
+    ) -> SqlResult<Option<u64>> {
+        self.timed("find_linked_player", async move {
+            let result: Option<(i64,)> = sqlx::query_as("SELECT user_id FROM player_link WHERE guild_id = $1 AND world_id = $2 AND character_name = $3 AND (verified OR $4)")
                 .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(character_name)
+                .bind(include_unverified)
                 .fetch_optional(&self.pool)
                 .await?;
-        Ok(result.map(|x| x.0 as u64))
+            Ok(result.map(|x| x.0 as u64))
+        })
+        .await
     }
 
-    pub async fn write_log(
+    pub async fn get_player_link(
         &self,
-        guild_id: Option<u64>,
-        channel_id: u64,
+        guild_id: u64,
+        world_id: i32,
+        user_id: u64,
+    ) -> SqlResult<Option<PlayerLink>> {
+        self.timed("get_player_link", async move {
+            let row: Option<PlayerLinkDB> = sqlx::query_as("SELECT user_id, character_name, player_uid, steam_id, verified FROM player_link WHERE guild_id = $1 AND world_id = $2 AND user_id = $3")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(user_id as i64)
+                .fetch_optional(&self.pool)
+                .await?;
+            Ok(row.map(|x| x.into()))
+        })
+        .await
+    }
+
+    pub async fn set_vacation(&self, data: VacationData) -> SqlResult {
+        self.timed("set_vacation", async move {
+            sqlx::query("INSERT INTO vacation(guild_id, world_id, user_id, end_time, delegate_user_id) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id, world_id, user_id) DO UPDATE SET end_time = $4, delegate_user_id = $5")
+                .bind(data.guild_id as i64)
+                .bind(data.world_id)
+                .bind(data.user_id as i64)
+                .bind(data.end_time)
+                .bind(data.delegate_user_id.map(|x| x as i64))
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 結束休假，沒有進行中的休假時回傳 false
+    pub async fn end_vacation(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        user_id: u64,
+        now: DateTime<Utc>,
+    ) -> SqlResult<bool> {
+        self.timed("end_vacation", async move {
+            let result = sqlx::query("DELETE FROM vacation WHERE guild_id = $1 AND world_id = $2 AND user_id = $3 AND end_time > $4")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(user_id as i64)
+                .bind(now)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    /// 取得玩家進行中的休假
+    pub async fn get_vacation(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        user_id: u64,
+        now: DateTime<Utc>,
+    ) -> SqlResult<Option<VacationData>> {
+        self.timed("get_vacation", async move {
+            let row: Option<VacationDB> = sqlx::query_as("SELECT * FROM vacation WHERE guild_id = $1 AND world_id = $2 AND user_id = $3 AND end_time > $4")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(user_id as i64)
+                .bind(now)
+                .fetch_optional(&self.pool)
+                .await?;
+            Ok(row.map(|x| x.into()))
+        })
+        .await
+    }
+
+    /// 將礦點交給其他玩家，保留挑戰狀態，礦點已不屬於原佔領者時回傳 false
+    pub async fn transfer_occupy(
+        &self,
+        data: &OccupyData,
+        to_user_id: u64,
+        team_id: Option<i32>,
+        due_time: DateTime<Utc>,
+    ) -> SqlResult<bool> {
+        self.timed("transfer_occupy", async move {
+            let result = sqlx::query("UPDATE occupy_table SET user_id = $5, team_id = $6, due_time = $7 WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3 AND user_id = $4")
+                .bind(data.guild_id as i64)
+                .bind(data.world_id)
+                .bind(data.ore_point_id)
+                .bind(data.user_id as i64)
+                .bind(to_user_id as i64)
+                .bind(team_id)
+                .bind(due_time)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    /// 加入挑戰隊列，回傳是否成功加入
+    pub async fn join_queue(&self, data: QueueData) -> SqlResult<bool> {
+        self.timed("join_queue", async move {
+            let result = sqlx::query("INSERT INTO challenge_queue(guild_id, world_id, ore_point_id, user_id, team_id) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id, world_id, ore_point_id, user_id) DO NOTHING")
+                .bind(data.guild_id as i64)
+                .bind(data.world_id)
+                .bind(data.ore_point_id)
+                .bind(data.user_id as i64)
+                .bind(data.team_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    /// 離開挑戰隊列，回傳是否在隊列中
+    pub async fn leave_queue(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        ore_point_id: i32,
         user_id: u64,
-        content: &str,
+    ) -> SqlResult<bool> {
+        self.timed("leave_queue", async move {
+            let result = sqlx::query("DELETE FROM challenge_queue WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3 AND user_id = $4")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(ore_point_id)
+                .bind(user_id as i64)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    /// 取得已到期、未在挑戰中、不在防守或休假保護期且有玩家排隊的礦點
+    pub async fn get_promotable(&self, now: DateTime<Utc>) -> SqlResult<Vec<(u64, i32, i32)>> {
+        self.timed("get_promotable", async move {
+            let rows: Vec<(i64, i32, i32)> = sqlx::query_as("SELECT guild_id, world_id, ore_point_id FROM occupy_table WHERE due_time <= $1 AND battle_user_id IS NULL AND (shield_until IS NULL OR shield_until <= $1) AND EXISTS (SELECT 1 FROM challenge_queue WHERE challenge_queue.guild_id = occupy_table.guild_id AND challenge_queue.world_id = occupy_table.world_id AND challenge_queue.ore_point_id = occupy_table.ore_point_id) AND NOT EXISTS (SELECT 1 FROM vacation WHERE vacation.guild_id = occupy_table.guild_id AND vacation.world_id = occupy_table.world_id AND vacation.user_id = occupy_table.user_id AND vacation.delegate_user_id IS NULL AND vacation.end_time > $1) AND guild_id NOT IN (SELECT guild_id FROM guild_config WHERE frozen_at IS NOT NULL)")
+                .bind(now)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(guild_id, world_id, ore_point_id)| (guild_id as u64, world_id, ore_point_id))
+                .collect())
+        })
+        .await
+    }
+
+    /// 取得依排隊順序排列的挑戰者 (玩家, 隊伍)
+    pub async fn get_queue(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        ore_point_id: i32,
+    ) -> SqlResult<Vec<(u64, Option<i32>)>> {
+        self.timed("get_queue", async move {
+            let rows: Vec<(i64, Option<i32>)> = sqlx::query_as("SELECT user_id, team_id FROM challenge_queue WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3 ORDER BY queued_at, id")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(ore_point_id)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(user_id, team_id)| (user_id as u64, team_id))
+                .collect())
+        })
+        .await
+    }
+
+    pub async fn create_battle_proposal(&self, data: BattleProposal) -> SqlResult {
+        self.timed("create_battle_proposal", async move {
+            sqlx::query("INSERT INTO battle_proposal(guild_id, world_id, ore_point_id, channel_id, slots) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id, world_id, ore_point_id) DO UPDATE SET channel_id = $4, slots = $5, created_at = now()")
+                .bind(data.guild_id as i64)
+                .bind(data.world_id)
+                .bind(data.ore_point_id)
+                .bind(data.channel_id as i64)
+                .bind(data.slots)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_battle_proposal(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        ore_point_id: i32,
+    ) -> SqlResult<Option<BattleProposal>> {
+        self.timed("get_battle_proposal", async move {
+            let row: Option<BattleProposalDB> = sqlx::query_as("SELECT guild_id, world_id, ore_point_id, channel_id, slots FROM battle_proposal WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(ore_point_id)
+                .fetch_optional(&self.pool)
+                .await?;
+            Ok(row.map(|x| x.into()))
+        })
+        .await
+    }
+
+    pub async fn delete_battle_proposal(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        ore_point_id: i32,
+    ) -> SqlResult {
+        self.timed("delete_battle_proposal", async move {
+            sqlx::query("DELETE FROM battle_proposal WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(ore_point_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 取得超過回應時限的約戰
+    pub async fn get_expired_battle_proposals(
+        &self,
+        now: DateTime<Utc>,
+    ) -> SqlResult<Vec<BattleProposal>> {
+        self.timed("get_expired_battle_proposals", async move {
+            let rows: Vec<BattleProposalDB> = sqlx::query_as(
+                r#"SELECT battle_proposal.guild_id, battle_proposal.world_id, battle_proposal.ore_point_id, battle_proposal.channel_id, battle_proposal.slots FROM battle_proposal
+                    LEFT JOIN guild_config ON guild_config.guild_id = battle_proposal.guild_id
+                    WHERE battle_proposal.created_at + make_interval(hours => COALESCE(guild_config.proposal_timeout_hours, 24)) <= $1 AND guild_config.frozen_at IS NULL
+                "#,
+            )
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
+    }
+
+    pub async fn set_battle_time(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        ore_point_id: i32,
+        battle_time: DateTime<Utc>,
+        channel_id: u64,
+    ) -> SqlResult {
+        self.timed("set_battle_time", async move {
+            sqlx::query("UPDATE occupy_table SET battle_time = $1, battle_channel_id = $2, battle_reminded = FALSE WHERE guild_id = $3 AND world_id = $4 AND ore_point_id = $5")
+                .bind(battle_time)
+                .bind(channel_id as i64)
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(ore_point_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 取得即將開始且尚未通知的挑戰
+    pub async fn get_upcoming_battles(
+        &self,
+        before: DateTime<Utc>,
+    ) -> SqlResult<Vec<BattleReminder>> {
+        self.timed("get_upcoming_battles", async move {
+            let rows: Vec<BattleReminderDB> = sqlx::query_as("SELECT guild_id, world_id, ore_point_id, COALESCE(battle_thread_id, battle_channel_id) AS battle_channel_id, user_id, battle_user_id, battle_time FROM occupy_table WHERE battle_time <= $1 AND battle_user_id IS NOT NULL AND COALESCE(battle_thread_id, battle_channel_id) IS NOT NULL AND NOT battle_reminded")
+                .bind(before)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows.into_iter().map(|x| x.into()).collect())
+        })
+        .await
+    }
+
+    pub async fn set_battle_reminded(
+        &self,
+        guild_id: u64,
+        world_id: i32,
+        ore_point_id: i32,
     ) -> SqlResult {
-        sqlx::query("INSERT INTO command_log(guild_id, channel_id, user_id, content) VALUES ($1, $2, $3, $4)")
-            .bind(guild_id.map(|x| x as i64))
+        self.timed("set_battle_reminded", async move {
+            sqlx::query("UPDATE occupy_table SET battle_reminded = TRUE WHERE guild_id = $1 AND world_id = $2 AND ore_point_id = $3")
+                .bind(guild_id as i64)
+                .bind(world_id)
+                .bind(ore_point_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn set_bot_role(&self, guild_id: u64, role_id: u64, level: Option<i32>) -> SqlResult {
+        self.timed("set_bot_role", async move {
+            match level {
+                Some(level) => sqlx::query("INSERT INTO bot_role(guild_id, role_id, level) VALUES ($1, $2, $3) ON CONFLICT (guild_id, role_id) DO UPDATE SET level = $3")
+                    .bind(guild_id as i64)
+                    .bind(role_id as i64)
+                    .bind(level),
+                None => sqlx::query("DELETE FROM bot_role WHERE guild_id = $1 AND role_id = $2")
+                    .bind(guild_id as i64)
+                    .bind(role_id as i64),
+            }
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// 取得伺服器設定的身分組與權限等級
+    pub async fn get_bot_roles(&self, guild_id: u64) -> SqlResult<Vec<(u64, i32)>> {
+        self.timed("get_bot_roles", async move {
+            let rows: Vec<(i64, i32)> = sqlx::query_as(
+                "SELECT role_id, level FROM bot_role WHERE guild_id = $1 ORDER BY level DESC",
+            )
+            .bind(guild_id as i64)
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(role_id, level)| (role_id as u64, level))
+                .collect())
+        })
+        .await
+    }
+
+    pub async fn set_member_channel(
+        &self,
+        guild_id: u64,
+        channel_id: u64,
+        enabled: bool,
+    ) -> SqlResult<bool> {
+        self.timed("set_member_channel", async move {
+            let result = if enabled {
+                sqlx::query("INSERT INTO member_channel(guild_id, channel_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            } else {
+                sqlx::query("DELETE FROM member_channel WHERE guild_id = $1 AND channel_id = $2")
+            }
+            .bind(guild_id as i64)
             .bind(channel_id as i64)
-            .bind(user_id as i64)
-            .bind(content)
             .execute(&self.pool)
             .await?;
-        Ok(())
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    /// 取得成員可使用指令的頻道，沒有設定時不限制
+    pub async fn get_member_channels(&self, guild_id: u64) -> SqlResult<Vec<u64>> {
+        self.timed("get_member_channels", async move {
+            let rows: Vec<(i64,)> =
+                sqlx::query_as("SELECT channel_id FROM member_channel WHERE guild_id = $1")
+                    .bind(guild_id as i64)
+                    .fetch_all(&self.pool)
+                    .await?;
+            Ok(rows.into_iter().map(|x| x.0 as u64).collect())
+        })
+        .await
     }
 
     pub async fn begin_transaction(&self) -> SqlResult<Transaction<'_, Postgres>> {
-        self.pool.begin().await
+        self.timed("begin_transaction", async move { self.pool.begin().await })
+            .await
     }
 }
 
+/// 新增管理操作紀錄，可在交易中使用
+async fn insert_admin_action(executor: impl PgExecutor<'_>, data: AdminActionData) -> SqlResult {
+    let previous = data.previous.map(OccupyDB::from);
+    let previous = previous.as_ref();
+    let current = data.current.map(OccupyDB::from);
+    let current = current.as_ref();
+    sqlx::query("INSERT INTO admin_action(guild_id, world_id, ore_point_id, user_id, action, previous_user_id, previous_due_time, previous_team_id, previous_battle_user_id, previous_battle_team_id, previous_battle_time, previous_battle_thread_id, previous_shield_until, current_user_id, current_due_time, current_battle_user_id) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)")
+        .bind(data.guild_id as i64)
+        .bind(data.world_id)
+        .bind(data.ore_point_id)
+        .bind(data.user_id as i64)
+        .bind(data.action.as_str())
+        .bind(previous.map(|x| x.user_id))
+        .bind(previous.map(|x| x.due_time))
+        .bind(previous.and_then(|x| x.team_id))
+        .bind(previous.and_then(|x| x.battle_user_id))
+        .bind(previous.and_then(|x| x.battle_team_id))
+        .bind(previous.and_then(|x| x.battle_time))
+        .bind(previous.and_then(|x| x.battle_thread_id))
+        .bind(previous.and_then(|x| x.shield_until))
+        .bind(current.map(|x| x.user_id))
+        .bind(current.map(|x| x.due_time))
+        .bind(current.and_then(|x| x.battle_user_id))
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
 #[derive(FromRow)]
 struct ListResultDB {
     id: i32,
@@ -211,6 +2838,12 @@ struct ListResultDB {
     user_id: Option<i64>,
     due_time: Option<DateTime<Utc>>,
     battle_user_id: Option<i64>,
+    battle_time: Option<DateTime<Utc>>,
+    shield_until: Option<DateTime<Utc>>,
+    leave_until: Option<DateTime<Utc>>,
+    team_name: Option<String>,
+    battle_team_name: Option<String>,
+    queue_count: i64,
 }
 
 impl From<ListResultDB> for ListResult {
@@ -224,6 +2857,12 @@ impl From<ListResultDB> for ListResult {
             user_id: value.user_id.map(|x| x as u64),
             due_time: value.due_time,
             battle_user_id: value.battle_user_id.map(|x| x as u64),
+            battle_time: value.battle_time,
+            shield_until: value.shield_until,
+            leave_until: value.leave_until,
+            team_name: value.team_name,
+            battle_team_name: value.battle_team_name,
+            queue_count: value.queue_count as u32,
         }
     }
 }
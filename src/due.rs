@@ -0,0 +1,262 @@
+use crate::{
+    commands::Context,
+    confirm,
+    db::OccupyData,
+    i18n::{self, tr},
+    notify::autocomplete_ore_type,
+    permission::require_admin,
+    structs::{OrePoint, OreType},
+    time::parse_time,
+    world::{autocomplete_world, resolve_world},
+};
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Duration, Utc};
+
+/// 調整礦點佔領的到期時間
+#[poise::command(
+    slash_command,
+    rename = "到期時間",
+    check = "require_admin",
+    subcommands("set_due_time", "shift_due_time"),
+    subcommand_required
+)]
+pub async fn due_time(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 取得調整範圍內的佔領資料，未指定礦點與礦種時為整個世界，`all_worlds` 時為伺服器內所有世界，找不到世界時回傳 None
+async fn targets(
+    ctx: Context<'_>,
+    world: Option<String>,
+    all_worlds: bool,
+    point_id: Option<i32>,
+    ore_type: Option<String>,
+) -> Result<Option<Vec<OccupyData>>> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+
+    let ore_type = match ore_type {
+        Some(name) => Some(
+            OreType::iter()
+                .find(|ore_type| ore_type.name == name)
+                .context("找不到礦種")?
+                .id,
+        ),
+        None => None,
+    };
+    let occupations = if all_worlds {
+        ctx.data().get_guild_occupations(guild_id).await?
+    } else {
+        let Some(world_id) = resolve_world(ctx, world).await? else {
+            return Ok(None);
+        };
+        ctx.data().get_occupations(guild_id, world_id).await?
+    };
+    Ok(Some(in_scope(
+        occupations,
+        OrePoint::iter(),
+        point_id,
+        ore_type,
+    )))
+}
+
+/// 篩選出指定礦點與礦種範圍內的佔領
+fn in_scope(
+    occupations: Vec<OccupyData>,
+    points: impl Iterator<Item = OrePoint>,
+    point_id: Option<i32>,
+    ore_type: Option<i32>,
+) -> Vec<OccupyData> {
+    let points: Vec<OrePoint> = points
+        .filter(|point| point_id.is_none_or(|point_id| point.id == point_id))
+        // 礦種為位元遮罩，混合礦點符合其中任一礦種
+        .filter(|point| ore_type.is_none_or(|ore_type| point.ore_type & ore_type != 0))
+        .collect();
+
+    occupations
+        .into_iter()
+        .filter(|data| points.iter().any(|point| point.id == data.ore_point_id))
+        .collect()
+}
+
+/// 更新到期時間，並記錄佔領紀錄與管理操作，回傳實際調整的礦點數，取消則回傳 None
+async fn adjust(
+    ctx: Context<'_>,
+    targets: Vec<OccupyData>,
+    due_time: impl Fn(DateTime<Utc>) -> DateTime<Utc>,
+) -> Result<Option<usize>> {
+    if targets.len() > 1 {
+        let locale = i18n::locale(ctx).await;
+        let embed = confirm::embed(
+            tr!(locale, "confirm.adjust_due"),
+            tr!(
+                locale,
+                "confirm.adjust_due.description",
+                count = targets.len()
+            ),
+        );
+        if !confirm::confirm(ctx, embed).await? {
+            return Ok(None);
+        }
+    }
+
+    let adjustments = targets
+        .into_iter()
+        .map(|data| {
+            let due_time = due_time(data.due_time);
+            (data, due_time)
+        })
+        .collect();
+    let count = ctx
+        .data()
+        .adjust_due_times(ctx.author().id.get(), adjustments)
+        .await?;
+    Ok(Some(count))
+}
+
+/// 將佔領的到期時間設為指定的時間
+#[poise::command(slash_command, rename = "設定", ephemeral)]
+async fn set_due_time(
+    ctx: Context<'_>,
+    #[rename = "時間"]
+    #[description = "新的到期時間 (YYYY-MM-DD HH:MM)"]
+    time: String,
+    #[rename = "礦點"]
+    #[description = "調整的礦點編號，留空則調整所有礦點"]
+    point_id: Option<i32>,
+    #[rename = "礦種"]
+    #[description = "只調整此礦種的礦點"]
+    #[autocomplete = "autocomplete_ore_type"]
+    ore_type: Option<String>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+    #[rename = "所有世界"]
+    #[description = "調整伺服器內所有世界的礦點"]
+    all_worlds: Option<bool>,
+) -> Result<()> {
+    let locale = i18n::locale(ctx).await;
+    let Some(time) = parse_time(&time) else {
+        ctx.reply(tr!(locale, "time.invalid")).await?;
+        return Ok(());
+    };
+
+    let Some(targets) =
+        targets(ctx, world, all_worlds.unwrap_or(false), point_id, ore_type).await?
+    else {
+        return Ok(());
+    };
+    if targets.is_empty() {
+        ctx.reply(tr!(locale, "due.no_occupation")).await?;
+        return Ok(());
+    }
+    let Some(count) = adjust(ctx, targets, |_| time).await? else {
+        return Ok(());
+    };
+
+    ctx.reply(tr!(
+        locale,
+        "due.set",
+        count = count,
+        time = time.timestamp()
+    ))
+    .await?;
+    Ok(())
+}
+
+/// 延長或縮短佔領的到期時間
+#[poise::command(slash_command, rename = "延長", ephemeral)]
+async fn shift_due_time(
+    ctx: Context<'_>,
+    #[min = -720]
+    #[max = 720]
+    #[rename = "小時"]
+    #[description = "延長的小時數，負數則縮短"]
+    hours: i32,
+    #[rename = "礦點"]
+    #[description = "調整的礦點編號，留空則調整所有礦點"]
+    point_id: Option<i32>,
+    #[rename = "礦種"]
+    #[description = "只調整此礦種的礦點"]
+    #[autocomplete = "autocomplete_ore_type"]
+    ore_type: Option<String>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+    #[rename = "所有世界"]
+    #[description = "調整伺服器內所有世界的礦點"]
+    all_worlds: Option<bool>,
+) -> Result<()> {
+    let locale = i18n::locale(ctx).await;
+
+    let Some(targets) =
+        targets(ctx, world, all_worlds.unwrap_or(false), point_id, ore_type).await?
+    else {
+        return Ok(());
+    };
+    if targets.is_empty() {
+        ctx.reply(tr!(locale, "due.no_occupation")).await?;
+        return Ok(());
+    }
+    let shift = Duration::hours(hours.into());
+    let Some(count) = adjust(ctx, targets, |due_time| due_time + shift).await? else {
+        return Ok(());
+    };
+
+    ctx.reply(if hours >= 0 {
+        tr!(locale, "due.extended", count = count, hours = hours)
+    } else {
+        tr!(locale, "due.shortened", count = count, hours = -hours)
+    })
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn point(id: i32, ore_type: i32) -> OrePoint {
+        OrePoint {
+            id,
+            ore_type,
+            x: 0,
+            y: 0,
+            name: String::new(),
+            names: Default::default(),
+        }
+    }
+
+    fn scoped(point_id: Option<i32>, ore_type: Option<i32>) -> Vec<i32> {
+        let points = [point(1, 1), point(2, 1), point(3, 2), point(4, 3)];
+        let due_time = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let occupations = [1, 3, 4]
+            .into_iter()
+            .map(|ore_point_id| OccupyData {
+                ore_point_id,
+                ..OccupyData::fixture(due_time)
+            })
+            .collect();
+        in_scope(occupations, points.into_iter(), point_id, ore_type)
+            .into_iter()
+            .map(|data| data.ore_point_id)
+            .collect()
+    }
+
+    #[test]
+    fn whole_world_without_filters() {
+        assert_eq!(scoped(None, None), [1, 3, 4]);
+    }
+
+    #[test]
+    fn filters_by_point_and_ore_type() {
+        assert_eq!(scoped(Some(3), None), [3]);
+        assert_eq!(scoped(None, Some(1)), [1, 4]);
+        assert_eq!(scoped(None, Some(2)), [3, 4]);
+        // 未被佔領的礦點不會被調整
+        assert!(scoped(Some(2), None).is_empty());
+        assert!(scoped(Some(3), Some(1)).is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// 事件佇列的容量，訂閱者落後超過此數量時會遺失事件
+const CAPACITY: usize = 256;
+
+static EVENT_BUS: OnceCell<broadcast::Sender<OccupyEvent>> = OnceCell::new();
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Occupied,
+    Challenged,
+    Resolved,
+    Released,
+    Forced,
+    Expired,
+    Transferred,
+    Reverted,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Occupied => "occupied",
+            EventKind::Challenged => "challenged",
+            EventKind::Resolved => "resolved",
+            EventKind::Released => "released",
+            EventKind::Forced => "forced",
+            EventKind::Expired => "expired",
+            EventKind::Transferred => "transferred",
+            EventKind::Reverted => "reverted",
+        }
+    }
+}
+
+/// 礦點佔領狀態變更的事件
+#[derive(Clone, Debug, Serialize)]
+pub struct OccupyEvent {
+    pub event: EventKind,
+    pub guild_id: u64,
+    pub world_id: i32,
+    pub ore_point_id: i32,
+    /// 佔領者、挑戰者或勝者
+    pub user_id: u64,
+    /// 被挑戰者或敗者
+    pub target_user_id: Option<u64>,
+    pub time: DateTime<Utc>,
+}
+
+fn sender() -> &'static broadcast::Sender<OccupyEvent> {
+    EVENT_BUS.get_or_init(|| broadcast::channel(CAPACITY).0)
+}
+
+pub fn subscribe() -> broadcast::Receiver<OccupyEvent> {
+    sender().subscribe()
+}
+
+/// 發送事件給所有訂閱者
+pub fn emit(
+    event: EventKind,
+    guild_id: u64,
+    world_id: i32,
+    ore_point_id: i32,
+    user_id: u64,
+    target_user_id: Option<u64>,
+) {
+    // 沒有訂閱者時忽略
+    let _ = sender().send(OccupyEvent {
+        event,
+        guild_id,
+        world_id,
+        ore_point_id,
+        user_id,
+        target_user_id,
+        time: Utc::now(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn emit_reaches_subscribers() {
+        let mut receiver = subscribe();
+        emit(EventKind::Challenged, 1, 2, 3, 4, Some(5));
+
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.event.as_str(), "challenged");
+        assert_eq!(
+            (event.guild_id, event.world_id, event.ore_point_id),
+            (1, 2, 3)
+        );
+        assert_eq!((event.user_id, event.target_user_id), (4, Some(5)));
+
+        let payload = serde_json::to_value(&event).unwrap();
+        assert_eq!(payload["event"], "challenged");
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{
+    commands::Context,
+    i18n::{self, tr},
+    permission::require_admin,
+};
+use anyhow::{Context as _, Result};
+use chrono::Utc;
+use poise::CreateReply;
+
+/// 凍結期間無法使用的指令，於凍結中時回覆提示
+pub async fn not_frozen(ctx: Context<'_>) -> Result<bool> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(frozen_at) = ctx.data().get_guild_config(guild_id).await?.frozen_at else {
+        return Ok(true);
+    };
+
+    let locale = i18n::locale(ctx).await;
+    ctx.send(
+        CreateReply::default()
+            .reply(true)
+            .ephemeral(true)
+            .content(tr!(locale, "freeze.frozen", time = frozen_at.timestamp())),
+    )
+    .await?;
+    Ok(false)
+}
+
+/// 伺服器維護時凍結佔領計時
+#[poise::command(
+    slash_command,
+    rename = "凍結",
+    check = "require_admin",
+    subcommands("start_freeze", "end_freeze"),
+    subcommand_required
+)]
+pub async fn freeze(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 開始凍結，期間無法佔領或挑戰礦點
+#[poise::command(slash_command, rename = "開始")]
+async fn start_freeze(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let locale = i18n::locale(ctx).await;
+
+    if ctx.data().start_freeze(guild_id, Utc::now()).await? {
+        ctx.reply(tr!(locale, "freeze.started")).await?;
+    } else {
+        ctx.reply(tr!(locale, "freeze.already_frozen")).await?;
+    }
+    Ok(())
+}
+
+/// 解除凍結，所有佔領的到期時間延後凍結的時間
+#[poise::command(slash_command, rename = "解除")]
+async fn end_freeze(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let now = Utc::now();
+    let locale = i18n::locale(ctx).await;
+
+    let Some(frozen_at) = ctx.data().end_freeze(guild_id, now).await? else {
+        ctx.reply(tr!(locale, "freeze.not_frozen")).await?;
+        return Ok(());
+    };
+
+    let minutes = (now - frozen_at).num_minutes();
+    ctx.reply(tr!(
+        locale,
+        "freeze.ended",
+        hours = minutes / 60,
+        minutes = minutes % 60
+    ))
+    .await?;
+    Ok(())
+}
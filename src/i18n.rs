@@ -0,0 +1,252 @@
+use crate::{commands::Context, db::BotDB};
+use anyhow::Error;
+use once_cell::sync::Lazy;
+use poise::{ChoiceParameter, Command};
+use std::{collections::HashMap, fmt::Display};
+
+/// 機器人支援的語言
+#[derive(poise::ChoiceParameter, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    #[name = "繁體中文"]
+    ZhTw,
+    #[name = "简体中文"]
+    ZhCn,
+    #[name = "English"]
+    En,
+    #[name = "日本語"]
+    Ja,
+}
+
+impl Locale {
+    pub fn all() -> [Self; 4] {
+        [Locale::ZhTw, Locale::ZhCn, Locale::En, Locale::Ja]
+    }
+
+    /// 儲存於資料庫與翻譯資料表的語言代碼
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::ZhTw => "zh-TW",
+            Locale::ZhCn => "zh-CN",
+            Locale::En => "en",
+            Locale::Ja => "ja",
+        }
+    }
+
+    /// 對應的 Discord 語言代碼
+    fn discord_codes(self) -> &'static [&'static str] {
+        match self {
+            Locale::ZhTw => &["zh-TW"],
+            Locale::ZhCn => &["zh-CN"],
+            Locale::En => &["en-US", "en-GB"],
+            Locale::Ja => &["ja"],
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::all().into_iter().find(|locale| locale.code() == code)
+    }
+
+    pub fn from_discord(code: &str) -> Option<Self> {
+        Self::all()
+            .into_iter()
+            .find(|locale| locale.discord_codes().contains(&code))
+    }
+}
+
+static TRANSLATIONS: Lazy<HashMap<Locale, HashMap<String, String>>> = Lazy::new(|| {
+    [
+        (Locale::ZhTw, include_str!("../locales/zh-TW.json")),
+        (Locale::ZhCn, include_str!("../locales/zh-CN.json")),
+        (Locale::En, include_str!("../locales/en.json")),
+        (Locale::Ja, include_str!("../locales/ja.json")),
+    ]
+    .into_iter()
+    .map(|(locale, content)| {
+        let strings = serde_json::from_str(content).expect("Invalid translation file");
+        (locale, strings)
+    })
+    .collect()
+});
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static String> {
+    TRANSLATIONS.get(&locale)?.get(key)
+}
+
+/// 取得翻譯後的字串並代入參數，找不到時依序使用繁體中文與鍵值
+pub fn translate(locale: Locale, key: &str, args: &[(&str, &(dyn Display + Sync))]) -> String {
+    let template = lookup(locale, key)
+        .or_else(|| lookup(Locale::ZhTw, key))
+        .map_or(key, |template| template.as_str());
+    args.iter()
+        .fold(template.to_owned(), |output, (name, value)| {
+            output.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+/// 以 `tr!(locale, "key", name = value)` 取得翻譯後的字串
+macro_rules! tr {
+    ($locale:expr, $key:expr) => {
+        $crate::i18n::translate($locale, $key, &[])
+    };
+    ($locale:expr, $key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate(
+            $locale,
+            $key,
+            &[$((stringify!($name), &$value as &(dyn std::fmt::Display + Sync))),+],
+        )
+    };
+}
+pub(crate) use tr;
+
+/// 伺服器預設的語言，用於頻道公告等沒有互動對象的訊息
+pub async fn guild_locale(db: &BotDB, guild_id: u64) -> Locale {
+    db.get_guild_config(guild_id)
+        .await
+        .ok()
+        .and_then(|config| config.locale)
+        .and_then(|code| Locale::from_code(&code))
+        .unwrap_or_default()
+}
+
+/// 依互動的語言決定回覆的語言，不支援時使用伺服器預設的語言
+pub async fn interaction_locale(db: &BotDB, guild_id: Option<u64>, locale: &str) -> Locale {
+    match (Locale::from_discord(locale), guild_id) {
+        (Some(locale), _) => locale,
+        (None, Some(guild_id)) => guild_locale(db, guild_id).await,
+        (None, None) => Locale::default(),
+    }
+}
+
+/// 指令回覆使用的語言
+pub async fn locale(ctx: Context<'_>) -> Locale {
+    interaction_locale(
+        ctx.data(),
+        ctx.guild_id().map(|guild_id| guild_id.get()),
+        ctx.locale().unwrap_or_default(),
+    )
+    .await
+}
+
+/// 選項翻譯後的名稱
+pub fn choice(locale: Locale, value: impl ChoiceParameter) -> String {
+    translate(locale, &format!("choice.{}", value.name()), &[])
+}
+
+/// 由翻譯檔設定指令與參數的名稱及說明
+pub fn localize_commands(commands: &mut [Command<BotDB, Error>]) {
+    for command in commands {
+        localize_command(command, "command");
+    }
+}
+
+fn localize_command(command: &mut Command<BotDB, Error>, prefix: &str) {
+    let key = format!("{prefix}.{}", command.source_code_name);
+    for locale in Locale::all() {
+        let name = lookup(locale, &key);
+        let description = lookup(locale, &format!("{key}.description"));
+        for code in locale.discord_codes() {
+            if let Some(name) = name {
+                command
+                    .name_localizations
+                    .insert(code.to_string(), name.clone());
+            }
+            if let Some(description) = description {
+                command
+                    .description_localizations
+                    .insert(code.to_string(), description.clone());
+            }
+        }
+
+        for parameter in &mut command.parameters {
+            let parameter_key = format!("{key}.{}", parameter.name);
+            let name = lookup(locale, &parameter_key);
+            let description = lookup(locale, &format!("{parameter_key}.description"));
+            for code in locale.discord_codes() {
+                if let Some(name) = name {
+                    parameter
+                        .name_localizations
+                        .insert(code.to_string(), name.clone());
+                }
+                if let Some(description) = description {
+                    parameter
+                        .description_localizations
+                        .insert(code.to_string(), description.clone());
+                }
+            }
+
+            // 選項以繁體中文名稱作為鍵值，例如 `choice.佔領者`
+            for choice in &mut parameter.choices {
+                let Some(name) = lookup(locale, &format!("choice.{}", choice.name)) else {
+                    continue;
+                };
+                for code in locale.discord_codes() {
+                    choice.localizations.insert(code.to_string(), name.clone());
+                }
+            }
+        }
+    }
+
+    for subcommand in &mut command.subcommands {
+        localize_command(subcommand, &key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn placeholders(template: &str) -> BTreeSet<&str> {
+        template
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn locales_have_same_keys() {
+        let source = &TRANSLATIONS[&Locale::ZhTw];
+        for locale in Locale::all() {
+            let strings = &TRANSLATIONS[&locale];
+            let mut missing: Vec<_> = source
+                .keys()
+                .filter(|key| !strings.contains_key(*key))
+                .collect();
+            let mut extra: Vec<_> = strings
+                .keys()
+                .filter(|key| !source.contains_key(*key))
+                .collect();
+            missing.sort();
+            extra.sort();
+            assert!(missing.is_empty(), "{} missing {missing:?}", locale.code());
+            assert!(extra.is_empty(), "{} extra {extra:?}", locale.code());
+        }
+    }
+
+    #[test]
+    fn locales_have_same_placeholders() {
+        let source = &TRANSLATIONS[&Locale::ZhTw];
+        for locale in Locale::all() {
+            for (key, template) in &TRANSLATIONS[&locale] {
+                assert_eq!(
+                    placeholders(template),
+                    placeholders(&source[key]),
+                    "{} {key}",
+                    locale.code()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn translate_fills_arguments() {
+        assert_eq!(
+            tr!(Locale::En, "challenge.withdrawn", user = "<@1>"),
+            "<@1> withdrew the challenge"
+        );
+        // 缺少的鍵值直接回傳鍵值
+        assert_eq!(tr!(Locale::Ja, "missing.key"), "missing.key");
+    }
+}
@@ -0,0 +1,553 @@
+use crate::{
+    commands::Context,
+    db::{BotDB, RankingRecord},
+    i18n::{self, tr, Locale},
+    link::{linked_names, mention},
+    list::ListContent,
+    notify::autocomplete_ore_type,
+    permission::require_member,
+    structs::{HistoryAction, OrePoint, OreType},
+    world::{autocomplete_world, resolve_world, DEFAULT_WORLD_ID},
+};
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Duration, Utc};
+use poise::{
+    serenity_prelude::{
+        ButtonStyle, Color, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter,
+    },
+    ChoiceParameter, CreateReply,
+};
+use std::{collections::HashMap, fmt::Write};
+
+const PAGE_SIZE: usize = 10;
+
+/// 排行的項目
+#[derive(ChoiceParameter, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    #[name = "目前佔領"]
+    Held,
+    #[name = "佔領天數"]
+    Days,
+    #[name = "挑戰勝場"]
+    Wins,
+    #[name = "勝率"]
+    WinRate,
+}
+
+/// 排行的對象
+#[derive(ChoiceParameter, Clone, Copy, PartialEq, Eq)]
+pub enum Group {
+    #[name = "玩家"]
+    Player,
+    #[name = "隊伍"]
+    Team,
+}
+
+/// 統計的期間
+#[derive(ChoiceParameter, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    #[name = "本賽季"]
+    Season,
+    #[name = "最近 7 天"]
+    Week,
+    #[name = "最近 30 天"]
+    Month,
+    #[name = "全部"]
+    All,
+}
+
+impl Period {
+    fn since(self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Period::Season | Period::All => None,
+            Period::Week => Some(now - Duration::days(7)),
+            Period::Month => Some(now - Duration::days(30)),
+        }
+    }
+}
+
+/// 排行榜的查詢條件
+#[derive(Clone, Copy)]
+pub struct Ranking {
+    pub world_id: i32,
+    pub metric: Metric,
+    pub group: Group,
+    pub period: Period,
+    /// 礦種編號，0 為所有礦種
+    pub ore_type: i32,
+}
+
+impl Ranking {
+    fn custom_id(&self, page_index: usize) -> String {
+        format!(
+            "rank:{}:{}:{}:{}:{}:{}",
+            self.world_id,
+            self.metric as usize,
+            self.group as usize,
+            self.period as usize,
+            self.ore_type,
+            page_index
+        )
+    }
+
+    /// 解析按鈕的 custom_id，回傳查詢條件與頁碼
+    pub fn parse(args: &str) -> Option<(Self, usize)> {
+        let mut parts = args.split(':');
+        let ranking = Ranking {
+            world_id: parts.next()?.parse().ok()?,
+            metric: Metric::from_index(parts.next()?.parse().ok()?)?,
+            group: Group::from_index(parts.next()?.parse().ok()?)?,
+            period: Period::from_index(parts.next()?.parse().ok()?)?,
+            ore_type: parts.next()?.parse().ok()?,
+        };
+        Some((ranking, parts.next()?.parse().ok()?))
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    held: u32,
+    seconds: i64,
+    wins: u32,
+    losses: u32,
+}
+
+impl Stats {
+    fn win_rate(&self) -> f64 {
+        match self.wins + self.losses {
+            0 => 0.0,
+            total => self.wins as f64 / total as f64,
+        }
+    }
+}
+
+/// 統計的鍵值，玩家為使用者 id，隊伍為隊伍 id
+fn key(group: Group, user_id: u64, team_id: Option<i32>) -> Option<u64> {
+    match group {
+        Group::Player => Some(user_id),
+        Group::Team => team_id.map(|team_id| team_id as u64),
+    }
+}
+
+/// 依時間重播佔領紀錄，計算佔領時間與勝敗場數
+fn replay(
+    records: &[RankingRecord],
+    group: Group,
+    since: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    stats: &mut HashMap<u64, Stats>,
+) {
+    // 依賽季與礦點分開重播
+    let mut timelines: HashMap<(Option<i32>, i32), Vec<&RankingRecord>> = HashMap::new();
+    for record in records {
+        timelines
+            .entry((record.season_id, record.ore_point_id))
+            .or_default()
+            .push(record);
+    }
+
+    let mut add_time = |key: Option<u64>, start: DateTime<Utc>, end: DateTime<Utc>| {
+        let start = since.map_or(start, |since| start.max(since));
+        if let Some(key) = key.filter(|_| end > start) {
+            stats.entry(key).or_default().seconds += (end - start).num_seconds();
+        }
+    };
+
+    let mut results = Vec::new();
+    for timeline in timelines.values() {
+        let end = timeline[0].season_end.unwrap_or(now);
+        let mut holder: Option<(u64, Option<i32>, DateTime<Utc>)> = None;
+        let mut challenger_teams: HashMap<u64, Option<i32>> = HashMap::new();
+
+        for record in timeline {
+            let action = record.action.as_str();
+            if action == HistoryAction::Occupy.as_str()
+                || action == HistoryAction::ForceOccupy.as_str()
+                || action == HistoryAction::Transfer.as_str()
+                || action == HistoryAction::Revert.as_str()
+            {
+                if let Some((user_id, team_id, start)) = holder {
+                    add_time(key(group, user_id, team_id), start, record.time);
+                }
+                holder = Some((record.user_id, record.team_id, record.time));
+            } else if action == HistoryAction::Challenge.as_str() {
+                challenger_teams.insert(record.user_id, record.team_id);
+            } else if action == HistoryAction::ChallengeWon.as_str() {
+                let defended = holder.is_some_and(|(user_id, _, _)| user_id == record.user_id);
+                if since.is_none_or(|since| record.time >= since) {
+                    let loser_team = match holder {
+                        Some((_, team_id, _)) if !defended => team_id,
+                        _ => record
+                            .target_user_id
+                            .and_then(|user_id| challenger_teams.get(&user_id).copied())
+                            .flatten(),
+                    };
+                    results.push((key(group, record.user_id, record.team_id), true));
+                    if let Some(loser) = record.target_user_id {
+                        results.push((key(group, loser, loser_team), false));
+                    }
+                }
+                if !defended {
+                    if let Some((user_id, team_id, start)) = holder {
+                        add_time(key(group, user_id, team_id), start, record.time);
+                    }
+                    holder = Some((record.user_id, record.team_id, record.time));
+                }
+            } else if action == HistoryAction::Release.as_str() {
+                if let Some((user_id, team_id, start)) = holder.take() {
+                    add_time(key(group, user_id, team_id), start, record.time);
+                }
+            }
+        }
+
+        if let Some((user_id, team_id, start)) = holder {
+            add_time(key(group, user_id, team_id), start, end);
+        }
+    }
+
+    for (key, won) in results {
+        let Some(key) = key else { continue };
+        let entry = stats.entry(key).or_default();
+        if won {
+            entry.wins += 1;
+        } else {
+            entry.losses += 1;
+        }
+    }
+}
+
+pub async fn leaderboard(
+    db: &BotDB,
+    guild_id: u64,
+    ranking: Ranking,
+    page_index: usize,
+    locale: Locale,
+) -> Result<ListContent> {
+    let now = Utc::now();
+    let since = ranking.period.since(now);
+    let world_id = ranking.world_id;
+
+    // 篩選礦種的礦點
+    let point_ids: Vec<i32> = OrePoint::iter()
+        .filter(|point| ranking.ore_type == 0 || point.ore_type & ranking.ore_type != 0)
+        .map(|point| point.id)
+        .collect();
+
+    let mut stats: HashMap<u64, Stats> = HashMap::new();
+    for data in db.get_occupations(guild_id, world_id).await? {
+        if !point_ids.contains(&data.ore_point_id) {
+            continue;
+        }
+        if let Some(key) = key(ranking.group, data.user_id, data.team_id) {
+            stats.entry(key).or_default().held += 1;
+        }
+    }
+
+    let records: Vec<RankingRecord> = db
+        .get_ranking_history(guild_id, world_id, since, ranking.period == Period::Season)
+        .await?
+        .into_iter()
+        .filter(|record| point_ids.contains(&record.ore_point_id))
+        .collect();
+    replay(&records, ranking.group, since, now, &mut stats);
+
+    let mut rows: Vec<(u64, Stats)> = stats
+        .into_iter()
+        .filter(|(_, stats)| match ranking.metric {
+            Metric::Held => stats.held > 0,
+            Metric::Days => stats.seconds > 0,
+            Metric::Wins => stats.wins > 0,
+            Metric::WinRate => stats.wins + stats.losses > 0,
+        })
+        .collect();
+    rows.sort_by(|(a_key, a), (b_key, b)| {
+        match ranking.metric {
+            Metric::Held => b.held.cmp(&a.held),
+            Metric::Days => b.seconds.cmp(&a.seconds),
+            Metric::Wins => b.wins.cmp(&a.wins),
+            Metric::WinRate => b
+                .win_rate()
+                .total_cmp(&a.win_rate())
+                .then(b.wins.cmp(&a.wins)),
+        }
+        .then(a_key.cmp(b_key))
+    });
+
+    let names = linked_names(db, guild_id, world_id).await?;
+    let teams: HashMap<u64, String> = db
+        .get_teams(guild_id)
+        .await?
+        .into_iter()
+        .map(|team| (team.id as u64, team.name))
+        .collect();
+
+    let max_page = rows.len().div_ceil(PAGE_SIZE).max(1);
+    let page_index = page_index.min(max_page - 1);
+
+    let mut description = String::new();
+    for (rank, (key, stats)) in rows
+        .iter()
+        .enumerate()
+        .skip(page_index * PAGE_SIZE)
+        .take(PAGE_SIZE)
+    {
+        let name = match ranking.group {
+            Group::Player => mention(&names, *key),
+            Group::Team => teams.get(key).cloned().unwrap_or_default(),
+        };
+        let value = match ranking.metric {
+            Metric::Held => tr!(locale, "season.point_count", count = stats.held),
+            Metric::Days => tr!(
+                locale,
+                "ranking.days",
+                days = format!("{:.1}", stats.seconds as f64 / 86400.0)
+            ),
+            Metric::Wins => tr!(locale, "ranking.wins", wins = stats.wins),
+            Metric::WinRate => tr!(
+                locale,
+                "ranking.win_rate",
+                rate = format!("{:.0}", stats.win_rate() * 100.0),
+                wins = stats.wins,
+                losses = stats.losses
+            ),
+        };
+        let _ = writeln!(description, "`{:>2}.` {} {}", rank + 1, name, value);
+    }
+    if description.is_empty() {
+        description.push_str(&tr!(locale, "ranking.empty"));
+    }
+
+    let mut title = tr!(
+        locale,
+        "ranking.title",
+        metric = i18n::choice(locale, ranking.metric),
+        period = i18n::choice(locale, ranking.period)
+    );
+    if let Some(ore_type) = OreType::iter().find(|ore_type| ore_type.id == ranking.ore_type) {
+        let _ = write!(
+            title,
+            " <{}> {}",
+            ore_type.emoji,
+            ore_type.localized_name(locale)
+        );
+    }
+    if world_id != DEFAULT_WORLD_ID {
+        let world_name = db
+            .get_world_name(guild_id, world_id)
+            .await?
+            .unwrap_or_default();
+        let _ = write!(title, " - {world_name}");
+    }
+
+    let embed = CreateEmbed::new()
+        .color(Color::GOLD)
+        .title(title)
+        .description(description)
+        .footer(CreateEmbedFooter::new(format!(
+            "{}/{}",
+            page_index + 1,
+            max_page
+        )));
+
+    let buttons = CreateActionRow::Buttons(vec![
+        if page_index > 0 {
+            CreateButton::new(ranking.custom_id(page_index - 1))
+        } else {
+            CreateButton::new("0").disabled(true)
+        }
+        .emoji('◀'),
+        CreateButton::new(ranking.custom_id(page_index))
+            .emoji('🔄')
+            .style(ButtonStyle::Success),
+        if page_index + 1 < max_page {
+            CreateButton::new(ranking.custom_id(page_index + 1))
+        } else {
+            CreateButton::new("0").disabled(true)
+        }
+        .emoji('▶'),
+    ]);
+
+    Ok(ListContent {
+        embed,
+        component: vec![buttons],
+    })
+}
+
+/// 查看佔領排行榜
+#[poise::command(slash_command, rename = "排行榜", check = "require_member")]
+pub async fn ranking(
+    ctx: Context<'_>,
+    #[rename = "項目"]
+    #[description = "排行的項目"]
+    metric: Metric,
+    #[rename = "對象"]
+    #[description = "依玩家或隊伍排行，預設為玩家"]
+    group: Option<Group>,
+    #[rename = "期間"]
+    #[description = "統計的期間，預設為本賽季"]
+    period: Option<Period>,
+    #[rename = "礦種"]
+    #[description = "只統計此礦種的礦點，留空則統計所有礦點"]
+    #[autocomplete = "autocomplete_ore_type"]
+    ore_type: Option<String>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+
+    let ore_type = match ore_type {
+        Some(name) => {
+            OreType::iter()
+                .find(|ore_type| ore_type.name == name)
+                .context("找不到礦種")?
+                .id
+        }
+        None => 0,
+    };
+    let ranking = Ranking {
+        world_id,
+        metric,
+        group: group.unwrap_or(Group::Player),
+        period: period.unwrap_or(Period::Season),
+        ore_type,
+    };
+
+    let locale = i18n::locale(ctx).await;
+    let content = leaderboard(ctx.data(), guild_id, ranking, 0, locale).await?;
+    ctx.send(
+        CreateReply::default()
+            .embed(content.embed)
+            .components(content.component),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const DAY: i64 = 86400;
+
+    fn day(n: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, n, 0, 0, 0).unwrap()
+    }
+
+    fn record(
+        action: HistoryAction,
+        user_id: u64,
+        target_user_id: Option<u64>,
+        team_id: Option<i32>,
+        time: DateTime<Utc>,
+    ) -> RankingRecord {
+        RankingRecord {
+            season_id: None,
+            season_end: None,
+            ore_point_id: 1,
+            user_id,
+            target_user_id,
+            team_id,
+            action: action.as_str().to_owned(),
+            time,
+        }
+    }
+
+    fn run(records: &[RankingRecord], group: Group, period: Period) -> HashMap<u64, Stats> {
+        let now = day(10);
+        let mut stats = HashMap::new();
+        replay(records, group, period.since(now), now, &mut stats);
+        stats
+    }
+
+    #[test]
+    fn held_then_taken() {
+        let records = [
+            record(HistoryAction::Occupy, 1, None, None, day(1)),
+            record(HistoryAction::Challenge, 2, None, None, day(2)),
+            record(HistoryAction::ChallengeWon, 2, Some(1), None, day(4)),
+        ];
+        let stats = run(&records, Group::Player, Period::All);
+        assert_eq!(stats[&1].seconds, 3 * DAY);
+        assert_eq!((stats[&1].wins, stats[&1].losses), (0, 1));
+        assert_eq!(stats[&2].seconds, 6 * DAY);
+        assert_eq!((stats[&2].wins, stats[&2].losses), (1, 0));
+    }
+
+    #[test]
+    fn defended_keeps_holding() {
+        let records = [
+            record(HistoryAction::Occupy, 1, None, None, day(1)),
+            record(HistoryAction::Challenge, 2, None, None, day(2)),
+            record(HistoryAction::ChallengeWon, 1, Some(2), None, day(4)),
+        ];
+        let stats = run(&records, Group::Player, Period::All);
+        assert_eq!(stats[&1].seconds, 9 * DAY);
+        assert_eq!((stats[&1].wins, stats[&1].losses), (1, 0));
+        assert_eq!(stats[&2].seconds, 0);
+        assert_eq!((stats[&2].wins, stats[&2].losses), (0, 1));
+    }
+
+    #[test]
+    fn released_stops_holding() {
+        let records = [
+            record(HistoryAction::Occupy, 1, None, None, day(1)),
+            record(HistoryAction::Release, 1, None, None, day(3)),
+        ];
+        let stats = run(&records, Group::Player, Period::All);
+        assert_eq!(stats[&1].seconds, 2 * DAY);
+    }
+
+    #[test]
+    fn reverted_point_returns_to_previous_owner() {
+        let records = [
+            record(HistoryAction::Occupy, 1, None, None, day(1)),
+            record(HistoryAction::ForceOccupy, 2, None, None, day(2)),
+            record(HistoryAction::Revert, 1, None, None, day(3)),
+        ];
+        let stats = run(&records, Group::Player, Period::All);
+        assert_eq!(stats[&1].seconds, 8 * DAY);
+        assert_eq!(stats[&2].seconds, DAY);
+    }
+
+    #[test]
+    fn week_counts_from_period_start() {
+        // 期間從第 3 天開始，之前的佔領時間與勝場不計入
+        let records = [
+            record(HistoryAction::Occupy, 1, None, None, day(1)),
+            record(HistoryAction::ChallengeWon, 2, Some(1), None, day(2)),
+        ];
+        let stats = run(&records, Group::Player, Period::Week);
+        assert!(!stats.contains_key(&1));
+        assert_eq!(stats[&2].seconds, 7 * DAY);
+        assert_eq!((stats[&2].wins, stats[&2].losses), (0, 0));
+
+        let records = [record(HistoryAction::Occupy, 1, None, None, day(1))];
+        let stats = run(&records, Group::Player, Period::Week);
+        assert_eq!(stats[&1].seconds, 7 * DAY);
+    }
+
+    #[test]
+    fn team_groups_by_team() {
+        let records = [
+            record(HistoryAction::Occupy, 1, None, Some(100), day(1)),
+            record(HistoryAction::Challenge, 2, None, Some(200), day(2)),
+            record(HistoryAction::ChallengeWon, 1, Some(2), Some(100), day(4)),
+            RankingRecord {
+                ore_point_id: 2,
+                ..record(HistoryAction::Occupy, 3, None, None, day(1))
+            },
+        ];
+        let stats = run(&records, Group::Team, Period::All);
+        // 沒有隊伍的玩家不列入隊伍排行
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[&100].seconds, 9 * DAY);
+        assert_eq!((stats[&100].wins, stats[&100].losses), (1, 0));
+        assert_eq!((stats[&200].wins, stats[&200].losses), (0, 1));
+    }
+}
@@ -0,0 +1,312 @@
+use crate::{
+    commands::Context,
+    db::{BotDB, PlayerLink},
+    i18n::{self, tr},
+    permission::{require_member, require_officer},
+    world::{autocomplete_world, resolve_world},
+};
+use anyhow::{Context as _, Result};
+use poise::{
+    serenity_prelude::{Color, CreateEmbed, User},
+    CreateReply,
+};
+use std::collections::HashMap;
+
+/// 取得已驗證的角色名稱
+pub async fn linked_names(
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+) -> Result<HashMap<u64, String>> {
+    Ok(db
+        .get_player_links(guild_id, world_id)
+        .await?
+        .into_iter()
+        .filter(|link| link.verified)
+        .map(|link| (link.user_id, link.character_name))
+        .collect())
+}
+
+/// 玩家的標記，已驗證角色時附上遊戲內名稱
+pub fn mention(names: &HashMap<u64, String>, user_id: u64) -> String {
+    match names.get(&user_id) {
+        Some(name) => format!("<@{user_id}> ({name})"),
+        None => format!("<@{user_id}>"),
+    }
+}
+
+/// 查詢單一玩家的標記
+pub async fn mention_player(db: &BotDB, guild_id: u64, world_id: i32, user_id: u64) -> String {
+    match db.get_player_link(guild_id, world_id, user_id).await {
+        Ok(Some(link)) if link.verified => format!("<@{user_id}> ({})", link.character_name),
+        _ => format!("<@{user_id}>"),
+    }
+}
+
+/// 由 Discord 使用者或遊戲角色名稱取得玩家，`include_unverified` 為 false 時只接受已驗證的角色
+///
+/// 未指定玩家或找不到角色時回覆並回傳 None
+pub async fn resolve_player(
+    ctx: Context<'_>,
+    world_id: i32,
+    user: Option<User>,
+    character: Option<String>,
+    include_unverified: bool,
+) -> Result<Option<u64>> {
+    if let Some(user) = user {
+        return Ok(Some(user.id.get()));
+    }
+    let locale = i18n::locale(ctx).await;
+    let Some(character) = character else {
+        ctx.reply(tr!(locale, "link.player_required")).await?;
+        return Ok(None);
+    };
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let user_id = ctx
+        .data()
+        .find_linked_player(guild_id, world_id, &character, include_unverified)
+        .await?;
+    if user_id.is_none() {
+        ctx.reply(tr!(locale, "link.character_not_found", name = character))
+            .await?;
+    }
+    Ok(user_id)
+}
+
+async fn character_names(ctx: Context<'_>, partial: &str, include_unverified: bool) -> Vec<String> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Vec::new();
+    };
+    let Ok(Some(world_id)) = resolve_world(ctx, None).await else {
+        return Vec::new();
+    };
+    ctx.data()
+        .get_player_links(guild_id.get(), world_id)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|link| include_unverified || link.verified)
+        .map(|link| link.character_name)
+        .filter(|name| name.contains(partial))
+        .take(25)
+        .collect()
+}
+
+/// 已驗證的角色名稱
+pub async fn autocomplete_character(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    character_names(ctx, partial, false).await
+}
+
+/// 所有綁定的角色名稱，包含等待驗證的角色
+async fn autocomplete_linked_character(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    character_names(ctx, partial, true).await
+}
+
+/// 綁定自己的遊戲角色
+#[poise::command(
+    slash_command,
+    rename = "綁定角色",
+    check = "require_member",
+    ephemeral
+)]
+pub async fn link_character(
+    ctx: Context<'_>,
+    #[max_length = 255]
+    #[rename = "角色名稱"]
+    #[description = "遊戲內的角色名稱"]
+    name: String,
+    #[max_length = 64]
+    #[rename = "玩家uid"]
+    #[description = "遊戲內的玩家 UID"]
+    player_uid: Option<String>,
+    #[max_length = 64]
+    #[rename = "steam_id"]
+    #[description = "Steam ID"]
+    steam_id: Option<String>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let user_id = ctx.author().id.get();
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+
+    if db
+        .find_linked_player(guild_id, world_id, &name, true)
+        .await?
+        .is_some_and(|linked| linked != user_id)
+    {
+        ctx.reply(tr!(locale, "link.taken")).await?;
+        return Ok(());
+    }
+
+    db.link_player(
+        guild_id,
+        world_id,
+        user_id,
+        PlayerLink {
+            user_id,
+            character_name: name.clone(),
+            player_uid,
+            steam_id,
+            verified: false,
+        },
+    )
+    .await?;
+    ctx.reply(tr!(locale, "link.linked", name = name)).await?;
+    Ok(())
+}
+
+/// 管理玩家綁定的遊戲角色
+#[poise::command(
+    slash_command,
+    rename = "角色",
+    check = "require_officer",
+    subcommands("verify_character", "unlink_character", "list_characters"),
+    subcommand_required
+)]
+pub async fn character(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 驗證玩家綁定的角色
+#[poise::command(slash_command, rename = "驗證", ephemeral)]
+async fn verify_character(
+    ctx: Context<'_>,
+    #[rename = "通過"]
+    #[description = "是否通過驗證"]
+    verified: bool,
+    #[rename = "玩家"]
+    #[description = "Discord 使用者"]
+    user: Option<User>,
+    #[rename = "角色"]
+    #[description = "遊戲內的角色名稱"]
+    #[autocomplete = "autocomplete_linked_character"]
+    character: Option<String>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    // 驗證與解除綁定的對象可以是尚未驗證的角色
+    let Some(user_id) = resolve_player(ctx, world_id, user, character, true).await? else {
+        return Ok(());
+    };
+    let locale = i18n::locale(ctx).await;
+
+    if !ctx
+        .data()
+        .set_player_verified(guild_id, world_id, user_id, verified)
+        .await?
+    {
+        ctx.reply(tr!(locale, "link.not_linked")).await?;
+        return Ok(());
+    }
+    let user = format!("<@{user_id}>");
+    ctx.reply(if verified {
+        tr!(locale, "link.verified", user = user)
+    } else {
+        tr!(locale, "link.unverified", user = user)
+    })
+    .await?;
+    Ok(())
+}
+
+/// 解除玩家綁定的角色
+#[poise::command(slash_command, rename = "解除綁定", ephemeral)]
+async fn unlink_character(
+    ctx: Context<'_>,
+    #[rename = "玩家"]
+    #[description = "Discord 使用者"]
+    user: Option<User>,
+    #[rename = "角色"]
+    #[description = "遊戲內的角色名稱"]
+    #[autocomplete = "autocomplete_linked_character"]
+    character: Option<String>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let Some(user_id) = resolve_player(ctx, world_id, user, character, true).await? else {
+        return Ok(());
+    };
+    let locale = i18n::locale(ctx).await;
+
+    if ctx
+        .data()
+        .unlink_player(guild_id, world_id, user_id)
+        .await?
+    {
+        ctx.reply(tr!(locale, "link.unlinked", user = format!("<@{user_id}>")))
+            .await?;
+    } else {
+        ctx.reply(tr!(locale, "link.not_linked")).await?;
+    }
+    Ok(())
+}
+
+/// 列出綁定的角色
+#[poise::command(slash_command, rename = "列表", ephemeral)]
+async fn list_characters(
+    ctx: Context<'_>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let links = ctx.data().get_player_links(guild_id, world_id).await?;
+    let locale = i18n::locale(ctx).await;
+
+    let description = if links.is_empty() {
+        tr!(locale, "link.empty")
+    } else {
+        links
+            .iter()
+            .map(|link| {
+                format!(
+                    "{} <@{}> {}{}",
+                    if link.verified { "✅" } else { "⏳" },
+                    link.user_id,
+                    link.character_name,
+                    link.player_uid
+                        .as_ref()
+                        .or(link.steam_id.as_ref())
+                        .map_or(String::new(), |id| format!(" `{id}`"))
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .color(Color::BLUE)
+                    .title(tr!(locale, "link.list"))
+                    .description(description),
+            )
+            .reply(true)
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
@@ -1,5 +1,11 @@
-use crate::db::BotDB;
+use crate::{
+    db::BotDB,
+    i18n::{tr, Locale},
+    link::{linked_names, mention},
+    world::DEFAULT_WORLD_ID,
+};
 use anyhow::{Error, Ok, Result};
+use chrono::Utc;
 use poise::serenity_prelude::{
     ButtonStyle, Color, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter,
 };
@@ -12,46 +18,102 @@ pub struct ListContent {
 pub async fn list(
     db: &BotDB,
     guild_id: u64,
+    world_id: i32,
     page_index: u32,
     page_size: u32,
+    locale: Locale,
 ) -> Result<ListContent, Error> {
     let start = page_size * page_index;
 
     let max_page = db.get_point_count().await?.div_ceil(page_size);
-    let data = db.get_point_data(guild_id, start, page_size).await?;
+    let data = db
+        .get_point_data(guild_id, world_id, start, page_size)
+        .await?;
+    let names = linked_names(db, guild_id, world_id).await?;
+
+    let title = match world_id {
+        DEFAULT_WORLD_ID => tr!(locale, "list.title"),
+        _ => tr!(
+            locale,
+            "list.title_world",
+            world = db
+                .get_world_name(guild_id, world_id)
+                .await?
+                .unwrap_or_default()
+        ),
+    };
 
-    let mut embed = CreateEmbed::new()
-        .color(Color::BLUE)
-        .title("礦點列表")
-        .footer(CreateEmbedFooter::new(format!(
-            "{}/{}",
-            page_index + 1,
-            max_page
-        )));
+    let mut embed =
+        CreateEmbed::new()
+            .color(Color::BLUE)
+            .title(title)
+            .footer(CreateEmbedFooter::new(format!(
+                "{}/{}",
+                page_index + 1,
+                max_page
+            )));
+    if let Some(frozen_at) = db.get_guild_config(guild_id).await?.frozen_at {
+        embed = embed.description(tr!(locale, "list.frozen", time = frozen_at.timestamp()));
+    }
 
+    let now = Utc::now();
     for row in data {
         embed = embed.field(
             format!(
                 "`{:>2}` {} {} `({}, {})`",
                 row.id,
                 row.emoji(),
-                row.name,
+                row.localized_name(locale),
                 row.x,
                 row.y
             ),
             format!(
-                "{}{}{}",
-                row.user_id
-                    .map_or(String::new(), |user_id| format!("佔領者: <@{}>\n", user_id)),
+                "{}{}{}{}{}{}{}{}",
+                row.user_id.map_or(String::new(), |user_id| format!(
+                    "{}\n",
+                    tr!(locale, "list.owner", user = mention(&names, user_id))
+                )),
+                row.team_name
+                    .as_ref()
+                    .map_or(String::new(), |team_name| format!(
+                        "{}\n",
+                        tr!(locale, "list.team", team = team_name)
+                    )),
                 row.due_time.map_or(String::new(), |due_time| format!(
-                    "佔領期限: <t:{}:F>\n",
-                    due_time.timestamp()
+                    "{}\n",
+                    tr!(locale, "list.due_time", time = due_time.timestamp())
+                )),
+                row.shield_until
+                    .filter(|shield_until| *shield_until > now)
+                    .map_or(String::new(), |shield_until| format!(
+                        "{}\n",
+                        tr!(locale, "list.shield", time = shield_until.timestamp())
+                    )),
+                row.leave_until.map_or(String::new(), |leave_until| format!(
+                    "{}\n",
+                    tr!(locale, "list.on_leave", time = leave_until.timestamp())
                 )),
                 row.battle_user_id
                     .map_or(String::new(), |battle_user_id| format!(
-                        "<@{}> 已發起挑戰\n",
-                        battle_user_id
-                    ))
+                        "{}\n",
+                        tr!(
+                            locale,
+                            "list.challenger",
+                            user = mention(&names, battle_user_id),
+                            team = row.battle_team_name.as_ref().map_or(
+                                String::new(),
+                                |team_name| tr!(locale, "list.challenger_team", team = team_name)
+                            )
+                        )
+                    )),
+                row.battle_time.map_or(String::new(), |battle_time| format!(
+                    "{}\n",
+                    tr!(locale, "list.battle_time", time = battle_time.timestamp())
+                )),
+                match row.queue_count {
+                    0 => String::new(),
+                    count => format!("{}\n", tr!(locale, "list.queue", count = count)),
+                }
             ),
             false,
         );
@@ -59,16 +121,16 @@ pub async fn list(
 
     let buttons = CreateActionRow::Buttons(vec![
         if page_index > 0 {
-            CreateButton::new(format!("list:{}:{}", page_index - 1, page_size))
+            CreateButton::new(custom_id(world_id, page_index - 1, page_size))
         } else {
             CreateButton::new("0").disabled(true)
         }
         .emoji('◀'),
-        CreateButton::new(format!("list:{}:{}", page_index, page_size))
+        CreateButton::new(custom_id(world_id, page_index, page_size))
             .emoji('🔄')
             .style(ButtonStyle::Success),
         if page_index + 1 < max_page {
-            CreateButton::new(format!("list:{}:{}", page_index + 1, page_size))
+            CreateButton::new(custom_id(world_id, page_index + 1, page_size))
         } else {
             CreateButton::new("0").disabled(true)
         }
@@ -80,3 +142,37 @@ pub async fn list(
         component: vec![buttons],
     })
 }
+
+/// 翻頁按鈕的 custom_id，格式為 `list:世界:頁數:每頁數量`
+fn custom_id(world_id: i32, page_index: u32, page_size: u32) -> String {
+    format!("list:{}:{}:{}", world_id, page_index, page_size)
+}
+
+/// 解析翻頁按鈕的 custom_id，回傳 (世界, 頁數, 每頁數量)
+pub fn parse_custom_id(custom_id: &str) -> Option<(i32, u32, u32)> {
+    let mut parts = custom_id.strip_prefix("list:")?.split(':');
+    Some((
+        parts.next()?.parse().ok()?,
+        parts.next()?.parse().ok()?,
+        parts.next()?.parse().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_id_keeps_world() {
+        assert_eq!(parse_custom_id(&custom_id(3, 1, 10)), Some((3, 1, 10)));
+        assert_eq!(parse_custom_id(&custom_id(0, 0, 25)), Some((0, 0, 25)));
+    }
+
+    #[test]
+    fn custom_id_rejects_other_buttons() {
+        // 舊格式沒有世界編號
+        assert_eq!(parse_custom_id("list:1:10"), None);
+        assert_eq!(parse_custom_id("battle:1:2:0"), None);
+        assert_eq!(parse_custom_id("list:a:1:10"), None);
+    }
+}
@@ -1,19 +1,45 @@
 use anyhow::{Context as _, Error, Result};
-use db::BotDB;
+use db::{BotDB, CommandLog};
 use poise::{
     serenity_prelude::{
-        self as serenity, ClientBuilder, Context, CreateInteractionResponse,
+        self as serenity, ClientBuilder, ComponentInteraction, Context, CreateInteractionResponse,
         CreateInteractionResponseMessage, EventHandler, FullEvent, GatewayIntents, Interaction,
     },
     BoxFuture,
 };
 use shuttle_runtime::{self, async_trait, Error as ShuttleError, SecretStore, Service};
-use std::{net::SocketAddr, num::ParseIntError, ops::DerefMut, sync::Arc};
+use std::{net::SocketAddr, ops::DerefMut, sync::Arc, time::Instant};
 use tokio::sync::Mutex;
+mod api;
+mod audit;
+mod challenge;
 mod commands;
+mod config;
+mod confirm;
 mod db;
+mod due;
+mod events;
+mod freeze;
+mod i18n;
+mod leaderboard;
+mod link;
 mod list;
+mod metrics;
+mod notify;
+mod palworld;
+mod permission;
+mod rcon;
+mod scheduler;
+mod season;
+mod server;
 mod structs;
+mod team;
+mod time;
+mod transfer;
+mod undo;
+mod vacation;
+mod webhook;
+mod world;
 
 type FrameworkContext<'a> = poise::FrameworkContext<'a, BotDB, Error>;
 type FrameworkError<'a> = poise::FrameworkError<'a, BotDB, Error>;
@@ -21,11 +47,15 @@ type PoiseContext<'a> = poise::Context<'a, BotDB, Error>;
 
 struct BotService {
     client: serenity::Client,
+    db: BotDB,
+    /// 存取 /metrics 的金鑰，未設定時不提供 /metrics
+    metrics_token: Option<String>,
 }
 
 #[shuttle_runtime::async_trait]
 impl Service for BotService {
-    async fn bind(mut self, _addr: SocketAddr) -> Result<(), ShuttleError> {
+    async fn bind(mut self, addr: SocketAddr) -> Result<(), ShuttleError> {
+        tokio::spawn(api::serve(addr, self.db, self.metrics_token));
         _ = self.client.start().await;
         Ok(())
     }
@@ -39,17 +69,22 @@ async fn main(
     let db = BotDB::new(pool);
     structs::init(&db).await;
 
+    let api_db = db.clone();
     let discord_bot = poise::Framework::builder()
         .options(poise::FrameworkOptions {
             commands: commands::get_commands(),
             event_handler,
             on_error,
             pre_command: write_log,
+            post_command: observe_command,
             ..Default::default()
         })
         .setup(|ctx, _ready, framework| {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
+                tokio::spawn(scheduler::run(ctx.http.clone(), db.clone()));
+                tokio::spawn(webhook::run(db.clone()));
+                tokio::spawn(server::run(ctx.http.clone(), db.clone()));
                 Ok(db)
             })
         })
@@ -63,41 +98,73 @@ async fn main(
         .await
         .map_err(Error::new)?;
 
-    Ok(BotService { client })
+    Ok(BotService {
+        client,
+        db: api_db,
+        metrics_token: secrets.get("METRICS_TOKEN"),
+    })
 }
 
 struct Handler(BotDB, Arc<Mutex<Result<()>>>);
 
 impl Handler {
-    async fn interaction(&self, ctx: Context, interaction: Interaction) -> Result<()> {
-        let Interaction::Component(c) = interaction else {
+    async fn interaction(&self, ctx: Context, c: &ComponentInteraction) -> Result<()> {
+        metrics::observe_component(&c.data.custom_id);
+
+        if let Some(args) = c.data.custom_id.strip_prefix("battle:") {
+            return challenge::accept_battle(&ctx, &self.0, c, args).await;
+        }
+        if let Some(args) = c.data.custom_id.strip_prefix("result:") {
+            return challenge::report_result(&ctx, &self.0, c, args).await;
+        }
+        if let Some(args) = c.data.custom_id.strip_prefix("transfer:") {
+            return transfer::answer_transfer(&ctx, &self.0, c, args).await;
+        }
+        if c.data.custom_id.starts_with("confirm:") {
+            // 由 confirm::confirm 的收集器處理
             return Ok(());
-        };
-        _ = self
-            .0
-            .write_log(
-                c.guild_id.map(|x| x.get()),
-                c.channel_id.get(),
-                c.user.id.get(),
-                &c.data.custom_id,
+        }
+        if let Some(args) = c.data.custom_id.strip_prefix("rank:") {
+            let (ranking, page_index) =
+                leaderboard::Ranking::parse(args).context("parse custom_id error")?;
+            let guild_id = c.guild_id.context("Unknown guild_id")?.get();
+            let locale = i18n::interaction_locale(&self.0, Some(guild_id), &c.locale).await;
+            let content =
+                leaderboard::leaderboard(&self.0, guild_id, ranking, page_index, locale).await?;
+            c.create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .embed(content.embed)
+                        .components(content.component),
+                ),
             )
-            .await;
-        let (page_index, page_size): (u32, u32) = c
-            .data
-            .custom_id
-            .strip_prefix("list:")
-            .and_then(|x| x.split_once(':'))
-            .and_then(|(a, b)| {
-                (|| -> Result<_, ParseIntError> { Ok((a.parse()?, b.parse()?)) })().ok()
-            })
-            .context("parse custom_id error")?;
-        let content = list::list(
-            &self.0,
-            c.guild_id.context("Unknown guild_id")?.get(),
-            page_index,
-            page_size,
-        )
-        .await?;
+            .await?;
+            return Ok(());
+        }
+        if let Some(args) = c.data.custom_id.strip_prefix("log:") {
+            let (filter, page_index) = audit::parse(args).context("parse custom_id error")?;
+            let guild_id = c.guild_id.context("Unknown guild_id")?.get();
+            let locale = i18n::interaction_locale(&self.0, Some(guild_id), &c.locale).await;
+            let content = audit::logs(&self.0, guild_id, &filter, page_index, locale).await?;
+            c.create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .embed(content.embed)
+                        .components(content.component),
+                ),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        let (world_id, page_index, page_size) =
+            list::parse_custom_id(&c.data.custom_id).context("parse custom_id error")?;
+        let guild_id = c.guild_id.context("Unknown guild_id")?.get();
+        let locale = i18n::interaction_locale(&self.0, Some(guild_id), &c.locale).await;
+        let content =
+            list::list(&self.0, guild_id, world_id, page_index, page_size, locale).await?;
         c.create_response(
             ctx,
             CreateInteractionResponse::UpdateMessage(
@@ -118,35 +185,99 @@ fn event_handler<'a>(
     data: &'a BotDB,
 ) -> BoxFuture<'a, Result<()>> {
     Box::pin(async move {
+        metrics::observe_event(event);
         let handler = Handler(data.clone(), Arc::new(Mutex::const_new(Ok(()))));
         event.clone().dispatch(ctx.clone(), &handler).await;
         std::mem::replace(handler.1.lock_owned().await.deref_mut(), Ok(()))
     })
 }
 
+/// 指令執行時的開始時間與紀錄編號
+struct Invocation {
+    start: Instant,
+    log_id: Option<i64>,
+}
+
 fn on_error(err: FrameworkError<'_>) -> BoxFuture<'_, ()> {
     Box::pin(async move {
+        if let FrameworkError::Command { ctx, error, .. } = &err {
+            metrics::observe_command_error(&ctx.command().qualified_name);
+            let log_id = ctx
+                .invocation_data::<Invocation>()
+                .await
+                .and_then(|invocation| invocation.log_id);
+            if let Some(log_id) = log_id {
+                _ = ctx
+                    .data()
+                    .set_log_result(log_id, Some(&error.to_string()))
+                    .await;
+            }
+        }
         tracing::error!("{err}");
     })
 }
 
 fn write_log(ctx: PoiseContext<'_>) -> BoxFuture<'_, ()> {
     Box::pin(async move {
-        _ = ctx
+        let log_id = ctx
             .data()
-            .write_log(
-                ctx.guild_id().map(|x| x.get()),
-                ctx.channel_id().get(),
-                ctx.author().id.get(),
-                &ctx.invocation_string(),
-            )
-            .await;
+            .write_log(CommandLog {
+                guild_id: ctx.guild_id().map(|x| x.get()),
+                channel_id: ctx.channel_id().get(),
+                user_id: ctx.author().id.get(),
+                content: &ctx.invocation_string(),
+                command: Some(&ctx.command().qualified_name),
+                ore_point_id: audit::command_point_id(ctx),
+            })
+            .await
+            .ok();
+        ctx.set_invocation_data(Invocation {
+            start: Instant::now(),
+            log_id,
+        })
+        .await;
+    })
+}
+fn observe_command(ctx: PoiseContext<'_>) -> BoxFuture<'_, ()> {
+    Box::pin(async move {
+        let Some((start, log_id)) = ctx
+            .invocation_data::<Invocation>()
+            .await
+            .map(|invocation| (invocation.start, invocation.log_id))
+        else {
+            return;
+        };
+        metrics::observe_command(&ctx.command().qualified_name, start.elapsed());
+        if let Some(log_id) = log_id {
+            _ = ctx.data().set_log_result(log_id, None).await;
+        }
     })
 }
+
 #[async_trait]
 impl EventHandler for Handler {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        let result = self.interaction(ctx, interaction).await;
+        let Interaction::Component(c) = interaction else {
+            return;
+        };
+        let log_id = self
+            .0
+            .write_log(CommandLog {
+                guild_id: c.guild_id.map(|x| x.get()),
+                channel_id: c.channel_id.get(),
+                user_id: c.user.id.get(),
+                content: &c.data.custom_id,
+                command: None,
+                ore_point_id: audit::component_point_id(&c),
+            })
+            .await
+            .ok();
+
+        let result = self.interaction(ctx, &c).await;
+        if let Some(log_id) = log_id {
+            let error = result.as_ref().err().map(|err| err.to_string());
+            _ = self.0.set_log_result(log_id, error.as_deref()).await;
+        }
         *self.1.lock().await = result;
     }
 }
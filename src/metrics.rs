@@ -0,0 +1,341 @@
+use crate::{
+    api::{bearer_token, hash_token},
+    db::BotDB,
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use once_cell::sync::Lazy;
+use poise::serenity_prelude::{ConnectionStage, FullEvent};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// 延遲直方圖的區間上限 (秒)
+const BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+static REGISTRY: Lazy<Mutex<Registry>> = Lazy::new(Default::default);
+static GATEWAY_CONNECTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, le) in self.buckets.iter_mut().zip(BUCKETS) {
+            if seconds <= le {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+
+    fn write(&self, output: &mut String, name: &str, label: &str, value: &str) {
+        for (bucket, le) in self.buckets.iter().zip(BUCKETS) {
+            let _ = writeln!(
+                output,
+                "{name}_bucket{{{label}=\"{value}\",le=\"{le}\"}} {bucket}"
+            );
+        }
+        let _ = writeln!(
+            output,
+            "{name}_bucket{{{label}=\"{value}\",le=\"+Inf\"}} {}",
+            self.count
+        );
+        let _ = writeln!(output, "{name}_sum{{{label}=\"{value}\"}} {}", self.sum);
+        let _ = writeln!(output, "{name}_count{{{label}=\"{value}\"}} {}", self.count);
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    commands: BTreeMap<String, Histogram>,
+    command_errors: BTreeMap<String, u64>,
+    components: BTreeMap<String, u64>,
+    queries: BTreeMap<&'static str, Histogram>,
+    query_errors: BTreeMap<&'static str, u64>,
+}
+
+fn registry() -> std::sync::MutexGuard<'static, Registry> {
+    // 統計資料不會因 panic 而失效，忽略 poison
+    REGISTRY.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+pub fn observe_command(command: &str, duration: Duration) {
+    registry()
+        .commands
+        .entry(command.to_string())
+        .or_default()
+        .observe(duration);
+}
+
+pub fn observe_command_error(command: &str) {
+    *registry()
+        .command_errors
+        .entry(command.to_string())
+        .or_default() += 1;
+}
+
+/// 以 custom_id 的前綴記錄元件互動
+pub fn observe_component(custom_id: &str) {
+    let kind = custom_id.split(':').next().unwrap_or_default();
+    *registry().components.entry(kind.to_string()).or_default() += 1;
+}
+
+pub fn observe_db(method: &'static str, duration: Duration, failed: bool) {
+    let mut registry = registry();
+    registry
+        .queries
+        .entry(method)
+        .or_default()
+        .observe(duration);
+    if failed {
+        *registry.query_errors.entry(method).or_default() += 1;
+    }
+}
+
+/// 由 gateway 事件更新連線狀態
+pub fn observe_event(event: &FullEvent) {
+    match event {
+        FullEvent::Ready { .. } | FullEvent::Resume { .. } => {
+            GATEWAY_CONNECTED.store(true, Ordering::Relaxed);
+        }
+        FullEvent::ShardStageUpdate { event } => {
+            GATEWAY_CONNECTED.store(event.new == ConnectionStage::Connected, Ordering::Relaxed);
+        }
+        _ => {}
+    }
+}
+
+fn write_header(output: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(output, "# HELP {name} {help}");
+    let _ = writeln!(output, "# TYPE {name} {kind}");
+}
+
+async fn render(db: &BotDB) -> String {
+    // 先查詢資料庫，避免持有鎖時等待
+    let counts = db.get_occupation_counts().await;
+
+    let mut output = String::new();
+    {
+        let registry = registry();
+
+        write_header(
+            &mut output,
+            "bot_command_duration_seconds",
+            "histogram",
+            "Slash command latency by command name.",
+        );
+        for (command, histogram) in &registry.commands {
+            histogram.write(
+                &mut output,
+                "bot_command_duration_seconds",
+                "command",
+                command,
+            );
+        }
+
+        write_header(
+            &mut output,
+            "bot_command_errors_total",
+            "counter",
+            "Slash command errors by command name.",
+        );
+        for (command, count) in &registry.command_errors {
+            let _ = writeln!(
+                output,
+                "bot_command_errors_total{{command=\"{command}\"}} {count}"
+            );
+        }
+
+        write_header(
+            &mut output,
+            "bot_component_interactions_total",
+            "counter",
+            "Component interactions by custom id prefix.",
+        );
+        for (kind, count) in &registry.components {
+            let _ = writeln!(
+                output,
+                "bot_component_interactions_total{{kind=\"{kind}\"}} {count}"
+            );
+        }
+
+        write_header(
+            &mut output,
+            "bot_db_query_duration_seconds",
+            "histogram",
+            "Database latency by BotDB method.",
+        );
+        for (method, histogram) in &registry.queries {
+            histogram.write(
+                &mut output,
+                "bot_db_query_duration_seconds",
+                "method",
+                method,
+            );
+        }
+
+        write_header(
+            &mut output,
+            "bot_db_query_errors_total",
+            "counter",
+            "Database errors by BotDB method.",
+        );
+        for (method, count) in &registry.query_errors {
+            let _ = writeln!(
+                output,
+                "bot_db_query_errors_total{{method=\"{method}\"}} {count}"
+            );
+        }
+    }
+
+    write_header(
+        &mut output,
+        "bot_gateway_connected",
+        "gauge",
+        "Whether the Discord gateway is connected.",
+    );
+    let _ = writeln!(
+        output,
+        "bot_gateway_connected {}",
+        GATEWAY_CONNECTED.load(Ordering::Relaxed) as u8
+    );
+
+    match counts {
+        Ok(counts) => {
+            write_header(
+                &mut output,
+                "bot_occupied_points",
+                "gauge",
+                "Occupied ore points per guild and world.",
+            );
+            for count in &counts {
+                let _ = writeln!(
+                    output,
+                    "bot_occupied_points{{guild=\"{}\",world=\"{}\"}} {}",
+                    count.guild_id, count.world_id, count.occupied
+                );
+            }
+            write_header(
+                &mut output,
+                "bot_challenged_points",
+                "gauge",
+                "Ore points with a registered challenger per guild and world.",
+            );
+            for count in &counts {
+                let _ = writeln!(
+                    output,
+                    "bot_challenged_points{{guild=\"{}\",world=\"{}\"}} {}",
+                    count.guild_id, count.world_id, count.challenged
+                );
+            }
+        }
+        Err(err) => tracing::error!("{err}"),
+    }
+
+    output
+}
+
+/// 統計資料包含各伺服器的 id，需以 METRICS_TOKEN 存取
+async fn metrics(db: BotDB, headers: HeaderMap, token_hash: Option<String>) -> Response {
+    let authorized = token_hash.is_some_and(|token_hash| {
+        bearer_token(&headers).is_some_and(|token| hash_token(token) == token_hash)
+    });
+    if !authorized {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    render(&db).await.into_response()
+}
+
+/// gateway 已連線且資料庫可查詢時回傳 200
+async fn healthz(State(db): State<BotDB>) -> (StatusCode, &'static str) {
+    if !GATEWAY_CONNECTED.load(Ordering::Relaxed) {
+        return (StatusCode::SERVICE_UNAVAILABLE, "gateway disconnected");
+    }
+    if db.ping().await.is_err() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "database unavailable");
+    }
+    (StatusCode::OK, "ok")
+}
+
+pub fn routes(token: Option<String>) -> Router<BotDB> {
+    // 比對雜湊值，避免逐字比對金鑰
+    let token_hash = token.as_deref().map(hash_token);
+    Router::new()
+        .route(
+            "/metrics",
+            get(move |State(db): State<BotDB>, headers: HeaderMap| {
+                metrics(db, headers, token_hash.clone())
+            }),
+        )
+        .route("/healthz", get(healthz))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Client;
+    use std::net::{SocketAddr, TcpListener};
+
+    fn serve(token: Option<&str>) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = routes(token.map(String::from)).with_state(BotDB::offline());
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        addr
+    }
+
+    async fn get_metrics(addr: SocketAddr, token: Option<&str>) -> reqwest::Response {
+        let request = Client::new().get(format!("http://{addr}/metrics"));
+        match token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+        .send()
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn metrics_requires_token() {
+        let addr = serve(Some("secret"));
+        assert_eq!(get_metrics(addr, None).await.status(), 401);
+        assert_eq!(get_metrics(addr, Some("wrong")).await.status(), 401);
+
+        let response = get_metrics(addr, Some("secret")).await;
+        assert_eq!(response.status(), 200);
+        assert!(response
+            .text()
+            .await
+            .unwrap()
+            .contains("bot_gateway_connected"));
+    }
+
+    #[tokio::test]
+    async fn metrics_disabled_without_token() {
+        let addr = serve(None);
+        assert_eq!(get_metrics(addr, None).await.status(), 401);
+        assert_eq!(get_metrics(addr, Some("")).await.status(), 401);
+    }
+}
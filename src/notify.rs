@@ -0,0 +1,199 @@
+use crate::{
+    commands::Context,
+    db::BotDB,
+    events::{self, EventKind},
+    i18n::{self, tr},
+    structs::{OrePoint, OreType},
+};
+use anyhow::Result;
+use chrono::Utc;
+use poise::serenity_prelude::{ChannelId, CreateAllowedMentions, CreateMessage, Http};
+
+/// 可選擇是否通知的事件
+#[derive(poise::ChoiceParameter, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyEvent {
+    #[name = "新佔領"]
+    Occupy,
+    #[name = "登記挑戰"]
+    Challenge,
+    #[name = "挑戰結果"]
+    Resolve,
+    #[name = "開放挑戰"]
+    Challengeable,
+}
+
+impl NotifyEvent {
+    pub fn all() -> [Self; 4] {
+        [
+            NotifyEvent::Occupy,
+            NotifyEvent::Challenge,
+            NotifyEvent::Resolve,
+            NotifyEvent::Challengeable,
+        ]
+    }
+
+    pub fn bit(self) -> i32 {
+        1 << self as i32
+    }
+
+    /// 事件是否在設定中啟用
+    pub fn enabled(self, events: i32) -> bool {
+        events & self.bit() != 0
+    }
+
+    /// 啟用或停用事件後的設定
+    pub fn toggle(self, events: i32, enabled: bool) -> i32 {
+        if enabled {
+            events | self.bit()
+        } else {
+            events & !self.bit()
+        }
+    }
+}
+
+pub async fn autocomplete_ore_type(_: Context<'_>, partial: &str) -> Vec<String> {
+    OreType::iter()
+        .map(|ore_type| ore_type.name.clone())
+        .filter(|name| name.contains(partial))
+        .collect()
+}
+
+/// 取得礦點需要通知的身分組標記
+pub async fn role_mentions(
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    point: &OrePoint,
+) -> Result<String> {
+    let roles = db
+        .get_notify_roles(guild_id, world_id, point.ore_type)
+        .await?;
+    Ok(roles
+        .into_iter()
+        .map(|role_id| format!("<@&{role_id}>"))
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+/// 事件啟用時取得身分組標記，未設定通知頻道時於指令回覆中通知
+pub async fn reply_mentions(
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    point: &OrePoint,
+    event: NotifyEvent,
+) -> Result<String> {
+    let config = db.get_guild_config(guild_id).await?;
+    if config.notify_channel_id.is_some() || !event.enabled(config.notify_events) {
+        return Ok(String::new());
+    }
+    role_mentions(db, guild_id, world_id, point).await
+}
+
+/// 在通知頻道發送事件通知
+pub async fn announce(
+    http: &Http,
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    point: &OrePoint,
+    event: NotifyEvent,
+    content: &str,
+) {
+    if let Err(err) = send(http, db, guild_id, world_id, point, event, content).await {
+        tracing::error!("{err}");
+    }
+}
+
+async fn send(
+    http: &Http,
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    point: &OrePoint,
+    event: NotifyEvent,
+    content: &str,
+) -> Result<()> {
+    let config = db.get_guild_config(guild_id).await?;
+    let Some(channel_id) = config.notify_channel_id else {
+        return Ok(());
+    };
+    if !event.enabled(config.notify_events) {
+        return Ok(());
+    }
+
+    let mentions = role_mentions(db, guild_id, world_id, point).await?;
+    ChannelId::new(channel_id as u64)
+        .send_message(
+            http,
+            CreateMessage::new()
+                .allowed_mentions(CreateAllowedMentions::new().all_roles(true).all_users(true))
+                .content(format!("{content} {mentions}")),
+        )
+        .await?;
+    Ok(())
+}
+
+/// 通知佔領期限已到、開放挑戰的礦點
+pub async fn run_challengeable(http: &Http, db: &BotDB) -> Result<()> {
+    for item in db.take_challengeable(Utc::now()).await? {
+        events::emit(
+            EventKind::Expired,
+            item.guild_id,
+            item.world_id,
+            item.ore_point_id,
+            item.user_id,
+            None,
+        );
+        let Some(point) = OrePoint::iter().find(|p| p.id == item.ore_point_id) else {
+            continue;
+        };
+        let locale = i18n::guild_locale(db, item.guild_id).await;
+        announce(
+            http,
+            db,
+            item.guild_id,
+            item.world_id,
+            &point,
+            NotifyEvent::Challengeable,
+            &tr!(
+                locale,
+                "notify.challengeable",
+                user = format!("<@{}>", item.user_id),
+                point = point.label(locale)
+            ),
+        )
+        .await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::GuildConfig;
+
+    #[test]
+    fn default_config_notifies_challenges_only() {
+        let events = GuildConfig::default().notify_events;
+        let enabled: Vec<_> = NotifyEvent::all()
+            .into_iter()
+            .filter(|event| event.enabled(events))
+            .collect();
+        assert!(enabled == [NotifyEvent::Challenge]);
+    }
+
+    #[test]
+    fn toggle_changes_one_event() {
+        let events = NotifyEvent::Resolve.toggle(0, true);
+        assert!(NotifyEvent::Resolve.enabled(events));
+        assert!(!NotifyEvent::Occupy.enabled(events));
+
+        let events = NotifyEvent::Occupy.toggle(events, true);
+        let events = NotifyEvent::Resolve.toggle(events, false);
+        assert!(NotifyEvent::Occupy.enabled(events));
+        assert!(!NotifyEvent::Resolve.enabled(events));
+        // 重複停用不影響其他事件
+        assert_eq!(NotifyEvent::Resolve.toggle(events, false), events);
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+
+static CLIENT: Lazy<Client> = Lazy::new(Client::new);
+
+/// 請求的逾時時間
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Palworld 專用伺服器 REST API 的管理帳號名稱
+const ADMIN_USER: &str = "admin";
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Player {
+    pub name: String,
+    pub player_id: String,
+    /// Steam 帳號，格式為 steam_<id>
+    pub user_id: String,
+}
+
+#[derive(Deserialize)]
+struct Players {
+    players: Vec<Player>,
+}
+
+/// Palworld 專用伺服器的 REST API
+pub struct RestClient<'a> {
+    url: &'a str,
+    password: &'a str,
+    timeout: Duration,
+}
+
+impl<'a> RestClient<'a> {
+    pub fn new(url: &'a str, password: &'a str) -> Self {
+        Self {
+            url: url.trim_end_matches('/'),
+            password,
+            timeout: TIMEOUT,
+        }
+    }
+
+    pub async fn players(&self) -> Result<Vec<Player>> {
+        let players: Players = CLIENT
+            .get(format!("{}/v1/api/players", self.url))
+            .basic_auth(ADMIN_USER, Some(self.password))
+            .timeout(self.timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(players.players)
+    }
+
+    pub async fn announce(&self, message: &str) -> Result<()> {
+        CLIENT
+            .post(format!("{}/v1/api/announce", self.url))
+            .basic_auth(ADMIN_USER, Some(self.password))
+            .json(&serde_json::json!({ "message": message }))
+            .timeout(self.timeout)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        http::{HeaderMap, StatusCode},
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::{json, Value};
+    use std::{
+        net::{SocketAddr, TcpListener},
+        sync::{Arc, Mutex},
+    };
+
+    /// `admin:secret` 的 Basic 認證
+    const AUTHORIZATION: &str = "Basic YWRtaW46c2VjcmV0";
+
+    fn authorized(headers: &HeaderMap) -> bool {
+        headers
+            .get("authorization")
+            .is_some_and(|value| value == AUTHORIZATION)
+    }
+
+    /// 模擬專用伺服器的 REST API，記錄收到的公告
+    fn serve(announcements: Arc<Mutex<Vec<Value>>>, delay: Duration) -> SocketAddr {
+        let players = move |headers: HeaderMap| async move {
+            tokio::time::sleep(delay).await;
+            if !authorized(&headers) {
+                return Err(StatusCode::UNAUTHORIZED);
+            }
+            Ok(Json(json!({
+                "players": [{
+                    "name": "Alice",
+                    "playerId": "0123ABCD",
+                    "userId": "steam_76561198000000000",
+                    "ip": "127.0.0.1",
+                    "ping": 12.5,
+                    "level": 30
+                }]
+            })))
+        };
+        let announce = move |headers: HeaderMap, Json(body): Json<Value>| async move {
+            tokio::time::sleep(delay).await;
+            if !authorized(&headers) {
+                return StatusCode::UNAUTHORIZED;
+            }
+            announcements.lock().unwrap().push(body);
+            StatusCode::OK
+        };
+        let app = Router::new()
+            .route("/v1/api/players", get(players))
+            .route("/v1/api/announce", post(announce));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        addr
+    }
+
+    #[tokio::test]
+    async fn players_parses_response() {
+        let addr = serve(Arc::default(), Duration::ZERO);
+        let url = format!("http://{addr}/");
+
+        let players = RestClient::new(&url, "secret").players().await.unwrap();
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].name, "Alice");
+        assert_eq!(players[0].player_id, "0123ABCD");
+        assert_eq!(players[0].user_id, "steam_76561198000000000");
+    }
+
+    #[tokio::test]
+    async fn announce_sends_message() {
+        let announcements = Arc::default();
+        let addr = serve(Arc::clone(&announcements), Duration::ZERO);
+        let url = format!("http://{addr}");
+
+        RestClient::new(&url, "secret")
+            .announce("礦點 42 已被佔領")
+            .await
+            .unwrap();
+        assert_eq!(
+            *announcements.lock().unwrap(),
+            vec![json!({ "message": "礦點 42 已被佔領" })]
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_password_fails() {
+        let announcements = Arc::default();
+        let addr = serve(Arc::clone(&announcements), Duration::ZERO);
+        let url = format!("http://{addr}");
+        let client = RestClient::new(&url, "wrong");
+
+        let err = client.players().await.unwrap_err();
+        let err = err.downcast_ref::<reqwest::Error>().unwrap();
+        assert_eq!(err.status(), Some(reqwest::StatusCode::UNAUTHORIZED));
+
+        assert!(client.announce("hello").await.is_err());
+        assert!(announcements.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn slow_server_times_out() {
+        let addr = serve(Arc::default(), Duration::from_secs(5));
+        let url = format!("http://{addr}");
+        let client = RestClient {
+            timeout: Duration::from_millis(50),
+            ..RestClient::new(&url, "secret")
+        };
+
+        let err = client.players().await.unwrap_err();
+        assert!(err.downcast_ref::<reqwest::Error>().unwrap().is_timeout());
+        let err = client.announce("hello").await.unwrap_err();
+        assert!(err.downcast_ref::<reqwest::Error>().unwrap().is_timeout());
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{
+    commands::Context,
+    db::BotDB,
+    i18n::{self, tr},
+};
+use anyhow::{Context as _, Result};
+use poise::{
+    serenity_prelude::{
+        Channel, Color, CreateEmbed, GuildChannel, Member, Permissions, Role, RoleId,
+    },
+    CreateReply,
+};
+
+/// 機器人的權限等級
+#[derive(poise::ChoiceParameter, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BotRole {
+    #[name = "成員"]
+    Member = 1,
+    #[name = "幹部"]
+    Officer = 2,
+    #[name = "管理員"]
+    Admin = 3,
+}
+
+impl BotRole {
+    fn from_level(level: i32) -> Option<Self> {
+        match level {
+            1 => Some(BotRole::Member),
+            2 => Some(BotRole::Officer),
+            3 => Some(BotRole::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// 取得成員的權限等級，沒有任何權限時回傳 None
+pub async fn member_level(
+    db: &BotDB,
+    guild_id: u64,
+    roles: &[RoleId],
+    permissions: Option<Permissions>,
+) -> Result<Option<BotRole>> {
+    if permissions.is_some_and(|permissions| permissions.manage_guild()) {
+        return Ok(Some(BotRole::Admin));
+    }
+
+    let bot_roles = db.get_bot_roles(guild_id).await?;
+    Ok(role_level(&bot_roles, roles))
+}
+
+/// 依設定的身分組等級取得成員擁有的最高等級
+fn role_level(bot_roles: &[(u64, i32)], roles: &[RoleId]) -> Option<BotRole> {
+    let level = bot_roles
+        .iter()
+        .filter(|(role_id, _)| roles.contains(&RoleId::new(*role_id)))
+        .filter_map(|(_, level)| BotRole::from_level(*level))
+        .max();
+    // 未設定成員身分組時所有人皆為成員
+    let has_member_role = bot_roles
+        .iter()
+        .any(|(_, level)| *level == BotRole::Member as i32);
+    level.or((!has_member_role).then_some(BotRole::Member))
+}
+
+/// 取得互動中成員的權限等級
+pub async fn interaction_level(
+    db: &BotDB,
+    guild_id: u64,
+    member: Option<&Member>,
+) -> Result<Option<BotRole>> {
+    match member {
+        Some(member) => member_level(db, guild_id, &member.roles, member.permissions).await,
+        None => Ok(None),
+    }
+}
+
+/// 取得指令使用者的權限等級
+pub async fn author_level(ctx: Context<'_>) -> Result<Option<BotRole>> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let member = ctx.author_member().await;
+    interaction_level(ctx.data(), guild_id, member.as_deref()).await
+}
+
+/// 檢查成員是否能在目前的頻道使用指令，討論串依所屬的頻道判斷
+async fn in_member_channel(ctx: Context<'_>, guild_id: u64) -> Result<bool> {
+    let channels = ctx.data().get_member_channels(guild_id).await?;
+    let channel_id = ctx.channel_id();
+    if channels.is_empty() || channels.contains(&channel_id.get()) {
+        return Ok(true);
+    }
+    let parent_id = match channel_id.to_channel(ctx).await? {
+        Channel::Guild(channel) => channel.parent_id,
+        _ => None,
+    };
+    Ok(parent_id.is_some_and(|parent_id| channels.contains(&parent_id.get())))
+}
+
+async fn require(ctx: Context<'_>, required: BotRole) -> Result<bool> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let level = author_level(ctx).await?;
+
+    let key = match level {
+        Some(level) if level < required => "permission.denied",
+        None => "permission.denied",
+        // 幹部以上不受頻道限制
+        Some(BotRole::Member) if !in_member_channel(ctx, guild_id).await? => "permission.channel",
+        Some(_) => return Ok(true),
+    };
+    let locale = i18n::locale(ctx).await;
+    ctx.send(
+        CreateReply::default()
+            .reply(true)
+            .ephemeral(true)
+            .content(tr!(locale, key)),
+    )
+    .await?;
+    Ok(false)
+}
+
+pub async fn require_member(ctx: Context<'_>) -> Result<bool> {
+    require(ctx, BotRole::Member).await
+}
+
+pub async fn require_officer(ctx: Context<'_>) -> Result<bool> {
+    require(ctx, BotRole::Officer).await
+}
+
+pub async fn require_admin(ctx: Context<'_>) -> Result<bool> {
+    require(ctx, BotRole::Admin).await
+}
+
+/// 設定機器人權限
+#[poise::command(
+    slash_command,
+    rename = "權限",
+    default_member_permissions = "MANAGE_GUILD",
+    subcommands("set_role", "member_channel", "list_permissions"),
+    subcommand_required
+)]
+pub async fn permission(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 設定身分組的權限等級
+#[poise::command(slash_command, rename = "身分組", ephemeral)]
+async fn set_role(
+    ctx: Context<'_>,
+    #[rename = "身分組"]
+    #[description = "設定的身分組"]
+    role: Role,
+    #[rename = "等級"]
+    #[description = "權限等級，留空則移除身分組的權限"]
+    level: Option<BotRole>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let role_id = role.id.get();
+
+    ctx.data()
+        .set_bot_role(guild_id, role_id, level.map(|level| level as i32))
+        .await?;
+    let locale = i18n::locale(ctx).await;
+    let role = format!("<@&{role_id}>");
+    ctx.reply(match level {
+        Some(level) => tr!(
+            locale,
+            "permission.role_set",
+            role = role,
+            level = i18n::choice(locale, level)
+        ),
+        None => tr!(locale, "permission.role_removed", role = role),
+    })
+    .await?;
+    Ok(())
+}
+
+/// 設定成員可使用指令的頻道
+#[poise::command(slash_command, rename = "成員頻道", ephemeral)]
+async fn member_channel(
+    ctx: Context<'_>,
+    #[rename = "頻道"]
+    #[description = "成員可使用指令的頻道"]
+    #[channel_types("Text", "Forum")]
+    channel: GuildChannel,
+    #[rename = "允許"]
+    #[description = "是否允許成員在此頻道使用指令"]
+    enabled: bool,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let channel_id = channel.id.get();
+
+    ctx.data()
+        .set_member_channel(guild_id, channel_id, enabled)
+        .await?;
+    let locale = i18n::locale(ctx).await;
+    let channel = format!("<#{channel_id}>");
+    ctx.reply(if enabled {
+        tr!(locale, "permission.channel_allowed", channel = channel)
+    } else {
+        tr!(locale, "permission.channel_denied", channel = channel)
+    })
+    .await?;
+    Ok(())
+}
+
+/// 列出權限設定
+#[poise::command(slash_command, rename = "列表", ephemeral)]
+async fn list_permissions(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let db = ctx.data();
+
+    let roles = db.get_bot_roles(guild_id).await?;
+    let channels = db.get_member_channels(guild_id).await?;
+    let locale = i18n::locale(ctx).await;
+
+    let roles = if roles.is_empty() {
+        tr!(locale, "permission.no_roles")
+    } else {
+        roles
+            .iter()
+            .filter_map(|(role_id, level)| {
+                BotRole::from_level(*level)
+                    .map(|level| format!("<@&{role_id}> {}", i18n::choice(locale, level)))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let channels = if channels.is_empty() {
+        tr!(locale, "config.unlimited")
+    } else {
+        channels
+            .iter()
+            .map(|channel_id| format!("<#{channel_id}>"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .color(Color::BLUE)
+                    .title(tr!(locale, "permission.title"))
+                    .field(tr!(locale, "permission.roles"), roles, false)
+                    .field(tr!(locale, "permission.channels"), channels, false),
+            )
+            .reply(true)
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMBER: u64 = 10;
+    const OFFICER: u64 = 20;
+
+    #[test]
+    fn highest_matching_role_wins() {
+        let bot_roles = [(OFFICER, 2), (MEMBER, 1)];
+        let roles = [RoleId::new(MEMBER), RoleId::new(OFFICER)];
+        assert!(role_level(&bot_roles, &roles) == Some(BotRole::Officer));
+        assert!(role_level(&bot_roles, &roles[..1]) == Some(BotRole::Member));
+    }
+
+    #[test]
+    fn member_role_restricts_everyone_else() {
+        let bot_roles = [(OFFICER, 2), (MEMBER, 1)];
+        assert!(role_level(&bot_roles, &[RoleId::new(99)]).is_none());
+    }
+
+    #[test]
+    fn everyone_is_member_without_member_role() {
+        let bot_roles = [(OFFICER, 2)];
+        assert!(role_level(&bot_roles, &[]) == Some(BotRole::Member));
+        assert!(role_level(&[], &[]) == Some(BotRole::Member));
+        assert!(role_level(&bot_roles, &[RoleId::new(OFFICER)]) == Some(BotRole::Officer));
+    }
+}
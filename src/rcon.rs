@@ -0,0 +1,236 @@
+use crate::palworld::Player;
+use anyhow::{bail, Context as _, Result};
+use std::time::Duration;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+};
+
+const SERVERDATA_AUTH: i32 = 3;
+const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+const SERVERDATA_EXECCOMMAND: i32 = 2;
+
+/// 連線與等待回應的時限
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// 封包內容的最大長度
+const MAX_PACKET_SIZE: i32 = 4096;
+
+/// 解析 ShowPlayers 的結果，格式為 name,playeruid,steamid
+pub fn parse_players(output: &str) -> Vec<Player> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut parts = line.rsplitn(3, ',');
+            let steam_id = parts.next()?.trim();
+            let player_id = parts.next()?.trim();
+            let name = parts.next()?.trim();
+            Some(Player {
+                name: name.to_string(),
+                player_id: player_id.to_string(),
+                user_id: format!("steam_{steam_id}"),
+            })
+        })
+        .collect()
+}
+
+struct Packet {
+    id: i32,
+    kind: i32,
+    body: String,
+}
+
+/// Source RCON 協定的客戶端
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl RconClient {
+    /// 連線並以密碼登入
+    pub async fn connect(address: &str, password: &str) -> Result<Self> {
+        let stream = timeout(TIMEOUT, TcpStream::connect(address))
+            .await
+            .context("RCON 連線逾時")??;
+        let mut client = Self { stream, next_id: 1 };
+
+        let id = client.send(SERVERDATA_AUTH, password).await?;
+        // 部分伺服器會先回傳一個空的回應封包
+        loop {
+            let packet = client.receive().await?;
+            if packet.kind != SERVERDATA_AUTH_RESPONSE {
+                continue;
+            }
+            if packet.id == -1 || packet.id != id {
+                bail!("RCON 密碼錯誤");
+            }
+            return Ok(client);
+        }
+    }
+
+    /// 執行指令並回傳結果
+    pub async fn execute(&mut self, command: &str) -> Result<String> {
+        let id = self.send(SERVERDATA_EXECCOMMAND, command).await?;
+        loop {
+            let packet = self.receive().await?;
+            if packet.id == id {
+                return Ok(packet.body);
+            }
+        }
+    }
+
+    async fn send(&mut self, kind: i32, body: &str) -> Result<i32> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        // 長度不含自身，包含 id、類型與兩個結尾的 0
+        let size = body.len() as i32 + 10;
+        let mut packet = Vec::with_capacity(size as usize + 4);
+        packet.extend_from_slice(&size.to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+
+        timeout(TIMEOUT, self.stream.write_all(&packet))
+            .await
+            .context("RCON 傳送逾時")??;
+        Ok(id)
+    }
+
+    async fn receive(&mut self) -> Result<Packet> {
+        timeout(TIMEOUT, async {
+            let size = self.stream.read_i32_le().await?;
+            if !(10..=MAX_PACKET_SIZE + 10).contains(&size) {
+                bail!("RCON 封包長度錯誤: {size}");
+            }
+            let id = self.stream.read_i32_le().await?;
+            let kind = self.stream.read_i32_le().await?;
+            let mut body = vec![0; size as usize - 8];
+            self.stream.read_exact(&mut body).await?;
+            body.truncate(body.len() - 2);
+
+            Ok(Packet {
+                id,
+                kind,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            })
+        })
+        .await
+        .context("RCON 回應逾時")?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    const SERVERDATA_RESPONSE_VALUE: i32 = 0;
+    const PLAYERS: &str = "name,playeruid,steamid\nAlice,12345678,76561198000000001\nBob, the Builder,9abcdef0,76561198000000002\n";
+
+    /// 收到的封包 (id, 類型, 內容)
+    type Received = Arc<Mutex<Vec<(i32, i32, String)>>>;
+
+    async fn read_packet(stream: &mut TcpStream) -> Option<(i32, i32, String)> {
+        let size = stream.read_i32_le().await.ok()?;
+        let id = stream.read_i32_le().await.ok()?;
+        let kind = stream.read_i32_le().await.ok()?;
+        let mut body = vec![0; size as usize - 8];
+        stream.read_exact(&mut body).await.ok()?;
+        // 內容後方必須有兩個結尾的 0
+        assert_eq!(body.split_off(body.len() - 2), [0, 0]);
+        Some((id, kind, String::from_utf8(body).unwrap()))
+    }
+
+    async fn write_packet(stream: &mut TcpStream, id: i32, kind: i32, body: &str) {
+        let mut packet = Vec::new();
+        packet.extend_from_slice(&(body.len() as i32 + 10).to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+        stream.write_all(&packet).await.unwrap();
+    }
+
+    /// 模擬 Palworld 的 RCON 伺服器
+    async fn serve(password: &'static str) -> (String, Received) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let received = Received::default();
+
+        let packets = Arc::clone(&received);
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            while let Some((id, kind, body)) = read_packet(&mut stream).await {
+                packets.lock().unwrap().push((id, kind, body.clone()));
+                if kind == SERVERDATA_AUTH {
+                    write_packet(&mut stream, id, SERVERDATA_RESPONSE_VALUE, "").await;
+                    let id = if body == password { id } else { -1 };
+                    write_packet(&mut stream, id, SERVERDATA_AUTH_RESPONSE, "").await;
+                    continue;
+                }
+                let response = match body.split_once(' ') {
+                    Some(("Broadcast", message)) => format!("Broadcasted: {message}"),
+                    _ if body == "ShowPlayers" => String::from(PLAYERS),
+                    _ => format!("Unknown command: {body}"),
+                };
+                write_packet(&mut stream, id, SERVERDATA_RESPONSE_VALUE, &response).await;
+            }
+        });
+        (address, received)
+    }
+
+    #[tokio::test]
+    async fn connect_sends_auth_packet() {
+        let (address, received) = serve("secret").await;
+
+        RconClient::connect(&address, "secret").await.unwrap();
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![(1, SERVERDATA_AUTH, String::from("secret"))]
+        );
+    }
+
+    #[tokio::test]
+    async fn connect_rejects_wrong_password() {
+        let (address, _) = serve("secret").await;
+
+        let err = RconClient::connect(&address, "wrong").await.err().unwrap();
+        assert_eq!(err.to_string(), "RCON 密碼錯誤");
+    }
+
+    #[tokio::test]
+    async fn execute_broadcast_and_show_players() {
+        let (address, received) = serve("secret").await;
+        let mut client = RconClient::connect(&address, "secret").await.unwrap();
+
+        let output = client.execute("Broadcast 礦點_42_已被佔領").await.unwrap();
+        assert_eq!(output, "Broadcasted: 礦點_42_已被佔領");
+        let output = client.execute("ShowPlayers").await.unwrap();
+        assert_eq!(output, PLAYERS);
+
+        assert_eq!(
+            received.lock().unwrap()[1..],
+            [
+                (
+                    2,
+                    SERVERDATA_EXECCOMMAND,
+                    String::from("Broadcast 礦點_42_已被佔領")
+                ),
+                (3, SERVERDATA_EXECCOMMAND, String::from("ShowPlayers")),
+            ]
+        );
+
+        let players = parse_players(&output);
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[0].name, "Alice");
+        assert_eq!(players[0].player_id, "12345678");
+        assert_eq!(players[0].user_id, "steam_76561198000000001");
+        // 名稱中的逗號不影響解析
+        assert_eq!(players[1].name, "Bob, the Builder");
+        assert_eq!(players[1].player_id, "9abcdef0");
+    }
+}
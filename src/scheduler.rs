@@ -0,0 +1,29 @@
+use crate::{audit, challenge, db::BotDB, notify, season};
+use anyhow::Result;
+use poise::serenity_prelude::Http;
+use std::{sync::Arc, time::Duration};
+
+/// 每分鐘執行一次的背景工作
+pub async fn run(http: Arc<Http>, db: BotDB) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60));
+    loop {
+        interval.tick().await;
+        tick(&http, &db).await;
+    }
+}
+
+/// 記錄工作的錯誤，不影響其他工作
+fn log_error(job: &str, result: Result<()>) {
+    if let Err(err) = result {
+        tracing::error!("{job}: {err}");
+    }
+}
+
+async fn tick(http: &Http, db: &BotDB) {
+    log_error("season schedules", season::run_schedules(http, db).await);
+    log_error("forfeits", challenge::run_forfeits(http, db).await);
+    log_error("reminders", challenge::run_reminders(http, db).await);
+    log_error("promotions", challenge::run_promotions(http, db).await);
+    log_error("challengeable", notify::run_challengeable(http, db).await);
+    log_error("purge logs", audit::run_purge(db).await);
+}
@@ -0,0 +1,349 @@
+use crate::{
+    challenge,
+    commands::Context,
+    confirm,
+    db::{BotDB, SeasonSchedule},
+    i18n::{self, tr, Locale},
+    permission::{require_admin, require_member},
+    structs::OrePoint,
+    time::parse_time,
+    world::{autocomplete_world, resolve_world, world_name},
+};
+use anyhow::{Context as _, Error, Result};
+use chrono::Utc;
+use poise::{
+    serenity_prelude::{ChannelId, Color, CreateEmbed, CreateMessage, Http},
+    CreateReply,
+};
+use std::{collections::HashMap, fmt::Write};
+
+/// 開始新賽季或管理排定的新賽季
+#[poise::command(
+    slash_command,
+    rename = "新賽季",
+    check = "require_admin",
+    subcommands("start", "show_schedule", "cancel_schedule"),
+    subcommand_required
+)]
+pub async fn new_season(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 立即或於排定的時間開始新賽季
+#[poise::command(slash_command, rename = "開始")]
+async fn start(
+    ctx: Context<'_>,
+    #[rename = "開始時間"]
+    #[description = "排定的開始時間 (YYYY-MM-DD HH:MM)，留空則立即開始"]
+    start_time: Option<String>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+    let Some(name) = world_name(ctx, world_id).await? else {
+        return Ok(());
+    };
+
+    if let Some(start_time) = start_time {
+        let Some(start_time) = parse_time(&start_time) else {
+            ctx.send(
+                CreateReply::default()
+                    .reply(true)
+                    .ephemeral(true)
+                    .content(tr!(locale, "time.invalid")),
+            )
+            .await?;
+            return Ok(());
+        };
+
+        if start_time > Utc::now() {
+            let embed = confirm::embed(
+                tr!(locale, "confirm.schedule_season"),
+                tr!(
+                    locale,
+                    "confirm.schedule_season.description",
+                    world = name,
+                    time = start_time.timestamp()
+                ),
+            );
+            if !confirm::confirm(ctx, embed).await? {
+                return Ok(());
+            }
+
+            // 排定開始時間
+            db.schedule_season(SeasonSchedule {
+                guild_id,
+                world_id,
+                channel_id: ctx.channel_id().get(),
+                start_time,
+            })
+            .await?;
+            ctx.reply(tr!(
+                locale,
+                "season.scheduled",
+                world = name,
+                time = start_time.timestamp()
+            ))
+            .await?;
+            return Ok(());
+        }
+    }
+
+    let embed = confirm::embed(
+        tr!(locale, "confirm.new_season"),
+        tr!(locale, "confirm.new_season.description", world = name),
+    );
+    if !confirm::confirm(ctx, embed).await? {
+        return Ok(());
+    }
+
+    let (number, threads) = db.start_season(guild_id, world_id, Utc::now()).await?;
+    ctx.reply(tr!(
+        locale,
+        "season.started_world",
+        world = name,
+        number = number
+    ))
+    .await?;
+    close_threads(ctx.http(), db, guild_id, threads).await;
+    Ok(())
+}
+
+/// 查看排定的新賽季
+#[poise::command(slash_command, rename = "排程", ephemeral)]
+async fn show_schedule(
+    ctx: Context<'_>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let locale = i18n::locale(ctx).await;
+    let Some(name) = world_name(ctx, world_id).await? else {
+        return Ok(());
+    };
+
+    let content = match ctx.data().get_season_schedule(guild_id, world_id).await? {
+        Some(schedule) => tr!(
+            locale,
+            "season.scheduled",
+            world = name,
+            time = schedule.start_time.timestamp()
+        ),
+        None => tr!(locale, "season.no_schedule", world = name),
+    };
+    ctx.send(
+        CreateReply::default()
+            .reply(true)
+            .ephemeral(true)
+            .content(content),
+    )
+    .await?;
+    Ok(())
+}
+
+/// 取消排定的新賽季
+#[poise::command(slash_command, rename = "取消排程")]
+async fn cancel_schedule(
+    ctx: Context<'_>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let locale = i18n::locale(ctx).await;
+    let Some(name) = world_name(ctx, world_id).await? else {
+        return Ok(());
+    };
+
+    if ctx
+        .data()
+        .cancel_season_schedule(guild_id, world_id)
+        .await?
+    {
+        ctx.reply(tr!(locale, "season.schedule_cancelled", world = name))
+            .await?;
+    } else {
+        ctx.send(
+            CreateReply::default()
+                .reply(true)
+                .ephemeral(true)
+                .content(tr!(locale, "season.no_schedule", world = name)),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// 關閉新賽季開始前仍在進行的挑戰討論串
+async fn close_threads(http: &Http, db: &BotDB, guild_id: u64, threads: Vec<u64>) {
+    if threads.is_empty() {
+        return;
+    }
+    let locale = i18n::guild_locale(db, guild_id).await;
+    for thread_id in threads {
+        challenge::close_thread(http, thread_id, Some(tr!(locale, "season.thread_closed"))).await;
+    }
+}
+
+/// 查看過往賽季的最終排名
+#[poise::command(
+    slash_command,
+    rename = "賽季紀錄",
+    check = "require_member",
+    ephemeral
+)]
+pub async fn season_history(
+    ctx: Context<'_>,
+    #[min = 1]
+    #[rename = "賽季"]
+    #[description = "賽季編號，留空則列出所有賽季"]
+    number: Option<i32>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+
+    let embed = match number {
+        Some(number) => standings(db, locale, guild_id, world_id, number).await?,
+        None => seasons(db, locale, guild_id, world_id).await?,
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .embed(embed)
+            .reply(true)
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn seasons(
+    db: &BotDB,
+    locale: Locale,
+    guild_id: u64,
+    world_id: i32,
+) -> Result<CreateEmbed, Error> {
+    let seasons = db.get_seasons(guild_id, world_id).await?;
+
+    let mut embed = CreateEmbed::new()
+        .color(Color::BLUE)
+        .title(tr!(locale, "season.list"));
+    if seasons.is_empty() {
+        embed = embed.description(tr!(locale, "season.empty"));
+    }
+
+    for season in seasons.into_iter().take(25) {
+        embed = embed.field(
+            tr!(locale, "season.number", number = season.number),
+            match season.end_time {
+                Some(end_time) => format!(
+                    "<t:{}:F> ~ <t:{}:F>",
+                    season.start_time.timestamp(),
+                    end_time.timestamp()
+                ),
+                None => format!(
+                    "<t:{}:F> ~ {}",
+                    season.start_time.timestamp(),
+                    tr!(locale, "season.ongoing")
+                ),
+            },
+            false,
+        );
+    }
+
+    Ok(embed)
+}
+
+async fn standings(
+    db: &BotDB,
+    locale: Locale,
+    guild_id: u64,
+    world_id: i32,
+    number: i32,
+) -> Result<CreateEmbed, Error> {
+    let archive = db.get_season_archive(guild_id, world_id, number).await?;
+
+    // 依佔領者整理礦點
+    let mut players: HashMap<u64, Vec<OrePoint>> = HashMap::new();
+    for (ore_point_id, user_id) in archive {
+        if let Some(point) = OrePoint::iter().find(|p| p.id == ore_point_id) {
+            players.entry(user_id).or_default().push(point);
+        }
+    }
+
+    let mut players: Vec<_> = players.into_iter().collect();
+    players.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(&b.0)));
+
+    let mut description = String::new();
+    for (rank, (user_id, points)) in players.iter().enumerate() {
+        let _ = writeln!(
+            description,
+            "`{:>2}.` <@{}> {} {}",
+            rank + 1,
+            user_id,
+            tr!(locale, "season.point_count", count = points.len()),
+            points.iter().map(|p| p.emoji()).collect::<String>()
+        );
+    }
+    if description.is_empty() {
+        description.push_str(&tr!(locale, "season.no_archive"));
+    }
+
+    Ok(CreateEmbed::new()
+        .color(Color::BLUE)
+        .title(tr!(locale, "season.standings", number = number))
+        .description(description))
+}
+
+/// 開始所有到期的排定賽季
+pub async fn run_schedules(http: &Http, db: &BotDB) -> Result<()> {
+    for schedule in db.get_due_season_schedules(Utc::now()).await? {
+        // 單一排程失敗時不影響其他排程
+        let (number, threads) = match db
+            .start_season(schedule.guild_id, schedule.world_id, schedule.start_time)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                tracing::error!("{err}");
+                continue;
+            }
+        };
+        let locale = i18n::guild_locale(db, schedule.guild_id).await;
+        let result = ChannelId::new(schedule.channel_id)
+            .send_message(
+                http,
+                CreateMessage::new().content(tr!(locale, "season.started", number = number)),
+            )
+            .await;
+        if let Err(err) = result {
+            tracing::error!("{err}");
+        }
+        close_threads(http, db, schedule.guild_id, threads).await;
+    }
+    Ok(())
+}
@@ -0,0 +1,433 @@
+use crate::{
+    commands::Context,
+    confirm,
+    db::{BotDB, GameServerConfig, PlayerLink},
+    events::{self, EventKind, OccupyEvent},
+    i18n::{self, tr},
+    palworld::{Player, RestClient},
+    permission::require_admin,
+    rcon::{parse_players, RconClient},
+    structs::OrePoint,
+    world::{autocomplete_world, resolve_world},
+};
+use anyhow::{Context as _, Result};
+use poise::{
+    serenity_prelude::{Color, CreateEmbed, Http, UserId},
+    CreateReply,
+};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+/// 登記挑戰時的在線檢查結果
+pub enum OnlineCheck {
+    /// 未設定伺服器或未要求在線
+    Skipped,
+    Online,
+    Offline,
+    NotLinked,
+}
+
+fn rest_client(config: &GameServerConfig) -> Option<RestClient<'_>> {
+    Some(RestClient::new(
+        config.rest_url.as_deref()?,
+        config.rest_password.as_deref()?,
+    ))
+}
+
+async fn rcon_client(config: &GameServerConfig) -> Option<Result<RconClient>> {
+    let address = config.rcon_address.as_deref()?;
+    let password = config.rcon_password.as_deref()?;
+    Some(RconClient::connect(address, password).await)
+}
+
+/// 取得線上玩家，優先使用 REST API，未設定時使用 RCON
+async fn players(config: &GameServerConfig) -> Option<Result<Vec<Player>>> {
+    if let Some(client) = rest_client(config) {
+        return Some(client.players().await);
+    }
+    let client = rcon_client(config).await?;
+    Some(
+        async {
+            let output = client?.execute("ShowPlayers").await?;
+            Ok(parse_players(&output))
+        }
+        .await,
+    )
+}
+
+/// 在遊戲內公告，優先使用 REST API，未設定時使用 RCON
+async fn announce_message(config: &GameServerConfig, message: &str) -> Option<Result<()>> {
+    if let Some(client) = rest_client(config) {
+        return Some(client.announce(message).await);
+    }
+    let client = rcon_client(config).await?;
+    Some(
+        async {
+            // RCON 的 Broadcast 只會顯示第一個空白前的文字
+            client?
+                .execute(&format!("Broadcast {}", message.replace(' ', "_")))
+                .await?;
+            Ok(())
+        }
+        .await,
+    )
+}
+
+fn is_player(link: &PlayerLink, player: &Player) -> bool {
+    if let Some(player_uid) = &link.player_uid {
+        return player.player_id.eq_ignore_ascii_case(player_uid);
+    }
+    if let Some(steam_id) = &link.steam_id {
+        return player.user_id.trim_start_matches("steam_")
+            == steam_id.trim_start_matches("steam_");
+    }
+    player.name == link.character_name
+}
+
+/// 確認玩家綁定的角色是否在遊戲伺服器上
+pub async fn check_online(
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    user_id: u64,
+) -> Result<OnlineCheck> {
+    let Some(config) = db
+        .get_game_server(guild_id, world_id)
+        .await?
+        .filter(|config| config.require_online)
+    else {
+        return Ok(OnlineCheck::Skipped);
+    };
+    // 未經驗證的角色可能是其他人的角色
+    let Some(link) = db
+        .get_player_link(guild_id, world_id, user_id)
+        .await?
+        .filter(|link| link.verified)
+    else {
+        return Ok(OnlineCheck::NotLinked);
+    };
+    let Some(players) = players(&config).await else {
+        return Ok(OnlineCheck::Skipped);
+    };
+
+    let players = players?;
+    Ok(if players.iter().any(|player| is_player(&link, player)) {
+        OnlineCheck::Online
+    } else {
+        OnlineCheck::Offline
+    })
+}
+
+/// 取得玩家在遊戲內的名稱，未驗證角色時使用 Discord 名稱
+async fn player_name(
+    http: &Http,
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    user_id: u64,
+) -> String {
+    if let Ok(Some(link)) = db.get_player_link(guild_id, world_id, user_id).await {
+        if link.verified {
+            return link.character_name;
+        }
+    }
+    match UserId::new(user_id).to_user(http).await {
+        Ok(user) => user.name,
+        Err(_) => user_id.to_string(),
+    }
+}
+
+async fn announcement(http: &Http, db: &BotDB, event: &OccupyEvent) -> Option<String> {
+    let point = OrePoint::iter().find(|p| p.id == event.ore_point_id)?;
+    let key = match event.event {
+        EventKind::Occupied | EventKind::Forced => "server.occupied",
+        EventKind::Released => "server.released",
+        EventKind::Challenged => "server.challenged",
+        EventKind::Resolved => "server.resolved",
+        EventKind::Transferred => "server.transferred",
+        EventKind::Expired | EventKind::Reverted => return None,
+    };
+    let locale = i18n::guild_locale(db, event.guild_id).await;
+    // 遊戲內無法顯示 emoji
+    let point = format!(
+        "{} ({}, {})",
+        point.localized_name(locale),
+        point.x,
+        point.y
+    );
+    let name = player_name(http, db, event.guild_id, event.world_id, event.user_id).await;
+    let target = match event.target_user_id {
+        Some(user_id) => player_name(http, db, event.guild_id, event.world_id, user_id).await,
+        None => String::new(),
+    };
+
+    Some(tr!(
+        locale,
+        key,
+        name = name,
+        target = target,
+        point = point
+    ))
+}
+
+async fn broadcast(http: &Http, db: &BotDB, event: &OccupyEvent) -> Result<()> {
+    let Some(config) = db.get_game_server(event.guild_id, event.world_id).await? else {
+        return Ok(());
+    };
+    if config.rest_url.is_none() && config.rcon_address.is_none() {
+        return Ok(());
+    }
+    let Some(message) = announcement(http, db, event).await else {
+        return Ok(());
+    };
+    announce_message(&config, &message).await.unwrap_or(Ok(()))
+}
+
+/// 將挑戰與佔領變更公告至遊戲伺服器
+pub async fn run(http: Arc<Http>, db: BotDB) {
+    let mut receiver = events::subscribe();
+    loop {
+        let event = match receiver.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(count)) => {
+                tracing::warn!("server broadcast skipped {count} events");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        if let Err(err) = broadcast(&http, &db, &event).await {
+            tracing::error!("{err}");
+        }
+    }
+}
+
+/// 管理遊戲伺服器連線
+#[poise::command(
+    slash_command,
+    rename = "伺服器",
+    check = "require_admin",
+    subcommands(
+        "connect_rest",
+        "connect_rcon",
+        "disconnect",
+        "online_players",
+        "announce"
+    ),
+    subcommand_required
+)]
+pub async fn server(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 設定 Palworld 專用伺服器的 REST API
+#[poise::command(slash_command, rename = "連線", ephemeral)]
+async fn connect_rest(
+    ctx: Context<'_>,
+    #[max_length = 2048]
+    #[rename = "網址"]
+    #[description = "REST API 網址，例如 http://127.0.0.1:8212"]
+    url: String,
+    #[max_length = 255]
+    #[rename = "密碼"]
+    #[description = "伺服器的 AdminPassword"]
+    password: String,
+    #[rename = "需要在線"]
+    #[description = "登記挑戰時是否需要綁定的角色在線上"]
+    require_online: Option<bool>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    ctx.defer_ephemeral().await?;
+
+    let locale = i18n::locale(ctx).await;
+
+    // 確認可以連線
+    if let Err(err) = RestClient::new(&url, &password).players().await {
+        ctx.reply(tr!(locale, "server.connect_failed", error = err))
+            .await?;
+        return Ok(());
+    }
+
+    ctx.data()
+        .set_rest_server(
+            guild_id,
+            world_id,
+            &url,
+            &password,
+            require_online.unwrap_or(false),
+        )
+        .await?;
+    ctx.reply(tr!(locale, "server.connected")).await?;
+    Ok(())
+}
+
+/// 設定舊版伺服器的 RCON 連線
+#[poise::command(slash_command, rename = "rcon連線", ephemeral)]
+async fn connect_rcon(
+    ctx: Context<'_>,
+    #[max_length = 255]
+    #[rename = "位址"]
+    #[description = "RCON 位址，例如 127.0.0.1:25575"]
+    address: String,
+    #[max_length = 255]
+    #[rename = "密碼"]
+    #[description = "伺服器的 AdminPassword"]
+    password: String,
+    #[rename = "需要在線"]
+    #[description = "登記挑戰時是否需要綁定的角色在線上"]
+    require_online: Option<bool>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    ctx.defer_ephemeral().await?;
+
+    let locale = i18n::locale(ctx).await;
+
+    // 確認可以登入
+    if let Err(err) = RconClient::connect(&address, &password).await {
+        ctx.reply(tr!(locale, "server.connect_failed", error = err))
+            .await?;
+        return Ok(());
+    }
+
+    ctx.data()
+        .set_rcon_server(
+            guild_id,
+            world_id,
+            &address,
+            &password,
+            require_online.unwrap_or(false),
+        )
+        .await?;
+    ctx.reply(tr!(locale, "server.connected")).await?;
+    Ok(())
+}
+
+/// 移除遊戲伺服器設定
+#[poise::command(slash_command, rename = "移除", ephemeral)]
+async fn disconnect(
+    ctx: Context<'_>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let locale = i18n::locale(ctx).await;
+
+    let embed = confirm::embed(
+        tr!(locale, "confirm.disconnect"),
+        tr!(locale, "confirm.disconnect.description"),
+    );
+    if !confirm::confirm(ctx, embed).await? {
+        return Ok(());
+    }
+
+    if ctx.data().delete_game_server(guild_id, world_id).await? {
+        ctx.reply(tr!(locale, "server.removed")).await?;
+    } else {
+        ctx.reply(tr!(locale, "server.not_configured")).await?;
+    }
+    Ok(())
+}
+
+/// 列出遊戲伺服器上的玩家
+#[poise::command(slash_command, rename = "線上玩家", ephemeral)]
+async fn online_players(
+    ctx: Context<'_>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    ctx.defer_ephemeral().await?;
+    let locale = i18n::locale(ctx).await;
+
+    // 未設定伺服器或沒有 REST API 與 RCON 時無法查詢
+    let Some(config) = ctx.data().get_game_server(guild_id, world_id).await? else {
+        ctx.reply(tr!(locale, "server.not_configured")).await?;
+        return Ok(());
+    };
+    let Some(players) = players(&config).await else {
+        ctx.reply(tr!(locale, "server.not_configured")).await?;
+        return Ok(());
+    };
+    let players = players?;
+
+    let description = if players.is_empty() {
+        tr!(locale, "server.no_players")
+    } else {
+        players
+            .iter()
+            .map(|player| format!("{} `{}`", player.name, player.player_id))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .color(Color::BLUE)
+                    .title(tr!(locale, "server.players", count = players.len()))
+                    .description(description),
+            )
+            .reply(true)
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+/// 在遊戲伺服器發送公告
+#[poise::command(slash_command, rename = "公告", ephemeral)]
+async fn announce(
+    ctx: Context<'_>,
+    #[max_length = 200]
+    #[rename = "訊息"]
+    #[description = "公告內容"]
+    message: String,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    ctx.defer_ephemeral().await?;
+    let locale = i18n::locale(ctx).await;
+
+    let Some(config) = ctx.data().get_game_server(guild_id, world_id).await? else {
+        ctx.reply(tr!(locale, "server.not_configured")).await?;
+        return Ok(());
+    };
+    let Some(result) = announce_message(&config, &message).await else {
+        ctx.reply(tr!(locale, "server.not_configured")).await?;
+        return Ok(());
+    };
+    result?;
+
+    ctx.reply(tr!(locale, "server.announced")).await?;
+    Ok(())
+}
@@ -1,14 +1,20 @@
-use crate::db::BotDB;
+use crate::{
+    db::BotDB,
+    i18n::{tr, Locale},
+};
 use chrono::{DateTime, Utc};
 use once_cell::sync::OnceCell;
 use sqlx::prelude::FromRow;
-use std::fmt::Write;
+use std::{collections::HashMap, fmt::Write};
 
 #[derive(PartialEq, Eq, Clone, Debug, FromRow)]
 pub(crate) struct OreType {
     pub id: i32,
     pub name: String,
     pub emoji: String,
+    /// 各語言的名稱
+    #[sqlx(skip)]
+    pub names: HashMap<String, String>,
 }
 
 #[derive(Clone, Debug, FromRow)]
@@ -18,6 +24,9 @@ pub(crate) struct OrePoint {
     pub x: i32,
     pub y: i32,
     pub name: String,
+    /// 各語言的名稱
+    #[sqlx(skip)]
+    pub names: HashMap<String, String>,
 }
 
 pub struct ListResult {
@@ -29,14 +38,65 @@ pub struct ListResult {
     pub user_id: Option<u64>,
     pub due_time: Option<DateTime<Utc>>,
     pub battle_user_id: Option<u64>,
+    pub battle_time: Option<DateTime<Utc>>,
+    pub shield_until: Option<DateTime<Utc>>,
+    /// 佔領者休假的結束時間
+    pub leave_until: Option<DateTime<Utc>>,
+    pub team_name: Option<String>,
+    pub battle_team_name: Option<String>,
+    pub queue_count: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HistoryAction {
+    Occupy,
+    Challenge,
+    ForceOccupy,
+    ChallengeWon,
+    Withdraw,
+    Release,
+    AdjustDue,
+    Transfer,
+    Revert,
+}
+
+impl HistoryAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryAction::Occupy => "occupy",
+            HistoryAction::Challenge => "challenge",
+            HistoryAction::ForceOccupy => "force_occupy",
+            HistoryAction::ChallengeWon => "challenge_won",
+            HistoryAction::Withdraw => "withdraw",
+            HistoryAction::Release => "release",
+            HistoryAction::AdjustDue => "adjust_due",
+            HistoryAction::Transfer => "transfer",
+            HistoryAction::Revert => "revert",
+        }
+    }
 }
 
 static ORE_POINTS: OnceCell<Vec<OrePoint>> = OnceCell::new();
 static ORE_TYPES: OnceCell<Vec<OreType>> = OnceCell::new();
 
+/// 將翻譯資料表中的名稱加入礦點與礦種
+fn translations(
+    rows: &[(String, i32, String, String)],
+    category: &str,
+    id: i32,
+) -> HashMap<String, String> {
+    rows.iter()
+        .filter(|row| row.0 == category && row.1 == id)
+        .map(|row| (row.2.clone(), row.3.clone()))
+        .collect()
+}
+
 impl OrePoint {
-    async fn init(db: &BotDB) {
-        let data = db.get_ore_points().await.expect("Cannot get ORE_POINTS");
+    async fn init(db: &BotDB, rows: &[(String, i32, String, String)]) {
+        let mut data = db.get_ore_points().await.expect("Cannot get ORE_POINTS");
+        for point in &mut data {
+            point.names = translations(rows, "ore_point", point.id);
+        }
         ORE_POINTS
             .set(data)
             .expect("ORE_POINTS set more than once.");
@@ -50,6 +110,22 @@ impl OrePoint {
             .cloned()
     }
 
+    pub fn localized_name(&self, locale: Locale) -> &str {
+        self.names.get(locale.code()).unwrap_or(&self.name)
+    }
+
+    /// 礦點的圖示、名稱與座標
+    pub fn label(&self, locale: Locale) -> String {
+        tr!(
+            locale,
+            "point",
+            emoji = self.emoji(),
+            name = self.localized_name(locale),
+            x = self.x,
+            y = self.y
+        )
+    }
+
     pub fn emoji(&self) -> String {
         OreType::iter()
             .filter(|ore_type| (ore_type.id & self.ore_type) != 0)
@@ -61,17 +137,33 @@ impl OrePoint {
 }
 
 impl OreType {
-    async fn init(db: &BotDB) {
-        let data = db.get_ore_types().await.expect("Cannot get ORE_TYPES");
+    async fn init(db: &BotDB, rows: &[(String, i32, String, String)]) {
+        let mut data = db.get_ore_types().await.expect("Cannot get ORE_TYPES");
+        for ore_type in &mut data {
+            ore_type.names = translations(rows, "ore_type", ore_type.id);
+        }
         ORE_TYPES.set(data).expect("ORE_TYPES set more than once.");
     }
 
+    pub fn localized_name(&self, locale: Locale) -> &str {
+        self.names.get(locale.code()).unwrap_or(&self.name)
+    }
+
     pub fn iter() -> impl Iterator<Item = &'static Self> {
         ORE_TYPES.get().expect("ORE_TYPES not initialize").iter()
     }
 }
 
 impl ListResult {
+    /// 依語言取得礦點名稱
+    pub fn localized_name(&self, locale: Locale) -> &str {
+        ORE_POINTS
+            .get()
+            .and_then(|points| points.iter().find(|point| point.id == self.id))
+            .and_then(|point| point.names.get(locale.code()))
+            .unwrap_or(&self.name)
+    }
+
     pub fn emoji(&self) -> String {
         OreType::iter()
             .filter(|ore_type| (ore_type.id & self.ore_type) != 0)
@@ -83,6 +175,10 @@ impl ListResult {
 }
 
 pub async fn init(db: &BotDB) {
-    OreType::init(db).await;
-    OrePoint::init(db).await;
+    let rows = db
+        .get_translations()
+        .await
+        .expect("Cannot get translations");
+    OreType::init(db, &rows).await;
+    OrePoint::init(db, &rows).await;
 }
@@ -0,0 +1,190 @@
+use crate::{
+    commands::Context,
+    confirm,
+    db::{BotDB, TeamData},
+    i18n::{self, tr},
+    permission::require_admin,
+};
+use anyhow::{Context as _, Result};
+use poise::{
+    serenity_prelude::{Color, CreateEmbed, Role, RoleId, UserId},
+    CreateReply,
+};
+
+/// 取得玩家所屬的隊伍
+pub async fn member_team(ctx: Context<'_>, user_id: u64) -> Result<Option<i32>> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?;
+    let member = guild_id.member(ctx, UserId::new(user_id)).await?;
+
+    roles_team(ctx.data(), guild_id.get(), &member.roles).await
+}
+
+/// 依身分組取得所屬的隊伍
+pub async fn roles_team(db: &BotDB, guild_id: u64, roles: &[RoleId]) -> Result<Option<i32>> {
+    let teams = db.get_teams(guild_id).await?;
+    Ok(team_for_roles(&teams, roles))
+}
+
+/// 取得第一個對應到身分組的隊伍
+fn team_for_roles(teams: &[TeamData], roles: &[RoleId]) -> Option<i32> {
+    teams
+        .iter()
+        .find(|team| roles.iter().any(|role| role.get() == team.role_id))
+        .map(|team| team.id)
+}
+
+pub async fn autocomplete_team(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Vec::new();
+    };
+    ctx.data()
+        .get_teams(guild_id.get())
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|team| team.name)
+        .filter(|name| name.contains(partial))
+        .collect()
+}
+
+/// 管理遊戲內的公會隊伍
+#[poise::command(
+    slash_command,
+    rename = "隊伍",
+    check = "require_admin",
+    subcommands("create_team", "delete_team", "list_teams"),
+    subcommand_required
+)]
+pub async fn team(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 新增隊伍並對應至身分組
+#[poise::command(slash_command, rename = "新增", ephemeral)]
+async fn create_team(
+    ctx: Context<'_>,
+    #[max_length = 255]
+    #[rename = "名稱"]
+    #[description = "隊伍名稱"]
+    name: String,
+    #[rename = "身分組"]
+    #[description = "隊伍成員的身分組"]
+    role: Role,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+
+    let teams = db.get_teams(guild_id).await?;
+    if teams
+        .iter()
+        .any(|team| team.name == name || team.role_id == role.id.get())
+    {
+        ctx.reply(tr!(locale, "team.exists")).await?;
+        return Ok(());
+    }
+
+    db.create_team(guild_id, &name, role.id.get()).await?;
+    ctx.reply(tr!(
+        locale,
+        "team.created",
+        name = name,
+        role = format!("<@&{}>", role.id.get())
+    ))
+    .await?;
+    Ok(())
+}
+
+/// 刪除隊伍
+#[poise::command(slash_command, rename = "刪除", ephemeral)]
+async fn delete_team(
+    ctx: Context<'_>,
+    #[rename = "名稱"]
+    #[description = "隊伍名稱"]
+    #[autocomplete = "autocomplete_team"]
+    name: String,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let db = ctx.data();
+
+    let team = db
+        .get_teams(guild_id)
+        .await?
+        .into_iter()
+        .find(|team| team.name == name)
+        .context("找不到隊伍")?;
+
+    let locale = i18n::locale(ctx).await;
+    let embed = confirm::embed(
+        tr!(locale, "confirm.delete_team"),
+        tr!(locale, "confirm.delete_team.description", name = name),
+    );
+    if !confirm::confirm(ctx, embed).await? {
+        return Ok(());
+    }
+
+    db.delete_team(guild_id, team.id).await?;
+    ctx.reply(tr!(locale, "team.deleted", name = name)).await?;
+    Ok(())
+}
+
+/// 列出所有隊伍
+#[poise::command(slash_command, rename = "列表", ephemeral)]
+async fn list_teams(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let teams = ctx.data().get_teams(guild_id).await?;
+    let locale = i18n::locale(ctx).await;
+
+    let description = if teams.is_empty() {
+        tr!(locale, "team.empty")
+    } else {
+        teams
+            .iter()
+            .map(|team| format!("{} <@&{}>", team.name, team.role_id))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .color(Color::BLUE)
+                    .title(tr!(locale, "team.list"))
+                    .description(description),
+            )
+            .reply(true)
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn teams() -> Vec<TeamData> {
+        [(1, "紅隊", 100), (2, "藍隊", 200)]
+            .into_iter()
+            .map(|(id, name, role_id)| TeamData {
+                id,
+                name: name.to_string(),
+                role_id,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn team_follows_roles() {
+        assert_eq!(team_for_roles(&teams(), &[RoleId::new(200)]), Some(2));
+        assert_eq!(team_for_roles(&teams(), &[RoleId::new(300)]), None);
+        assert_eq!(team_for_roles(&[], &[RoleId::new(100)]), None);
+    }
+
+    #[test]
+    fn first_team_wins_with_several_roles() {
+        let roles = [RoleId::new(200), RoleId::new(100)];
+        assert_eq!(team_for_roles(&teams(), &roles), Some(1));
+    }
+}
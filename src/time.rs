@@ -0,0 +1,15 @@
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// 伺服器使用的時區 (UTC+8)
+fn timezone() -> FixedOffset {
+    FixedOffset::east_opt(8 * 3600).expect("Invalid timezone offset")
+}
+
+/// 解析 `YYYY-MM-DD HH:MM` 格式的時間
+pub fn parse_time(input: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(input.trim(), TIME_FORMAT).ok()?;
+    let time = timezone().from_local_datetime(&naive).single()?;
+    Some(time.with_timezone(&Utc))
+}
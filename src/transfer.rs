@@ -0,0 +1,339 @@
+use crate::{
+    challenge::exceeds_type_limit,
+    commands::Context,
+    db::{BotDB, HistoryData},
+    events::{self, EventKind},
+    i18n::{self, tr},
+    permission::{require_member, require_officer},
+    structs::{HistoryAction, OrePoint},
+    team::roles_team,
+    world::{autocomplete_world, resolve_world},
+};
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Days, Duration, Utc};
+use poise::{
+    serenity_prelude::{
+        ButtonStyle, ComponentInteraction, Context as SerenityContext, CreateActionRow,
+        CreateAllowedMentions, CreateButton, CreateInteractionResponse,
+        CreateInteractionResponseMessage, User,
+    },
+    CreateReply,
+};
+
+/// 轉讓請求的有效時間
+const TRANSFER_TIMEOUT_HOURS: i64 = 24;
+
+/// 將佔領的礦點轉讓給其他玩家，需由對方接受
+#[poise::command(slash_command, rename = "轉讓", check = "require_member")]
+pub async fn transfer(
+    ctx: Context<'_>,
+    #[rename = "礦點"]
+    #[description = "轉讓的礦點編號"]
+    point_id: i32,
+    #[rename = "玩家"]
+    #[description = "接手礦點的玩家"]
+    recipient: User,
+    #[rename = "保留到期時間"]
+    #[description = "保留原本的到期時間，預設保留，重新計算佔領期限需幹部權限"]
+    keep_due_time: Option<bool>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let user_id = ctx.author().id.get();
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+
+    let point = OrePoint::iter()
+        .find(|p| p.id == point_id)
+        .context("找不到礦點")?;
+
+    let reply = |content: String| {
+        CreateReply::default()
+            .reply(true)
+            .ephemeral(true)
+            .content(content)
+    };
+
+    if recipient.bot || recipient.id == ctx.author().id {
+        ctx.send(reply(tr!(locale, "transfer.invalid_recipient")))
+            .await?;
+        return Ok(());
+    }
+    let Some(data) = db.get_occupy_data(guild_id, world_id, point.id).await? else {
+        ctx.send(reply(tr!(locale, "release.not_occupied"))).await?;
+        return Ok(());
+    };
+    if data.user_id != user_id {
+        ctx.send(reply(tr!(locale, "transfer.not_owner"))).await?;
+        return Ok(());
+    }
+    if data.battle_user_id.is_some() {
+        ctx.send(reply(tr!(locale, "transfer.in_challenge")))
+            .await?;
+        return Ok(());
+    }
+    // 避免隊友間互相轉讓來無限延長佔領期限
+    let keep_due_time = keep_due_time.unwrap_or(true);
+    if !keep_due_time && !require_officer(ctx).await? {
+        return Ok(());
+    }
+
+    let expires_at = (Utc::now() + Duration::hours(TRANSFER_TIMEOUT_HOURS)).timestamp();
+    let custom_id = |answer: &str| {
+        format!(
+            "transfer:{}:{}:{}:{}:{}:{}:{}",
+            world_id,
+            point.id,
+            user_id,
+            recipient.id,
+            u8::from(keep_due_time),
+            expires_at,
+            answer
+        )
+    };
+    // 轉讓請求由接手者回應，使用伺服器預設的語言
+    let guild_locale = i18n::guild_locale(db, guild_id).await;
+    ctx.send(
+        CreateReply::default()
+            .content(tr!(
+                guild_locale,
+                "transfer.request",
+                from = format!("<@{user_id}>"),
+                to = format!("<@{}>", recipient.id),
+                point = point.label(guild_locale),
+                expires = expires_at
+            ))
+            .allowed_mentions(CreateAllowedMentions::new().users([recipient.id]))
+            .components(vec![CreateActionRow::Buttons(vec![
+                CreateButton::new(custom_id("accept"))
+                    .label(tr!(guild_locale, "transfer.accept"))
+                    .style(ButtonStyle::Success),
+                CreateButton::new(custom_id("decline"))
+                    .label(tr!(guild_locale, "transfer.decline"))
+                    .style(ButtonStyle::Secondary),
+            ])]),
+    )
+    .await?;
+    Ok(())
+}
+
+/// 處理轉讓的接受與拒絕按鈕，custom_id 格式為 `transfer:世界:礦點:轉讓者:接手者:保留到期時間:失效時間:回應`
+pub async fn answer_transfer(
+    ctx: &SerenityContext,
+    db: &BotDB,
+    interaction: &ComponentInteraction,
+    args: &str,
+) -> Result<()> {
+    let guild_id = interaction.guild_id.context("Unknown guild_id")?.get();
+    let (world_id, ore_point_id, from_user_id, to_user_id, keep_due_time, expires_at, answer): (
+        i32,
+        i32,
+        u64,
+        u64,
+        u8,
+        i64,
+        &str,
+    ) = (|| {
+        let mut parts = args.split(':');
+        Some((
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+            parts.next()?,
+        ))
+    })()
+    .context("parse custom_id error")?;
+
+    let point = OrePoint::iter()
+        .find(|p| p.id == ore_point_id)
+        .context("找不到礦點")?;
+
+    let locale = i18n::interaction_locale(db, Some(guild_id), &interaction.locale).await;
+    let guild_locale = i18n::guild_locale(db, guild_id).await;
+
+    let reply = |content: String| {
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .ephemeral(true)
+                .content(content),
+        )
+    };
+    let update = |content: String| {
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(Vec::new()),
+        )
+    };
+
+    // 逾時的請求不能再接受，按下任一按鈕都會移除按鈕
+    if Utc::now().timestamp() > expires_at {
+        interaction
+            .create_response(ctx, update(tr!(guild_locale, "transfer.expired")))
+            .await?;
+        return Ok(());
+    }
+
+    let user_id = interaction.user.id.get();
+    if answer != "accept" {
+        // 轉讓者可撤回，接手者可拒絕
+        if user_id != from_user_id && user_id != to_user_id {
+            interaction
+                .create_response(ctx, reply(tr!(locale, "transfer.parties_only")))
+                .await?;
+            return Ok(());
+        }
+        interaction
+            .create_response(
+                ctx,
+                update(tr!(
+                    guild_locale,
+                    "transfer.cancelled",
+                    point = point.label(guild_locale)
+                )),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    if user_id != to_user_id {
+        interaction
+            .create_response(ctx, reply(tr!(locale, "transfer.recipient_only")))
+            .await?;
+        return Ok(());
+    }
+
+    let Some(data) = db
+        .get_occupy_data(guild_id, world_id, ore_point_id)
+        .await?
+        .filter(|data| data.user_id == from_user_id && data.battle_user_id.is_none())
+    else {
+        interaction
+            .create_response(ctx, update(tr!(guild_locale, "transfer.expired")))
+            .await?;
+        return Ok(());
+    };
+
+    let roles = interaction
+        .member
+        .as_ref()
+        .map(|member| member.roles.as_slice())
+        .unwrap_or_default();
+    let team_id = roles_team(db, guild_id, roles).await?;
+    let limit_team_id = limit_team(team_id, data.team_id);
+    if exceeds_type_limit(
+        db,
+        guild_id,
+        world_id,
+        to_user_id,
+        limit_team_id,
+        point.ore_type,
+    )
+    .await?
+    {
+        interaction
+            .create_response(ctx, reply(tr!(locale, "occupy.type_limit")))
+            .await?;
+        return Ok(());
+    }
+
+    let due_time = transfer_due_time(data.due_time, keep_due_time != 0, Utc::now())?;
+    if !db
+        .transfer_occupy(&data, to_user_id, team_id, due_time)
+        .await?
+    {
+        interaction
+            .create_response(ctx, update(tr!(guild_locale, "transfer.expired")))
+            .await?;
+        return Ok(());
+    }
+    db.leave_queue(guild_id, world_id, ore_point_id, to_user_id)
+        .await?;
+    db.add_history(HistoryData {
+        guild_id,
+        world_id,
+        team_id,
+        ore_point_id,
+        user_id: to_user_id,
+        target_user_id: Some(from_user_id),
+        action: HistoryAction::Transfer,
+    })
+    .await?;
+    events::emit(
+        EventKind::Transferred,
+        guild_id,
+        world_id,
+        ore_point_id,
+        to_user_id,
+        Some(from_user_id),
+    );
+
+    interaction
+        .create_response(
+            ctx,
+            update(tr!(
+                guild_locale,
+                "transfer.transferred",
+                from = format!("<@{from_user_id}>"),
+                to = format!("<@{to_user_id}>"),
+                point = point.label(guild_locale),
+                time = due_time.timestamp()
+            )),
+        )
+        .await?;
+    Ok(())
+}
+
+/// 接手者需檢查礦種上限的隊伍，同隊伍的轉讓不會改變隊伍佔領的礦種，只需確認接手者本身
+pub fn limit_team(team_id: Option<i32>, owner_team_id: Option<i32>) -> Option<i32> {
+    team_id.filter(|team_id| owner_team_id != Some(*team_id))
+}
+
+/// 轉讓後的到期時間，不保留時重新計算 14 天
+fn transfer_due_time(
+    due_time: DateTime<Utc>,
+    keep_due_time: bool,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>> {
+    if keep_due_time {
+        return Ok(due_time);
+    }
+    now.checked_add_days(Days::new(14))
+        .context("Failed to add days")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn same_team_transfer_skips_team_limit() {
+        assert_eq!(limit_team(Some(1), Some(1)), None);
+        assert_eq!(limit_team(Some(2), Some(1)), Some(2));
+        assert_eq!(limit_team(Some(2), None), Some(2));
+        assert_eq!(limit_team(None, Some(1)), None);
+    }
+
+    #[test]
+    fn repeated_transfers_keep_due_time() {
+        // 來回轉讓不會重置到期時間
+        let due_time = transfer_due_time(day(3), true, day(2)).unwrap();
+        let due_time = transfer_due_time(due_time, true, day(2)).unwrap();
+        assert_eq!(due_time, day(3));
+        assert_eq!(transfer_due_time(day(3), false, day(2)).unwrap(), day(16));
+    }
+}
@@ -0,0 +1,360 @@
+use crate::{
+    commands::Context,
+    confirm,
+    db::{AdminAction, AdminActionData, BotDB, HistoryData, OccupyData},
+    events::{self, EventKind},
+    i18n::{self, tr, Locale},
+    permission::require_officer,
+    structs::{HistoryAction, OrePoint},
+};
+use anyhow::{Context as _, Result};
+use poise::{
+    serenity_prelude::{AutocompleteChoice, Color, CreateEmbed},
+    CreateReply,
+};
+
+/// 列出的管理操作數量
+const ACTION_LIMIT: u32 = 10;
+
+/// 記錄管理操作，`previous` 為操作前的佔領資料
+pub async fn record(
+    db: &BotDB,
+    guild_id: u64,
+    world_id: i32,
+    ore_point_id: i32,
+    user_id: u64,
+    action: HistoryAction,
+    previous: Option<OccupyData>,
+) -> Result<()> {
+    let current = db.get_occupy_data(guild_id, world_id, ore_point_id).await?;
+    db.add_admin_action(AdminActionData {
+        guild_id,
+        world_id,
+        ore_point_id,
+        user_id,
+        action,
+        previous,
+        current,
+    })
+    .await?;
+    Ok(())
+}
+
+fn action_name(action: &str, locale: Locale) -> String {
+    let key = if action == HistoryAction::ForceOccupy.as_str() {
+        "undo.force_occupy"
+    } else if action == HistoryAction::Release.as_str() {
+        "undo.release"
+    } else if action == HistoryAction::ChallengeWon.as_str() {
+        "undo.challenge_won"
+    } else if action == HistoryAction::AdjustDue.as_str() {
+        "undo.adjust_due"
+    } else {
+        return action.to_string();
+    };
+    tr!(locale, key)
+}
+
+fn point_label(ore_point_id: i32, locale: Locale) -> String {
+    OrePoint::iter()
+        .find(|p| p.id == ore_point_id)
+        .map_or(ore_point_id.to_string(), |point| point.label(locale))
+}
+
+fn owner(user_id: Option<u64>, locale: Locale) -> String {
+    user_id.map_or(tr!(locale, "undo.nobody"), |user_id| {
+        format!("<@{user_id}>")
+    })
+}
+
+fn describe(action: &AdminAction, locale: Locale) -> String {
+    format!(
+        "`#{}` <t:{}:R> <@{}> {} {}：{} → {}{}",
+        action.id,
+        action.time.timestamp(),
+        action.user_id,
+        action_name(&action.action, locale),
+        point_label(action.ore_point_id, locale),
+        owner(
+            action.previous.as_ref().map(|previous| previous.user_id),
+            locale
+        ),
+        owner(action.current_user_id, locale),
+        if action.reverted {
+            format!(" ({})", tr!(locale, "undo.reverted"))
+        } else {
+            String::new()
+        }
+    )
+}
+
+async fn autocomplete_action(ctx: Context<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Vec::new();
+    };
+    let locale = i18n::locale(ctx).await;
+    ctx.data()
+        .get_admin_actions(guild_id.get(), 25)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|action| !action.reverted && action.season_id.is_none())
+        .map(|action| {
+            let name = format!(
+                "#{} {} {}",
+                action.id,
+                action_name(&action.action, locale),
+                point_label(action.ore_point_id, locale)
+            );
+            (name, action.id)
+        })
+        .filter(|(name, _)| name.contains(partial))
+        .map(|(name, id)| AutocompleteChoice::new(name, id))
+        .collect()
+}
+
+/// 復原管理操作
+#[poise::command(slash_command, rename = "復原", check = "require_officer", ephemeral)]
+pub async fn undo(
+    ctx: Context<'_>,
+    #[rename = "操作"]
+    #[description = "復原的操作，留空則列出最近的管理操作"]
+    #[autocomplete = "autocomplete_action"]
+    action_id: Option<i32>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+
+    let Some(action_id) = action_id else {
+        let actions = db.get_admin_actions(guild_id, ACTION_LIMIT).await?;
+        let description = if actions.is_empty() {
+            tr!(locale, "undo.empty")
+        } else {
+            actions
+                .iter()
+                .map(|action| describe(action, locale))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .color(Color::DARK_GREY)
+                        .title(tr!(locale, "undo.title"))
+                        .description(description),
+                )
+                .reply(true)
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let Some(action) = db.get_admin_action(guild_id, action_id).await? else {
+        ctx.reply(tr!(locale, "undo.not_found")).await?;
+        return Ok(());
+    };
+    if action.reverted {
+        ctx.reply(tr!(locale, "undo.already_reverted")).await?;
+        return Ok(());
+    }
+    if action.season_id.is_some() {
+        ctx.reply(tr!(locale, "undo.past_season")).await?;
+        return Ok(());
+    }
+
+    let embed = confirm::embed(
+        tr!(locale, "confirm.undo"),
+        tr!(
+            locale,
+            "confirm.undo.description",
+            action = describe(&action, locale)
+        ),
+    );
+    if !confirm::confirm(ctx, embed).await? {
+        return Ok(());
+    }
+
+    ctx.reply(if revert(db, &action).await? {
+        tr!(locale, "undo.done")
+    } else {
+        tr!(locale, "undo.changed")
+    })
+    .await?;
+    Ok(())
+}
+
+/// 礦點是否仍是管理操作後的狀態
+pub fn unchanged(action: &AdminAction, current: Option<&OccupyData>) -> bool {
+    match current {
+        Some(current) => {
+            action.current_user_id == Some(current.user_id)
+                && action.current_due_time == Some(current.due_time)
+                && action.current_battle_user_id == current.battle_user_id
+        }
+        None => action.current_user_id.is_none(),
+    }
+}
+
+/// 復原後寫入的佔領紀錄與事件，操作前後礦點皆無人佔領時回傳 None
+///
+/// 還原的佔領者記錄為復原，操作產生的佔領者記錄為釋放
+fn revert_history(action: &AdminAction) -> Option<(HistoryData, EventKind)> {
+    match &action.previous {
+        Some(previous) => Some((
+            HistoryData {
+                guild_id: action.guild_id,
+                world_id: action.world_id,
+                team_id: previous.team_id,
+                ore_point_id: action.ore_point_id,
+                user_id: previous.user_id,
+                target_user_id: action.current_user_id,
+                action: HistoryAction::Revert,
+            },
+            EventKind::Reverted,
+        )),
+        None => action.current_user_id.map(|user_id| {
+            (
+                HistoryData {
+                    guild_id: action.guild_id,
+                    world_id: action.world_id,
+                    team_id: None,
+                    ore_point_id: action.ore_point_id,
+                    user_id,
+                    target_user_id: None,
+                    action: HistoryAction::Release,
+                },
+                EventKind::Released,
+            )
+        }),
+    }
+}
+
+/// 還原管理操作並記錄佔領紀錄，礦點在操作後已有變動時回傳 false
+///
+/// 操作產生的紀錄不再計入排行榜與冷卻
+async fn revert(db: &BotDB, action: &AdminAction) -> Result<bool> {
+    if !db.revert_admin_action(action).await? {
+        return Ok(false);
+    }
+
+    if let Some((history, kind)) = revert_history(action) {
+        let (user_id, target_user_id) = (history.user_id, history.target_user_id);
+        db.add_history(history).await?;
+        events::emit(
+            kind,
+            action.guild_id,
+            action.world_id,
+            action.ore_point_id,
+            user_id,
+            target_user_id,
+        );
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn day(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap()
+    }
+
+    /// 玩家 10 的礦點被強制佔領給玩家 20
+    fn force_occupy() -> AdminAction {
+        AdminAction {
+            id: 1,
+            guild_id: 1,
+            world_id: 0,
+            season_id: None,
+            ore_point_id: 1,
+            user_id: 99,
+            action: HistoryAction::ForceOccupy.as_str().to_string(),
+            previous: Some(OccupyData::fixture(day(3))),
+            current_user_id: Some(20),
+            current_due_time: Some(day(15)),
+            current_battle_user_id: None,
+            reverted: false,
+            time: day(1),
+        }
+    }
+
+    /// 玩家 10 的礦點被幹部釋放
+    fn release() -> AdminAction {
+        AdminAction {
+            action: HistoryAction::Release.as_str().to_string(),
+            current_user_id: None,
+            current_due_time: None,
+            ..force_occupy()
+        }
+    }
+
+    #[test]
+    fn unchanged_point_can_be_reverted() {
+        let current = OccupyData {
+            user_id: 20,
+            ..OccupyData::fixture(day(15))
+        };
+        assert!(unchanged(&force_occupy(), Some(&current)));
+        assert!(unchanged(&release(), None));
+    }
+
+    #[test]
+    fn changed_point_is_not_reverted() {
+        let action = force_occupy();
+        // 之後被其他玩家佔領、調整到期時間或登記挑戰
+        for current in [
+            OccupyData {
+                user_id: 30,
+                ..OccupyData::fixture(day(15))
+            },
+            OccupyData {
+                user_id: 20,
+                ..OccupyData::fixture(day(16))
+            },
+            OccupyData {
+                user_id: 20,
+                battle_user_id: Some(30),
+                ..OccupyData::fixture(day(15))
+            },
+        ] {
+            assert!(!unchanged(&action, Some(&current)));
+        }
+        assert!(!unchanged(&action, None));
+        // 釋放後又被佔領
+        assert!(!unchanged(&release(), Some(&OccupyData::fixture(day(15)))));
+    }
+
+    #[test]
+    fn revert_restores_previous_owner() {
+        let (history, kind) = revert_history(&force_occupy()).unwrap();
+        assert!(matches!(kind, EventKind::Reverted));
+        assert_eq!(history.action, HistoryAction::Revert);
+        assert_eq!(history.user_id, 10);
+        assert_eq!(history.target_user_id, Some(20));
+    }
+
+    #[test]
+    fn revert_of_occupying_empty_point_releases_it() {
+        let action = AdminAction {
+            previous: None,
+            ..force_occupy()
+        };
+        let (history, kind) = revert_history(&action).unwrap();
+        assert!(matches!(kind, EventKind::Released));
+        assert_eq!(history.action, HistoryAction::Release);
+        assert_eq!(history.user_id, 20);
+        assert_eq!(history.target_user_id, None);
+
+        let action = AdminAction {
+            previous: None,
+            current_user_id: None,
+            ..force_occupy()
+        };
+        assert!(revert_history(&action).is_none());
+    }
+}
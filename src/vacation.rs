@@ -0,0 +1,288 @@
+use crate::{
+    challenge::exceeds_type_limit,
+    commands::Context,
+    db::{HistoryData, OccupyData, VacationData},
+    events::{self, EventKind},
+    i18n::{self, tr},
+    permission::require_member,
+    structs::{HistoryAction, OrePoint},
+    team::member_team,
+    time::parse_time,
+    transfer::limit_team,
+    world::{autocomplete_world, resolve_world},
+};
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Days, Utc};
+use poise::serenity_prelude::User;
+
+/// 休假期間保護或交出佔領的礦點
+#[poise::command(
+    slash_command,
+    rename = "休假",
+    check = "require_member",
+    subcommands("start_vacation", "end_vacation"),
+    subcommand_required
+)]
+pub async fn vacation(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 開始休假，指定代理人時將礦點交給代理人，否則在休假期間保護礦點
+#[poise::command(slash_command, rename = "開始", ephemeral)]
+async fn start_vacation(
+    ctx: Context<'_>,
+    #[rename = "結束時間"]
+    #[description = "休假結束的時間 (YYYY-MM-DD HH:MM)"]
+    end_time: String,
+    #[rename = "代理人"]
+    #[description = "接手礦點的隊友，留空則在休假期間保護礦點"]
+    delegate: Option<User>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let user_id = ctx.author().id.get();
+    let db = ctx.data();
+    let now = Utc::now();
+    let locale = i18n::locale(ctx).await;
+
+    let Some(end_time) = parse_time(&end_time) else {
+        ctx.reply(tr!(locale, "time.invalid")).await?;
+        return Ok(());
+    };
+    if end_time <= now {
+        ctx.reply(tr!(locale, "vacation.end_passed")).await?;
+        return Ok(());
+    }
+
+    let team_id = match &delegate {
+        Some(delegate) => {
+            if delegate.bot || delegate.id == ctx.author().id {
+                ctx.reply(tr!(locale, "vacation.invalid_delegate")).await?;
+                return Ok(());
+            }
+            let team_id = member_team(ctx, user_id).await?;
+            if team_id.is_none() || member_team(ctx, delegate.id.get()).await? != team_id {
+                ctx.reply(tr!(locale, "vacation.not_teammate")).await?;
+                return Ok(());
+            }
+            team_id
+        }
+        None => {
+            let Some(max_days) = db.get_guild_config(guild_id).await?.vacation_max_days else {
+                ctx.reply(tr!(locale, "vacation.no_protection")).await?;
+                return Ok(());
+            };
+            // 避免重複休假無限延長保護
+            if db
+                .get_vacation(guild_id, world_id, user_id, now)
+                .await?
+                .is_some_and(|vacation| protects(&vacation, now))
+            {
+                ctx.reply(tr!(locale, "vacation.already_protected")).await?;
+                return Ok(());
+            }
+            // 挑戰中的礦點需先完成挑戰，避免休假期間被判定棄權
+            if in_challenge(&db.get_occupations(guild_id, world_id).await?, user_id) {
+                ctx.reply(tr!(locale, "vacation.in_challenge")).await?;
+                return Ok(());
+            }
+            if exceeds_max_days(end_time, now, max_days)? {
+                ctx.reply(tr!(locale, "vacation.max_days", days = max_days))
+                    .await?;
+                return Ok(());
+            }
+            None
+        }
+    };
+
+    db.set_vacation(VacationData {
+        guild_id,
+        world_id,
+        user_id,
+        end_time,
+        delegate_user_id: delegate.as_ref().map(|delegate| delegate.id.get()),
+    })
+    .await?;
+
+    let Some(delegate) = delegate else {
+        ctx.reply(tr!(
+            locale,
+            "vacation.protected",
+            time = end_time.timestamp()
+        ))
+        .await?;
+        return Ok(());
+    };
+
+    // 將佔領中的礦點交給代理人，保留原本的到期時間
+    let delegate_id = delegate.id.get();
+    let mut count = 0;
+    let mut skipped = Vec::new();
+    for data in db.get_occupations(guild_id, world_id).await? {
+        if data.user_id != user_id {
+            continue;
+        }
+        let Some(point) = OrePoint::iter().find(|p| p.id == data.ore_point_id) else {
+            continue;
+        };
+        // 與轉讓相同，挑戰中或代理人已達上限的礦點不交出
+        let limit_team_id = limit_team(team_id, data.team_id);
+        if data.battle_user_id.is_some()
+            || exceeds_type_limit(
+                db,
+                guild_id,
+                world_id,
+                delegate_id,
+                limit_team_id,
+                point.ore_type,
+            )
+            .await?
+            || !db
+                .transfer_occupy(&data, delegate_id, team_id, data.due_time)
+                .await?
+        {
+            skipped.push(point.label(locale));
+            continue;
+        }
+        db.add_history(HistoryData {
+            guild_id,
+            world_id,
+            team_id,
+            ore_point_id: data.ore_point_id,
+            user_id: delegate_id,
+            target_user_id: Some(user_id),
+            action: HistoryAction::Transfer,
+        })
+        .await?;
+        events::emit(
+            EventKind::Transferred,
+            guild_id,
+            world_id,
+            data.ore_point_id,
+            delegate_id,
+            Some(user_id),
+        );
+        count += 1;
+    }
+
+    let mut content = tr!(
+        locale,
+        "vacation.delegated",
+        time = end_time.timestamp(),
+        count = count,
+        delegate = format!("<@{delegate_id}>")
+    );
+    if !skipped.is_empty() {
+        content.push('\n');
+        content.push_str(&tr!(
+            locale,
+            "vacation.skipped",
+            points = skipped.join(&tr!(locale, "list.separator"))
+        ));
+    }
+    ctx.reply(content).await?;
+    Ok(())
+}
+
+/// 休假是否在此時保護佔領者的礦點
+pub fn protects(vacation: &VacationData, now: DateTime<Utc>) -> bool {
+    vacation.delegate_user_id.is_none() && vacation.end_time > now
+}
+
+/// 玩家佔領的礦點是否有進行中的挑戰
+fn in_challenge(occupations: &[OccupyData], user_id: u64) -> bool {
+    occupations
+        .iter()
+        .any(|data| data.user_id == user_id && data.battle_user_id.is_some())
+}
+
+/// 休假結束時間是否超過保護天數上限
+fn exceeds_max_days(end_time: DateTime<Utc>, now: DateTime<Utc>, max_days: i32) -> Result<bool> {
+    let max_end_time = now
+        .checked_add_days(Days::new(max_days as u64))
+        .context("Failed to add days")?;
+    Ok(end_time > max_end_time)
+}
+
+/// 提前結束休假
+#[poise::command(slash_command, rename = "結束", ephemeral)]
+async fn end_vacation(
+    ctx: Context<'_>,
+    #[rename = "世界"]
+    #[description = "遊戲世界，留空則使用頻道預設的世界"]
+    #[autocomplete = "autocomplete_world"]
+    world: Option<String>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let Some(world_id) = resolve_world(ctx, world).await? else {
+        return Ok(());
+    };
+    let user_id = ctx.author().id.get();
+    let locale = i18n::locale(ctx).await;
+
+    if ctx
+        .data()
+        .end_vacation(guild_id, world_id, user_id, Utc::now())
+        .await?
+    {
+        ctx.reply(tr!(locale, "vacation.ended")).await?;
+    } else {
+        ctx.reply(tr!(locale, "vacation.not_on_leave")).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap()
+    }
+
+    fn vacation(end_time: DateTime<Utc>, delegate_user_id: Option<u64>) -> VacationData {
+        VacationData {
+            guild_id: 1,
+            world_id: 0,
+            user_id: 10,
+            end_time,
+            delegate_user_id,
+        }
+    }
+
+    fn occupation(user_id: u64, battle_user_id: Option<u64>) -> OccupyData {
+        OccupyData {
+            user_id,
+            battle_user_id,
+            ..OccupyData::fixture(day(1))
+        }
+    }
+
+    #[test]
+    fn only_active_protected_vacation_protects() {
+        assert!(protects(&vacation(day(5), None), day(3)));
+        assert!(!protects(&vacation(day(5), None), day(5)));
+        // 指定代理人時礦點已交出，不再保護
+        assert!(!protects(&vacation(day(5), Some(20)), day(3)));
+    }
+
+    #[test]
+    fn protection_limited_to_max_days() {
+        assert!(!exceeds_max_days(day(8), day(1), 7).unwrap());
+        assert!(exceeds_max_days(day(9), day(1), 7).unwrap());
+    }
+
+    #[test]
+    fn pending_challenge_blocks_protection() {
+        let occupations = [occupation(10, None), occupation(30, Some(10))];
+        assert!(!in_challenge(&occupations, 10));
+        assert!(in_challenge(&occupations, 30));
+    }
+}
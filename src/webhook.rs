@@ -0,0 +1,361 @@
+use crate::{
+    commands::Context,
+    confirm,
+    db::{BotDB, WebhookData},
+    events::{self, OccupyEvent},
+    i18n::{self, tr},
+    permission::require_admin,
+};
+use anyhow::{Context as _, Result};
+use hmac::{Hmac, Mac};
+use poise::{
+    serenity_prelude::{Color, CreateEmbed},
+    CreateReply,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use reqwest::{Client, Url};
+use sha2::Sha256;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+/// 傳送失敗時的最大嘗試次數
+const MAX_ATTEMPTS: u32 = 5;
+/// 第一次重試前的等待時間，之後每次加倍
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+
+/// 將事件以簽章過的 JSON 傳送至伺服器設定的 webhook
+pub async fn run(db: BotDB) {
+    let client = Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("Failed to build http client");
+    let mut receiver = events::subscribe();
+
+    loop {
+        let event = match receiver.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(count)) => {
+                tracing::warn!("webhook skipped {count} events");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        let webhooks = match db.get_webhooks(event.guild_id).await {
+            Ok(webhooks) => webhooks,
+            Err(err) => {
+                tracing::error!("{err}");
+                continue;
+            }
+        };
+        for webhook in webhooks {
+            tokio::spawn(deliver(
+                client.clone(),
+                webhook,
+                event.clone(),
+                INITIAL_BACKOFF,
+            ));
+        }
+    }
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// 傳送事件，失敗時等待 `backoff` 後重試，每次等待時間加倍
+async fn deliver(client: Client, webhook: WebhookData, event: OccupyEvent, backoff: Duration) {
+    let body = match serde_json::to_vec(&event) {
+        Ok(body) => body,
+        Err(err) => {
+            tracing::error!("{err}");
+            return;
+        }
+    };
+    let signature = format!("sha256={}", sign(&webhook.secret, &body));
+
+    let mut backoff = backoff;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let result = client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header("X-Occupy-Event", event.event.as_str())
+            .header("X-Occupy-Signature", &signature)
+            .body(body.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        match result {
+            Ok(_) => return,
+            Err(err) if attempt == MAX_ATTEMPTS => {
+                tracing::error!("webhook {} failed: {err}", webhook.id);
+            }
+            Err(err) => {
+                tracing::warn!("webhook {} attempt {attempt} failed: {err}", webhook.id);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+    }
+}
+
+/// 管理傳送佔領事件的 webhook
+#[poise::command(
+    slash_command,
+    rename = "webhook",
+    check = "require_admin",
+    subcommands("create_webhook", "delete_webhook", "list_webhooks"),
+    subcommand_required
+)]
+pub async fn webhook(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 新增 webhook，事件會以 HMAC-SHA256 簽章後 POST 至網址
+#[poise::command(slash_command, rename = "新增", ephemeral)]
+async fn create_webhook(
+    ctx: Context<'_>,
+    #[max_length = 2048]
+    #[rename = "網址"]
+    #[description = "接收事件的 http 或 https 網址"]
+    url: String,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let locale = i18n::locale(ctx).await;
+
+    let valid = Url::parse(&url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+    if !valid {
+        ctx.reply(tr!(locale, "webhook.invalid_url")).await?;
+        return Ok(());
+    }
+
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    let id = ctx.data().create_webhook(guild_id, &url, &secret).await?;
+
+    ctx.reply(tr!(locale, "webhook.created", id = id, secret = secret))
+        .await?;
+    Ok(())
+}
+
+/// 刪除 webhook
+#[poise::command(slash_command, rename = "刪除", ephemeral)]
+async fn delete_webhook(
+    ctx: Context<'_>,
+    #[rename = "編號"]
+    #[description = "webhook 編號"]
+    id: i32,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let locale = i18n::locale(ctx).await;
+
+    let embed = confirm::embed(
+        tr!(locale, "confirm.delete_webhook"),
+        tr!(locale, "confirm.delete_webhook.description", id = id),
+    );
+    if !confirm::confirm(ctx, embed).await? {
+        return Ok(());
+    }
+
+    if ctx.data().delete_webhook(guild_id, id).await? {
+        ctx.reply(tr!(locale, "webhook.deleted", id = id)).await?;
+    } else {
+        ctx.reply(tr!(locale, "webhook.not_found")).await?;
+    }
+    Ok(())
+}
+
+/// 列出所有 webhook
+#[poise::command(slash_command, rename = "列表", ephemeral)]
+async fn list_webhooks(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let webhooks = ctx.data().get_webhooks(guild_id).await?;
+    let locale = i18n::locale(ctx).await;
+
+    let description = if webhooks.is_empty() {
+        tr!(locale, "webhook.empty")
+    } else {
+        webhooks
+            .iter()
+            .map(|webhook| format!("#{} {}", webhook.id, webhook.url))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .color(Color::BLUE)
+                    .title(tr!(locale, "webhook.list"))
+                    .description(description),
+            )
+            .reply(true)
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventKind;
+    use axum::{
+        body::Bytes,
+        extract::State,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Router,
+    };
+    use chrono::Utc;
+    use std::{
+        net::{SocketAddr, TcpListener},
+        sync::{Arc, Mutex},
+        time::Instant,
+    };
+
+    const BACKOFF: Duration = Duration::from_millis(20);
+
+    /// 本機接收端，前 `failures` 次請求回傳 500
+    #[derive(Clone, Default)]
+    struct Receiver {
+        failures: usize,
+        requests: Arc<Mutex<Vec<(Instant, HeaderMap, Bytes)>>>,
+    }
+
+    async fn receive(
+        State(receiver): State<Receiver>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> StatusCode {
+        let mut requests = receiver.requests.lock().unwrap();
+        requests.push((Instant::now(), headers, body));
+        if requests.len() <= receiver.failures {
+            StatusCode::INTERNAL_SERVER_ERROR
+        } else {
+            StatusCode::OK
+        }
+    }
+
+    fn serve(receiver: Receiver) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/", post(receive)).with_state(receiver);
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        addr
+    }
+
+    fn webhook(addr: SocketAddr) -> WebhookData {
+        WebhookData {
+            id: 1,
+            url: format!("http://{addr}/"),
+            secret: String::from("secret"),
+        }
+    }
+
+    fn event() -> OccupyEvent {
+        OccupyEvent {
+            event: EventKind::Resolved,
+            guild_id: 1234,
+            world_id: 1,
+            ore_point_id: 42,
+            user_id: 5678,
+            target_user_id: Some(9012),
+            time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn sign_matches_hmac_sha256() {
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[tokio::test]
+    async fn deliver_signs_payload() {
+        let receiver = Receiver::default();
+        let addr = serve(receiver.clone());
+        let webhook = webhook(addr);
+
+        deliver(Client::new(), webhook.clone(), event(), BACKOFF).await;
+
+        let requests = receiver.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let (_, headers, body) = &requests[0];
+        assert_eq!(headers["content-type"], "application/json");
+        assert_eq!(headers["x-occupy-event"], "resolved");
+
+        let signature = headers["x-occupy-signature"].to_str().unwrap();
+        let signature = hex::decode(signature.strip_prefix("sha256=").unwrap()).unwrap();
+        let mut mac = Hmac::<Sha256>::new_from_slice(webhook.secret.as_bytes()).unwrap();
+        mac.update(body);
+        assert!(mac.verify_slice(&signature).is_ok());
+
+        let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload["event"], "resolved");
+        assert_eq!(payload["guild_id"], 1234);
+        assert_eq!(payload["world_id"], 1);
+        assert_eq!(payload["ore_point_id"], 42);
+        assert_eq!(payload["user_id"], 5678);
+        assert_eq!(payload["target_user_id"], 9012);
+        assert!(payload["time"].is_string());
+    }
+
+    #[tokio::test]
+    async fn deliver_retries_with_backoff() {
+        let receiver = Receiver {
+            failures: 2,
+            ..Default::default()
+        };
+        let addr = serve(receiver.clone());
+
+        deliver(Client::new(), webhook(addr), event(), BACKOFF).await;
+
+        let requests = receiver.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].0 - requests[0].0 >= BACKOFF);
+        assert!(requests[2].0 - requests[1].0 >= BACKOFF * 2);
+        // 每次重試的內容與簽章相同
+        assert_eq!(requests[0].2, requests[2].2);
+        assert_eq!(
+            requests[0].1["x-occupy-signature"],
+            requests[2].1["x-occupy-signature"]
+        );
+    }
+
+    #[tokio::test]
+    async fn deliver_gives_up_after_max_attempts() {
+        let receiver = Receiver {
+            failures: usize::MAX,
+            ..Default::default()
+        };
+        let addr = serve(receiver.clone());
+
+        deliver(
+            Client::new(),
+            webhook(addr),
+            event(),
+            Duration::from_millis(1),
+        )
+        .await;
+
+        assert_eq!(
+            receiver.requests.lock().unwrap().len(),
+            MAX_ATTEMPTS as usize
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use crate::{
+    commands::Context,
+    confirm,
+    i18n::{self, tr},
+    permission::require_admin,
+};
+use anyhow::{Context as _, Result};
+use poise::{
+    serenity_prelude::{Color, CreateEmbed},
+    CreateReply,
+};
+
+/// 未建立世界的伺服器使用的預設世界
+pub const DEFAULT_WORLD_ID: i32 = 0;
+pub const DEFAULT_WORLD_NAME: &str = "預設";
+
+/// 取得指令使用的世界，未指定時使用頻道綁定的世界，找不到世界時回覆並回傳 None
+pub async fn resolve_world(ctx: Context<'_>, name: Option<String>) -> Result<Option<i32>> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let db = ctx.data();
+
+    let world_id = match name {
+        Some(name) if name == DEFAULT_WORLD_NAME => Some(DEFAULT_WORLD_ID),
+        Some(name) => db.get_world_id(guild_id, &name).await?,
+        None => Some(
+            db.get_channel_world(ctx.channel_id().get())
+                .await?
+                .unwrap_or(DEFAULT_WORLD_ID),
+        ),
+    };
+    if world_id.is_none() {
+        let locale = i18n::locale(ctx).await;
+        ctx.reply(tr!(locale, "world.not_found")).await?;
+    }
+    Ok(world_id)
+}
+
+/// 取得世界名稱，世界已被刪除時回覆並回傳 None
+pub async fn world_name(ctx: Context<'_>, world_id: i32) -> Result<Option<String>> {
+    if world_id == DEFAULT_WORLD_ID {
+        return Ok(Some(DEFAULT_WORLD_NAME.to_string()));
+    }
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let name = ctx.data().get_world_name(guild_id, world_id).await?;
+    if name.is_none() {
+        let locale = i18n::locale(ctx).await;
+        ctx.reply(tr!(locale, "world.not_found")).await?;
+    }
+    Ok(name)
+}
+
+pub async fn autocomplete_world(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Vec::new();
+    };
+    let worlds = ctx
+        .data()
+        .get_worlds(guild_id.get())
+        .await
+        .unwrap_or_default();
+    world_choices(worlds.into_iter().map(|(_, name)| name), partial)
+}
+
+/// 符合輸入的世界名稱，預設世界排在最前面
+fn world_choices(worlds: impl IntoIterator<Item = String>, partial: &str) -> Vec<String> {
+    std::iter::once(DEFAULT_WORLD_NAME.to_string())
+        .chain(worlds)
+        .filter(|name| name.contains(partial))
+        .collect()
+}
+
+/// 管理伺服器的遊戲世界
+#[poise::command(
+    slash_command,
+    rename = "世界",
+    check = "require_admin",
+    subcommands(
+        "create_world",
+        "delete_world",
+        "bind_world",
+        "unbind_world",
+        "list_worlds"
+    ),
+    subcommand_required
+)]
+pub async fn world(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// 新增世界
+#[poise::command(slash_command, rename = "新增", ephemeral)]
+async fn create_world(
+    ctx: Context<'_>,
+    #[max_length = 255]
+    #[rename = "名稱"]
+    #[description = "世界名稱"]
+    name: String,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let db = ctx.data();
+    let locale = i18n::locale(ctx).await;
+
+    if name == DEFAULT_WORLD_NAME || db.get_world_id(guild_id, &name).await?.is_some() {
+        ctx.reply(tr!(locale, "world.exists")).await?;
+        return Ok(());
+    }
+
+    db.create_world(guild_id, &name).await?;
+    ctx.reply(tr!(locale, "world.created", name = name)).await?;
+    Ok(())
+}
+
+/// 刪除世界
+#[poise::command(slash_command, rename = "刪除", ephemeral)]
+async fn delete_world(
+    ctx: Context<'_>,
+    #[rename = "名稱"]
+    #[description = "世界名稱"]
+    #[autocomplete = "autocomplete_world"]
+    name: String,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let db = ctx.data();
+
+    let world_id = db
+        .get_world_id(guild_id, &name)
+        .await?
+        .context("找不到世界")?;
+
+    let locale = i18n::locale(ctx).await;
+    let embed = confirm::embed(
+        tr!(locale, "confirm.delete_world"),
+        tr!(locale, "confirm.delete_world.description", name = name),
+    );
+    if !confirm::confirm(ctx, embed).await? {
+        return Ok(());
+    }
+
+    if db.delete_world(guild_id, world_id).await? {
+        ctx.reply(tr!(locale, "world.deleted", name = name)).await?;
+    } else {
+        ctx.reply(tr!(locale, "world.in_use")).await?;
+    }
+    Ok(())
+}
+
+/// 將目前頻道綁定至世界
+#[poise::command(slash_command, rename = "綁定", ephemeral)]
+async fn bind_world(
+    ctx: Context<'_>,
+    #[rename = "名稱"]
+    #[description = "世界名稱"]
+    #[autocomplete = "autocomplete_world"]
+    name: String,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let channel_id = ctx.channel_id().get();
+    let Some(world_id) = resolve_world(ctx, Some(name.clone())).await? else {
+        return Ok(());
+    };
+
+    ctx.data()
+        .bind_channel_world(guild_id, channel_id, world_id)
+        .await?;
+    let locale = i18n::locale(ctx).await;
+    ctx.reply(tr!(
+        locale,
+        "world.bound",
+        channel = format!("<#{channel_id}>"),
+        name = name
+    ))
+    .await?;
+    Ok(())
+}
+
+/// 解除目前頻道的世界綁定
+#[poise::command(slash_command, rename = "解除綁定", ephemeral)]
+async fn unbind_world(ctx: Context<'_>) -> Result<()> {
+    let channel_id = ctx.channel_id().get();
+
+    ctx.data().unbind_channel_world(channel_id).await?;
+    let locale = i18n::locale(ctx).await;
+    ctx.reply(tr!(
+        locale,
+        "world.unbound",
+        channel = format!("<#{channel_id}>")
+    ))
+    .await?;
+    Ok(())
+}
+
+/// 列出所有世界
+#[poise::command(slash_command, rename = "列表", ephemeral)]
+async fn list_worlds(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx.guild_id().context("Missing guild id")?.get();
+    let worlds = ctx.data().get_worlds(guild_id).await?;
+    let locale = i18n::locale(ctx).await;
+
+    let description = std::iter::once(DEFAULT_WORLD_NAME.to_string())
+        .chain(worlds.into_iter().map(|(_, name)| name))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .color(Color::BLUE)
+                    .title(tr!(locale, "world.list"))
+                    .description(description),
+            )
+            .reply(true)
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choices_include_default_world() {
+        let worlds = || ["一服".to_string(), "二服".to_string()];
+        assert_eq!(
+            world_choices(worlds(), ""),
+            [DEFAULT_WORLD_NAME, "一服", "二服"]
+        );
+        assert_eq!(world_choices(worlds(), "二"), ["二服"]);
+        assert_eq!(world_choices(Vec::new(), ""), [DEFAULT_WORLD_NAME]);
+    }
+}